@@ -0,0 +1,211 @@
+use super::neural_network_game::*;
+use super::neural_network_game::game::*;
+use super::statistics::*;
+use super::tournament::*;
+
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufWriter;
+use std::path::Path;
+
+// The name benchmark results are saved under in the output directory of a run.
+pub const BENCHMARK_FILE_NAME: &str = "benchmark.csv";
+
+// How the champion did against one member of the panel.
+pub struct BenchmarkResult
+{
+	pub opponent: String,
+
+	// Checkpoints change as training goes, so their results are only logged and don't count towards the score.
+	pub checkpoint: bool,
+
+	pub games: usize,
+
+	// Wins are shared between players who tie for first.
+	pub wins: f64,
+
+	pub scores: Vec<f64>,
+}
+
+impl BenchmarkResult
+{
+	pub fn get_win_rate(&self) -> f64
+	{
+		if self.games == 0 { 0.0 } else { self.wins / self.games as f64 }
+	}
+
+	pub fn get_win_rate_interval(&self) -> (f64, f64)
+	{
+		wilson_interval(self.wins, self.games as f64)
+	}
+}
+
+// A fixed set of opponents, joined by the most recent checkpoints of the run, that every champion is measured against
+// on the same deals.
+pub struct BenchmarkPanel
+{
+	opponents: Vec<TournamentEntrant>,
+	checkpoints: Vec<TournamentEntrant>,
+	max_checkpoints: usize,
+	games: usize,
+	first_seed: u64,
+}
+
+impl BenchmarkPanel
+{
+	pub fn new(opponents: Vec<TournamentEntrant>, max_checkpoints: usize, games: usize, first_seed: u64) -> BenchmarkPanel
+	{
+		BenchmarkPanel{opponents, checkpoints: Vec::new(), max_checkpoints, games, first_seed}
+	}
+
+	// Adds a champion to the panel, pushing out the oldest checkpoint if the panel is full.
+	pub fn add_checkpoint(&mut self, name: &str, player: &NeuralNetworkGamePlayer)
+	{
+		if self.max_checkpoints == 0
+		{
+			return;
+		}
+
+		if self.checkpoints.len() == self.max_checkpoints
+		{
+			self.checkpoints.remove(0);
+		}
+
+		let mut player = player.clone();
+		player.name = name.to_string();
+
+		self.checkpoints.push(TournamentEntrant{name: name.to_string(), player});
+	}
+
+	// Plays the champion against every member of the panel in turn, with the member filling all the other seats.
+	pub fn run(&self, champion: &NeuralNetworkGamePlayer) -> Vec<BenchmarkResult>
+	{
+		let mut results = Vec::new();
+
+		let panel = self.opponents.iter().map(|x| (x, false)).chain(self.checkpoints.iter().map(|x| (x, true)));
+
+		for (x, checkpoint) in panel
+		{
+			let games = play_champion_games(champion, "champion", std::slice::from_ref(x), self.games, self.first_seed);
+
+			let mut result = BenchmarkResult{opponent: x.name.clone(), checkpoint, games: games.len(), wins: 0.0, scores: Vec::new()};
+
+			for (i, game) in games.iter().enumerate()
+			{
+				let scores = game.get_game().get_final_scores();
+				let seat = i % NUMBER_OF_PLAYERS;
+
+				result.wins += get_placements(&scores).0[seat];
+				result.scores.push(scores[seat] as f64);
+			}
+
+			results.push(result);
+		}
+
+		results
+	}
+}
+
+// A single number to compare champions by, the champion's average win rate against the fixed opponents. These are
+// played on the same deals every time, so scores from different generations can be compared.
+pub fn get_benchmark_score(results: &[BenchmarkResult]) -> f64
+{
+	mean(&results.iter().filter(|x| !x.checkpoint).map(|x| x.get_win_rate()).collect::<Vec<f64>>())
+}
+
+// Plays the champion against the opponents on consecutive seeded deals. The champion moves around the table and the
+// opponents take turns filling the other seats.
+pub fn play_champion_games(champion: &NeuralNetworkGamePlayer, name: &str, opponents: &[TournamentEntrant], number_of_games: usize, first_seed: u64) -> Vec<NeuralNetworkGame>
+{
+	let mut games = Vec::new();
+
+	for i in 0..number_of_games
+	{
+		let mut game = SushiGoGame::new(NUMBER_OF_PLAYERS);
+		game.setup_with_seed(first_seed.wrapping_add(i as u64));
+
+		let mut players: Vec<NeuralNetworkGamePlayer> = (0..NUMBER_OF_PLAYERS - 1).map(|j| opponents[j % opponents.len()].player.clone()).collect();
+
+		let mut player = champion.clone();
+		player.name = name.to_string();
+		players.insert(i % NUMBER_OF_PLAYERS, player);
+
+		games.push(NeuralNetworkGame::new(game, players));
+	}
+
+	play_games_parallel(&mut games);
+
+	games
+}
+
+// Writes a row for every panel member each time the champion is benchmarked.
+pub struct BenchmarkWriter
+{
+	writer: BufWriter<File>,
+}
+
+impl BenchmarkWriter
+{
+	pub fn new(file_name: &Path) -> io::Result<BenchmarkWriter>
+	{
+		let mut writer = BufWriter::new(File::create(file_name)?);
+
+		writeln!(writer, "generation,opponent,checkpoint,games,win_rate,win_rate_low,win_rate_high,mean_score")?;
+
+		Ok(BenchmarkWriter{writer})
+	}
+
+	pub fn write(&mut self, generation: usize, results: &[BenchmarkResult]) -> io::Result<()>
+	{
+		for x in results
+		{
+			let (low, high) = x.get_win_rate_interval();
+
+			// Opponents can be file names, so quote them in case they contain a comma.
+			writeln!(self.writer, "{},\"{}\",{},{},{},{},{},{}", generation, x.opponent.replace('"', "\"\""), x.checkpoint, x.games, x.get_win_rate(), low, high, mean(&x.scores))?;
+		}
+
+		self.writer.flush()
+	}
+}
+
+pub fn print_benchmark(generation: usize, results: &[BenchmarkResult])
+{
+	println!("\nBenchmark of the generation {} champion:", generation);
+
+	for x in results
+	{
+		let (low, high) = x.get_win_rate_interval();
+
+		let name = if x.checkpoint { format!("{} (checkpoint)", x.opponent) } else { x.opponent.clone() };
+
+		println!("{:<40} won {:>6.2}% (95% CI {:.2}-{:.2}), mean score {:.2}", name, x.get_win_rate() * 100.0, low * 100.0, high * 100.0, mean(&x.scores));
+	}
+
+	println!("{:<40} {:>10.2}%", "Benchmark score (fixed opponents)", get_benchmark_score(results) * 100.0);
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn result(opponent: &str, checkpoint: bool, wins: f64) -> BenchmarkResult
+	{
+		BenchmarkResult{opponent: opponent.to_string(), checkpoint, games: 10, wins, scores: Vec::new()}
+	}
+
+	#[test]
+	fn score_ignores_checkpoints()
+	{
+		let fixed = vec![result("random", false, 8.0), result("heuristic", false, 4.0)];
+
+		let mut with_checkpoints = vec![result("random", false, 8.0), result("heuristic", false, 4.0)];
+		with_checkpoints.push(result("generation 9", true, 0.0));
+		with_checkpoints.push(result("generation 19", true, 10.0));
+
+		assert!((get_benchmark_score(&fixed) - 0.6).abs() < 1e-9);
+		assert_eq!(get_benchmark_score(&fixed).to_bits(), get_benchmark_score(&with_checkpoints).to_bits());
+	}
+}
@@ -0,0 +1,58 @@
+// Opens a window to play a game against a saved network, or against random players if no network is given. The same
+// as `sushi_go_ai play --humans 1 --network FILE`, for starting from a shortcut.
+//
+// Usage: sushi_go_gui [NETWORK]
+
+extern crate sushi_go_ai;
+extern crate env_logger;
+
+use sushi_go_ai::neural_network_game::*;
+use sushi_go_ai::neural_network_game::neural_network::*;
+use sushi_go_ai::neural_network_game::policy::*;
+
+use std::env;
+use std::process;
+
+fn main()
+{
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+	let network = env::args().nth(1).unwrap_or_default();
+
+	if network == "--help" || network == "-h"
+	{
+		println!("Usage: sushi_go_gui [NETWORK]\n\nPlays a game in a window against a saved network, or against random players if no network is given.");
+		return;
+	}
+
+	// Without a network the seats are played by random players.
+	let player = if network.is_empty()
+	{
+		None
+	}
+	else
+	{
+		match load_nn_from_file(&network)
+		{
+			Ok(nn) =>
+			{
+				let mut player = NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, nn, PolicyMode::default());
+				player.name = network.clone();
+				Some(player)
+			}
+			Err(e) =>
+			{
+				eprintln!("Couldn't load {}: {}", network, e);
+				process::exit(1);
+			}
+		}
+	};
+
+	let random = if player.is_none() { NUMBER_OF_PLAYERS - 1 } else { 0 };
+
+	if let Err(e) = play_batches(1, 1, player.as_ref(), random, 1, &[], DisplayMode::Window, None, None, None, None)
+	{
+		eprintln!("Couldn't show the game: {}", e);
+		process::exit(1);
+	}
+}
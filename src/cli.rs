@@ -0,0 +1,974 @@
+use sushi_go_ai::neural_network_game::*;
+use sushi_go_ai::neural_network_game::model::*;
+use sushi_go_ai::neural_network_game::neural_network::*;
+use sushi_go_ai::neural_network_game::policy::*;
+use sushi_go_ai::neural_network_game::explanation::*;
+use sushi_go_ai::neural_network_game::spectator::*;
+use sushi_go_ai::neural_network_game::advisor::*;
+use sushi_go_ai::neural_network_game::external_bot::*;
+use sushi_go_ai::statistics::*;
+use sushi_go_ai::rating::*;
+use sushi_go_ai::tournament::*;
+use sushi_go_ai::evaluation::*;
+use sushi_go_ai::gen_algorithm::*;
+use sushi_go_ai::training_config::*;
+use sushi_go_ai::replay_viewer::*;
+use sushi_go_ai::server::*;
+use sushi_go_ai::client::*;
+use sushi_go_ai::http_api::*;
+use sushi_go_ai::render::*;
+use sushi_go_ai::neural_network_game::replay::*;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use std::time::Instant;
+
+// The arguments every command which plays networks takes.
+fn policy_arg<'a, 'b>() -> Arg<'a, 'b>
+{
+	Arg::with_name("policy")
+		.long("policy")
+		.value_name("POLICY")
+		.default_value("argmax")
+		.validator(is_policy)
+		.help("How networks pick their cards: argmax, temperature=<t> or epsilon=<e>")
+}
+
+fn address_arg<'a, 'b>() -> Arg<'a, 'b>
+{
+	Arg::with_name("address")
+		.long("address")
+		.value_name("HOST:PORT")
+		.default_value("127.0.0.1:7878")
+		.help("The address games are hosted on")
+}
+
+fn seed_arg<'a, 'b>() -> Arg<'a, 'b>
+{
+	Arg::with_name("seed")
+		.long("seed")
+		.value_name("SEED")
+		.default_value("0")
+		.validator(is_u64)
+		.help("The seed of the first deal, so results can be reproduced")
+}
+
+fn usize_arg<'a, 'b>(name: &'a str, default: &'a str, help: &'a str) -> Arg<'a, 'b>
+{
+	Arg::with_name(name)
+		.long(name)
+		.value_name("N")
+		.default_value(default)
+		.validator(is_usize)
+		.help(help)
+}
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b>
+{
+	App::new("sushi_go_ai")
+		.about("Trains and plays neural networks at Sushi Go.")
+		.setting(AppSettings::SubcommandRequiredElseHelp)
+		.setting(AppSettings::VersionlessSubcommands)
+		.arg(Arg::with_name("log-level")
+			.long("log-level")
+			.value_name("FILTER")
+			.global(true)
+			.help("What to log: off, error, warn, info, debug or trace, optionally per module such as \
+				'sushi_go_ai::neural_network_game::game=trace'. Defaults to RUST_LOG, or warn if that isn't set"))
+		.subcommand(SubCommand::with_name("play")
+			.about("Plays games between humans, random players and a saved network")
+			.arg(usize_arg("humans", "0", "How many players are human"))
+			.arg(usize_arg("random", "0", "How many players pick random cards"))
+			.arg(Arg::with_name("bot")
+				.long("bot")
+				.value_name("COMMAND")
+				.multiple(true)
+				.number_of_values(1)
+				.help("Runs a bot in another program for a seat, talking to it with JSON over stdin and stdout. Can be given more than once"))
+			.arg(Arg::with_name("network")
+				.long("network")
+				.value_name("FILE")
+				.help("The saved network which fills the remaining seats"))
+			.arg(usize_arg("games", "1", "How many games to play in each batch"))
+			.arg(usize_arg("batches", "1", "How many batches to play, smaller batches use less memory"))
+			.arg(policy_arg())
+			.arg(Arg::with_name("display")
+				.long("display")
+				.value_name("DISPLAY")
+				.default_value(if cfg!(feature = "gui") { "window" } else { "terminal" })
+				.validator(is_display_mode)
+				.help("How games with humans or spectators are shown: 'window' or 'terminal', which works over SSH without a display"))
+			.arg(Arg::with_name("spectate")
+				.long("spectate")
+				.help("Shows a game between bots turn by turn with why each bot picked its card, instead of playing at full speed"))
+			.arg(Arg::with_name("turn-delay")
+				.long("turn-delay")
+				.value_name("MS")
+				.default_value("500")
+				.validator(is_u64)
+				.help("How many milliseconds each turn is shown for when spectating"))
+			.arg(Arg::with_name("paused")
+				.long("paused")
+				.requires("spectate")
+				.help("Starts spectating paused, so turns are only played when stepped through"))
+			.arg(Arg::with_name("hints")
+				.long("hints")
+				.help("Lets humans ask an advisor which card to pick, press h in the terminal or click the hint button in the window"))
+			.arg(Arg::with_name("hint-network")
+				.long("hint-network")
+				.value_name("FILE")
+				.requires("hints")
+				.help("A saved network for the advisor to ask, which also plays out its rollouts instead of the heuristic player"))
+			.arg(usize_arg("hint-rollouts", "100", "How many times the advisor plays out the rest of the game for each card, 0 to only ask --hint-network"))
+			.arg(Arg::with_name("replay")
+				.long("replay")
+				.value_name("FILE")
+				.help("Records every game and appends them to a JSON lines replay file")))
+		.subcommand(SubCommand::with_name("train")
+			.about("Trains a new network with the genetic algorithm")
+			.arg(Arg::with_name("config")
+				.long("config")
+				.value_name("FILE")
+				.help("A TOML training config, any other options given override it"))
+			.arg(Arg::with_name("name")
+				.long("name")
+				.value_name("NAME")
+				.help("The name of the run [default: generated from the time]"))
+			.arg(Arg::with_name("output-dir")
+				.long("output-dir")
+				.value_name("DIR")
+				.help("The directory the run's folder is created in [default: runs]"))
+			.arg(Arg::with_name("seed")
+				.long("seed")
+				.value_name("SEED")
+				.validator(is_u64)
+				.help("Seeds the run so it can be repeated [default: random]"))
+			.arg(Arg::with_name("games")
+				.long("games")
+				.value_name("N")
+				.validator(is_usize)
+				.help("How many games are played each generation [default: 64]"))
+			.arg(Arg::with_name("generations")
+				.long("generations")
+				.value_name("N")
+				.validator(is_usize)
+				.help("How many generations to train for [default: 1000]"))
+			.arg(Arg::with_name("model")
+				.long("model")
+				.value_name("MODEL")
+				.validator(is_model)
+				.help("The network model to train [default: model_c]"))
+			.arg(Arg::with_name("hidden-nodes")
+				.long("hidden-nodes")
+				.value_name("N")
+				.validator(is_usize)
+				.help("Overrides the number of hidden nodes the model uses"))
+			.arg(Arg::with_name("mutate-amount")
+				.long("mutate-amount")
+				.value_name("AMOUNT")
+				.validator(is_f32)
+				.help("How far each weight can move when a network is mutated [default: 0.01]"))
+			.arg(Arg::with_name("policy")
+				.long("policy")
+				.value_name("POLICY")
+				.validator(is_policy)
+				.help("How networks pick their cards: argmax, temperature=<t> or epsilon=<e> [default: argmax]"))
+			.arg(Arg::with_name("format")
+				.long("format")
+				.value_name("FORMAT")
+				.validator(is_save_format)
+				.help("The format the trained network is saved in: json or binary [default: json]"))
+			.arg(Arg::with_name("metrics-format")
+				.long("metrics-format")
+				.value_name("FORMAT")
+				.possible_values(&["csv", "jsonl"])
+				.help("The format the metrics of each generation are written in [default: csv]"))
+			.arg(Arg::with_name("benchmark-interval")
+				.long("benchmark-interval")
+				.value_name("N")
+				.validator(is_usize)
+				.help("How many generations between benchmarking the champion, 0 turns it off [default: 10]"))
+			.arg(Arg::with_name("keep-best")
+				.long("keep-best")
+				.help("Also saves the champion which did best against the fixed benchmark opponents"))
+			.arg(Arg::with_name("live-summary")
+				.long("live-summary")
+				.help("Prints the metrics of the run as it trains")))
+		.subcommand(SubCommand::with_name("evaluate")
+			.about("Tests whether one agent is stronger than another")
+			.arg(Arg::with_name("agents")
+				.value_name("AGENT")
+				.required(true)
+				.multiple(true)
+				.min_values(2)
+				.max_values(NUMBER_OF_PLAYERS as u64)
+				.help("'random', 'heuristic', 'bot:COMMAND' to run a bot in another program, or the file name of a saved network"))
+			.arg(Arg::with_name("mode")
+				.long("mode")
+				.value_name("MODE")
+				.possible_values(&["head_to_head", "duplicate"])
+				.default_value("head_to_head")
+				.help("head_to_head compares two agents on paired deals, duplicate rotates every agent through every seat"))
+			.arg(usize_arg("deals", "1000", "The most deals to play"))
+			.arg(seed_arg())
+			.arg(Arg::with_name("sprt")
+				.long("sprt")
+				.value_name("ELO0,ELO1")
+				.validator(is_sprt_bounds)
+				.help("Stops a head to head match once a sequential test between the two Elo differences is decided"))
+			.arg(policy_arg()))
+		.subcommand(SubCommand::with_name("tournament")
+			.about("Plays saved networks against each other and ranks them")
+			.arg(Arg::with_name("entrants")
+				.value_name("ENTRANT")
+				.required(true)
+				.multiple(true)
+				.help("'random', 'heuristic', 'bot:COMMAND' to run a bot in another program, or the file name of a saved network"))
+			.arg(Arg::with_name("format")
+				.long("format")
+				.value_name("FORMAT")
+				.default_value("round_robin")
+				.validator(is_tournament_format)
+				.help("round_robin or swiss=<rounds>"))
+			.arg(usize_arg("games-per-table", "100", "How many games are played at each table"))
+			.arg(seed_arg())
+			.arg(policy_arg()))
+		.subcommand(SubCommand::with_name("inspect")
+			.about("Prints the header of a saved network, see explain for how it picks its cards")
+			.arg(Arg::with_name("file")
+				.value_name("FILE")
+				.required(true)
+				.help("The saved network")))
+		.subcommand(SubCommand::with_name("explain")
+			.about("Reports which cards a saved network prefers in a sample of positions, and which inputs those choices depend on")
+			.arg(Arg::with_name("file")
+				.value_name("FILE")
+				.required(true)
+				.help("The saved network"))
+			.arg(usize_arg("positions", "20", "How many positions to explain, each dealt from the next seed"))
+			.arg(usize_arg("top-inputs", "5", "How many of the most important inputs to list"))
+			.arg(seed_arg()))
+		.subcommand(SubCommand::with_name("replay")
+			.about("Steps through a recorded game, checking it reproduces the recorded scores")
+			.arg(Arg::with_name("file")
+				.value_name("FILE")
+				.required(true)
+				.help("A JSON lines replay file written by play --replay"))
+			.arg(usize_arg("game", "0", "Which game in the file to step through, counting from 0"))
+			.arg(Arg::with_name("verify")
+				.long("verify")
+				.help("Checks every game in the file reproduces without stepping through them")))
+		.subcommand(SubCommand::with_name("render")
+			.about("Draws a recorded game to PNG images, one for each turn, without needing a window or GPU")
+			.arg(Arg::with_name("file")
+				.value_name("FILE")
+				.required(true)
+				.help("A JSON lines replay file written by play --replay"))
+			.arg(usize_arg("game", "0", "Which game in the file to draw, counting from 0"))
+			.arg(Arg::with_name("turn")
+				.long("turn")
+				.value_name("N")
+				.validator(is_usize)
+				.help("Only draws this turn, counting from 0"))
+			.arg(Arg::with_name("output-dir")
+				.long("output-dir")
+				.value_name("DIR")
+				.default_value("frames")
+				.help("Where to write the images, created if it doesn't exist")))
+		.subcommand(SubCommand::with_name("serve")
+			.about("Hosts games over TCP for humans and bots in other processes, using a line delimited JSON protocol")
+			.arg(address_arg())
+			.arg(usize_arg("remote", "1", "How many seats at each table are for players who connect"))
+			.arg(Arg::with_name("network")
+				.long("network")
+				.value_name("FILE")
+				.help("A saved network to fill the seats nobody connects to, random players fill them if not given"))
+			.arg(policy_arg())
+			.arg(usize_arg("turn-timeout", "120", "How many seconds a player has to pick a card before a random player takes their seat"))
+			.arg(Arg::with_name("games")
+				.long("games")
+				.value_name("N")
+				.validator(is_usize)
+				.help("Stops after this many games, otherwise keeps hosting games until stopped")))
+		.subcommand(SubCommand::with_name("connect")
+			.about("Joins a game hosted with serve, picking cards in the terminal or letting a bot play")
+			.arg(address_arg())
+			.arg(Arg::with_name("name")
+				.long("name")
+				.value_name("NAME")
+				.default_value("remote")
+				.help("The name the other players see"))
+			.arg(Arg::with_name("network")
+				.long("network")
+				.value_name("FILE")
+				.conflicts_with("random")
+				.help("Lets a saved network play instead of picking cards in the terminal"))
+			.arg(Arg::with_name("random")
+				.long("random")
+				.help("Lets a random player play instead of picking cards in the terminal"))
+			.arg(policy_arg()))
+		.subcommand(SubCommand::with_name("api")
+			.about("Runs a local HTTP service for listing networks, asking them for moves and running evaluations with JSON")
+			.arg(Arg::with_name("address")
+				.long("address")
+				.value_name("HOST:PORT")
+				.default_value("127.0.0.1:8080")
+				.help("The address the service listens on"))
+			.arg(Arg::with_name("networks-dir")
+				.long("networks-dir")
+				.value_name("DIR")
+				.default_value(".")
+				.help("The directory networks are listed and loaded from, requests can't load networks outside it")))
+		.subcommand(SubCommand::with_name("ratings")
+			.about("Prints the rating of every agent that has played"))
+}
+
+// Sets up logging to stderr, so it doesn't mix with the results printed to stdout.
+pub fn init_logger(matches: &ArgMatches)
+{
+	// Global arguments given after the subcommand are only in the subcommand's matches.
+	let filter = matches.value_of("log-level").or_else(|| matches.subcommand().1.and_then(|m| m.value_of("log-level")));
+
+	let mut builder = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
+
+	if let Some(filter) = filter
+	{
+		builder.parse_filters(filter);
+	}
+
+	builder.init();
+}
+
+pub fn run_command(matches: &ArgMatches)
+{
+	match matches.subcommand()
+	{
+		("play", Some(m)) => run_play(m),
+		("train", Some(m)) => run_train(m),
+		("evaluate", Some(m)) => run_evaluate(m),
+		("tournament", Some(m)) => run_tournament_command(m),
+		("inspect", Some(m)) => run_inspect(m),
+		("explain", Some(m)) => run_explain(m),
+		("replay", Some(m)) => run_replay(m),
+		("render", Some(m)) => run_render(m),
+		("serve", Some(m)) => run_serve(m),
+		("connect", Some(m)) => run_connect(m),
+		("api", Some(m)) => run_api(m),
+		("ratings", Some(_)) => run_ratings(),
+		_ => unreachable!("A subcommand is required."),
+	}
+}
+
+fn run_play(m: &ArgMatches)
+{
+	let humans = get_usize(m, "humans");
+	let random = get_usize(m, "random");
+	let commands: Vec<&str> = m.values_of("bot").map_or(Vec::new(), |x| x.collect());
+
+	if humans + random + commands.len() > NUMBER_OF_PLAYERS
+	{
+		exit_with_error(&format!("There are only {} seats, but {} humans, {} random players and {} bots were asked for.", NUMBER_OF_PLAYERS, humans, random, commands.len()));
+	}
+
+	let network = m.value_of("network").unwrap_or("");
+
+	if humans + random + commands.len() < NUMBER_OF_PLAYERS && network.is_empty()
+	{
+		exit_with_error("--network is needed to fill the seats which aren't human, random or bots.");
+	}
+
+	let mut games = get_usize(m, "games");
+	let mut batches = get_usize(m, "batches");
+
+	let spectator = get_spectator_settings(m);
+	let advisor = get_advisor(m);
+
+	if advisor.is_some() && humans == 0
+	{
+		exit_with_error("--hints is for human players, try --spectate to see why bots pick their cards.");
+	}
+
+	if spectator.is_some() && humans > 0
+	{
+		exit_with_error("--spectate is for games between bots, humans already see the game as they play.");
+	}
+
+	// Humans and spectators can only keep up with one game at a time.
+	if humans > 0 || spectator.is_some()
+	{
+		games = 1;
+		batches = 1;
+	}
+
+	let bots: Vec<ExternalBot> = commands.iter().map(|x| match ExternalBot::spawn(x, DEFAULT_MOVE_TIMEOUT)
+	{
+		Ok(bot) => bot,
+		Err(e) => exit_with_error(&format!("Couldn't start {}: {}", x, e)),
+	}).collect();
+
+	// Only loaded if some of the seats need it.
+	let network = if humans + random + bots.len() < NUMBER_OF_PLAYERS
+	{
+		let mut player = match load_nn_from_file(network)
+		{
+			Ok(nn) => NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, nn, get_policy(m)),
+			Err(e) => exit_with_error(&format!("Couldn't load {}: {}", network, e)),
+		};
+
+		player.name = network.to_string();
+		Some(player)
+	}
+	else
+	{
+		None
+	};
+
+	let mut replay_writer = m.value_of("replay").map(|file| match ReplayWriter::new(file)
+	{
+		Ok(writer) => writer,
+		Err(e) => exit_with_error(&format!("Couldn't open {}: {}", file, e)),
+	});
+
+	// Ratings are only updated if the existing table could be read, so a bad file is never overwritten.
+	let mut ratings = match load_ratings_from_file(RATINGS_FILE_NAME)
+	{
+		Ok(ratings) => Some(ratings),
+		Err(e) =>
+		{
+			println!("Couldn't load {}, ratings won't be updated: {}", RATINGS_FILE_NAME, e);
+			None
+		}
+	};
+
+	let started = Instant::now();
+
+	let results = match play_batches(batches, games, network.as_ref(), random, humans, &bots, get_display_mode(m), spectator, advisor, replay_writer.as_mut(), ratings.as_mut())
+	{
+		Ok(results) => results,
+		Err(e) =>
+		{
+			println!("Couldn't show the game: {}", e);
+			std::process::exit(1);
+		}
+	};
+
+	// Whatever was played before a human quit isn't reported or rated.
+	if results.abandoned
+	{
+		println!("The game was abandoned.");
+		return;
+	}
+
+	print_batch_results(&results);
+
+	if let Some(ratings) = ratings
+	{
+		ratings.print_ratings(&results.player_names);
+		save_ratings(&ratings);
+	}
+
+	println!("\nGames per second: {}\n", results.get_games() as f64 / sec_from_time(started));
+	println!("Average batch time {}\n", sec_from_time(started) / batches as f64);
+}
+
+// Prints how often each seat won and how well it scored.
+fn print_batch_results(results: &BatchResults)
+{
+	let total_games = results.get_games();
+
+	for i in 0..NUMBER_OF_PLAYERS
+	{
+		let percentage_wins: f32 = (results.wins[i] as f32 / total_games as f32) * 100.0;
+		let (win_low, win_high) = wilson_interval(results.wins[i] as f64, total_games as f64);
+		let (score_low, score_high) = mean_confidence_interval(&results.seat_scores[i]);
+
+		println!("Player {} won {}% of games (95% CI {:.2}-{:.2}%), mean score {:.2} (95% CI {:.2}-{:.2})", i, percentage_wins, win_low * 100.0, win_high * 100.0, mean(&results.seat_scores[i]), score_low, score_high);
+
+		// Say whether this seat did better or worse than a player winning its fair share of games.
+		let baseline = 1.0 / NUMBER_OF_PLAYERS as f64;
+
+		if win_low > baseline
+		{
+			println!("Player {} won significantly more than the {}% baseline", i, baseline * 100.0);
+		}
+		else if win_high < baseline
+		{
+			println!("Player {} won significantly less than the {}% baseline", i, baseline * 100.0);
+		}
+	}
+}
+
+fn run_train(m: &ArgMatches)
+{
+	let mut config = match m.value_of("config")
+	{
+		Some(file) => match load_training_config(file)
+		{
+			Ok(config) => config,
+			Err(e) => exit_with_error(&format!("Couldn't load {}: {}", file, e)),
+		},
+		None => TrainingConfig::default(),
+	};
+
+	if let Some(name) = m.value_of("name")
+	{
+		config.name = name.to_string();
+	}
+
+	if let Some(output_dir) = m.value_of("output-dir")
+	{
+		config.output_dir = output_dir.to_string();
+	}
+
+	if m.is_present("seed")
+	{
+		config.seed = Some(get_u64(m, "seed"));
+	}
+
+	if m.is_present("games")
+	{
+		config.genetic.games_per_generation = get_usize(m, "games");
+	}
+
+	if m.is_present("generations")
+	{
+		config.genetic.generations = get_usize(m, "generations");
+	}
+
+	if let Some(model) = m.value_of("model")
+	{
+		config.model.name = NeuralNetworkModel::from_name(model).unwrap();
+	}
+
+	if m.is_present("hidden-nodes")
+	{
+		config.model.hidden_nodes = Some(get_usize(m, "hidden-nodes"));
+	}
+
+	if m.is_present("mutate-amount")
+	{
+		config.genetic.mutate_amount = value_t!(m, "mutate-amount", f32).unwrap_or_else(|e| e.exit());
+	}
+
+	if let Some(policy) = m.value_of("policy")
+	{
+		config.genetic.policy = policy.to_string();
+	}
+
+	if let Some(format) = m.value_of("format")
+	{
+		config.save_format = format.to_string();
+	}
+
+	if let Some(format) = m.value_of("metrics-format")
+	{
+		config.metrics.format = format.to_string();
+	}
+
+	if m.is_present("benchmark-interval")
+	{
+		config.benchmark.interval = get_usize(m, "benchmark-interval");
+	}
+
+	if m.is_present("keep-best")
+	{
+		config.benchmark.keep_best = true;
+	}
+
+	if m.is_present("live-summary")
+	{
+		config.metrics.live_summary = true;
+	}
+
+	if let Err(e) = config.validate()
+	{
+		exit_with_error(&e.to_string());
+	}
+
+	// Champions are only rated if the config asks for it.
+	let mut ratings = None;
+
+	if config.rating.count > 0
+	{
+		ratings = match load_ratings_from_file(RATINGS_FILE_NAME)
+		{
+			Ok(ratings) => Some(ratings),
+			Err(e) =>
+			{
+				println!("Couldn't load {}, champions won't be rated: {}", RATINGS_FILE_NAME, e);
+				None
+			}
+		};
+	}
+
+	run_generational(&config, ratings.as_mut());
+
+	if let Some(ratings) = ratings
+	{
+		save_ratings(&ratings);
+	}
+}
+
+fn run_evaluate(m: &ArgMatches)
+{
+	let names: Vec<&str> = m.values_of("agents").unwrap().collect();
+
+	let agents = match load_entrants(&names, get_policy(m))
+	{
+		Some(agents) => agents,
+		None => return,
+	};
+
+	let deals = get_usize(m, "deals");
+	let seed = get_u64(m, "seed");
+
+	let started = Instant::now();
+
+	match m.value_of("mode").unwrap()
+	{
+		"duplicate" =>
+		{
+			if agents.len() < 2 || agents.len() > NUMBER_OF_PLAYERS
+			{
+				exit_with_error(&format!("A duplicate match needs between 2 and {} agents.", NUMBER_OF_PLAYERS));
+			}
+
+			print_duplicate(&run_duplicate(&agents, deals, seed));
+		}
+		_ =>
+		{
+			if agents.len() != 2
+			{
+				exit_with_error("A head to head match needs exactly two agents, use --mode duplicate for more.");
+			}
+
+			let sprt = m.value_of("sprt").map(|x| SprtParameters::from_bounds(x).unwrap());
+
+			let result = run_head_to_head(&agents[0], &agents[1], deals, seed, sprt);
+			print_head_to_head(&agents[0].name, &agents[1].name, &result, sprt);
+		}
+	}
+
+	println!("\nTotal time: {}", sec_from_time(started));
+}
+
+fn run_tournament_command(m: &ArgMatches)
+{
+	let names: Vec<&str> = m.values_of("entrants").unwrap().collect();
+
+	let entrants = match load_entrants(&names, get_policy(m))
+	{
+		Some(entrants) => entrants,
+		None => return,
+	};
+
+	if entrants.is_empty()
+	{
+		exit_with_error("A tournament needs at least one entrant.");
+	}
+
+	let format = TournamentFormat::from_name(m.value_of("format").unwrap()).unwrap();
+
+	let mut ratings = match load_ratings_from_file(RATINGS_FILE_NAME)
+	{
+		Ok(ratings) => ratings,
+		Err(e) => exit_with_error(&format!("Couldn't load {}: {}", RATINGS_FILE_NAME, e)),
+	};
+
+	let results = run_tournament(&entrants, format, get_usize(m, "games-per-table"), get_u64(m, "seed"), &mut ratings);
+
+	print_standings(&results);
+
+	let agents: Vec<String> = entrants.iter().map(|x| x.player.name.clone()).collect();
+	ratings.print_ratings(&agents);
+
+	save_ratings(&ratings);
+}
+
+fn run_inspect(m: &ArgMatches)
+{
+	let file = m.value_of("file").unwrap();
+
+	match load_nn_from_file(file)
+	{
+		Ok(nn) => print_nn_summary(&nn),
+		Err(e) => println!("Couldn't load {}: {}", file, e),
+	}
+}
+
+fn run_explain(m: &ArgMatches)
+{
+	let file = m.value_of("file").unwrap();
+
+	match load_nn_from_file(file)
+	{
+		Ok(nn) => print_explanation_report(&nn, get_usize(m, "positions"), get_u64(m, "seed"), get_usize(m, "top-inputs")),
+		Err(e) => println!("Couldn't load {}: {}", file, e),
+	}
+}
+
+fn run_replay(m: &ArgMatches)
+{
+	let file = m.value_of("file").unwrap();
+	let replays = load_replays_or_exit(file);
+
+	if m.is_present("verify")
+	{
+		if !verify_replays(&replays)
+		{
+			std::process::exit(1);
+		}
+
+		return;
+	}
+
+	let game = get_usize(m, "game");
+
+	match replays.get(game)
+	{
+		Some(replay) => view_replay(replay),
+		None => exit_with_error(&format!("{} only has {} games, pick one from 0 to {}.", file, replays.len(), replays.len().saturating_sub(1))),
+	}
+}
+
+fn run_render(m: &ArgMatches)
+{
+	let file = m.value_of("file").unwrap();
+	let replays = load_replays_or_exit(file);
+
+	let game = get_usize(m, "game");
+	let turn = m.value_of("turn").map(|_| get_usize(m, "turn"));
+	let output_dir = m.value_of("output-dir").unwrap();
+
+	let replay = match replays.get(game)
+	{
+		Some(replay) => replay,
+		None => exit_with_error(&format!("{} only has {} games, pick one from 0 to {}.", file, replays.len(), replays.len().saturating_sub(1))),
+	};
+
+	match render_replay(replay, std::path::Path::new(output_dir), turn)
+	{
+		Ok(written) => println!("Wrote {} images to {}", written, output_dir),
+		Err(e) =>
+		{
+			println!("Couldn't render game {} of {}: {}", game, file, e);
+			std::process::exit(1);
+		}
+	}
+}
+
+fn load_replays_or_exit(file: &str) -> Vec<GameReplay>
+{
+	match load_replays_from_file(file)
+	{
+		Ok(replays) => replays,
+		Err(e) =>
+		{
+			println!("Couldn't load {}: {}", file, e);
+			std::process::exit(1);
+		}
+	}
+}
+
+fn run_serve(m: &ArgMatches)
+{
+	let remote_seats = get_usize(m, "remote");
+
+	if remote_seats == 0 || remote_seats > NUMBER_OF_PLAYERS
+	{
+		exit_with_error(&format!("--remote has to be from 1 to {}.", NUMBER_OF_PLAYERS));
+	}
+
+	let bot = match m.value_of("network")
+	{
+		Some(file) => match load_nn_from_file(file)
+		{
+			Ok(nn) =>
+			{
+				let mut player = NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, nn, get_policy(m));
+				player.name = file.to_string();
+				player
+			}
+			Err(e) => exit_with_error(&format!("Couldn't load {}: {}", file, e)),
+		},
+		None => NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_neural_network()),
+	};
+
+	let settings = ServerSettings
+	{
+		remote_seats,
+		bots: (remote_seats..NUMBER_OF_PLAYERS).map(|_| bot.clone()).collect(),
+		turn_timeout: std::time::Duration::from_secs(get_usize(m, "turn-timeout") as u64),
+		games: m.value_of("games").map(|_| get_usize(m, "games")),
+	};
+
+	let address = m.value_of("address").unwrap();
+
+	if let Err(e) = run_server(address, settings)
+	{
+		println!("Couldn't host games on {}: {}", address, e);
+		std::process::exit(1);
+	}
+}
+
+fn run_api(m: &ArgMatches)
+{
+	let address = m.value_of("address").unwrap();
+
+	if let Err(e) = run_api_server(address, m.value_of("networks-dir").unwrap())
+	{
+		println!("Couldn't run the service on {}: {}", address, e);
+		std::process::exit(1);
+	}
+}
+
+fn run_connect(m: &ArgMatches)
+{
+	let bot = if let Some(file) = m.value_of("network")
+	{
+		match load_nn_from_file(file)
+		{
+			Ok(nn) => Some(NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, nn, get_policy(m))),
+			Err(e) => exit_with_error(&format!("Couldn't load {}: {}", file, e)),
+		}
+	}
+	else if m.is_present("random")
+	{
+		Some(NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_neural_network()))
+	}
+	else
+	{
+		None
+	};
+
+	let address = m.value_of("address").unwrap();
+
+	if let Err(e) = run_client(address, m.value_of("name").unwrap(), bot)
+	{
+		println!("Lost the connection to {}: {}", address, e);
+		std::process::exit(1);
+	}
+}
+
+fn run_ratings()
+{
+	match load_ratings_from_file(RATINGS_FILE_NAME)
+	{
+		Ok(ratings) => ratings.print_all_ratings(),
+		Err(e) => println!("Couldn't load {}: {}", RATINGS_FILE_NAME, e),
+	}
+}
+
+fn save_ratings(ratings: &RatingsTable)
+{
+	if let Err(e) = ratings.save_to_file(RATINGS_FILE_NAME)
+	{
+		println!("Couldn't save {}: {}", RATINGS_FILE_NAME, e);
+	}
+}
+
+// Values are checked by their validators before the command runs, so these can't fail.
+fn get_usize(m: &ArgMatches, name: &str) -> usize
+{
+	value_t!(m, name, usize).unwrap_or_else(|e| e.exit())
+}
+
+fn get_u64(m: &ArgMatches, name: &str) -> u64
+{
+	value_t!(m, name, u64).unwrap_or_else(|e| e.exit())
+}
+
+fn get_policy(m: &ArgMatches) -> PolicyMode
+{
+	PolicyMode::from_name(m.value_of("policy").unwrap()).unwrap()
+}
+
+fn get_display_mode(m: &ArgMatches) -> DisplayMode
+{
+	DisplayMode::from_name(m.value_of("display").unwrap()).unwrap()
+}
+
+fn get_spectator_settings(m: &ArgMatches) -> Option<SpectatorSettings>
+{
+	if !m.is_present("spectate")
+	{
+		return None;
+	}
+
+	let turn_delay = std::time::Duration::from_millis(value_t!(m, "turn-delay", u64).unwrap_or_else(|e| e.exit()));
+
+	Some(SpectatorSettings{turn_delay, start_paused: m.is_present("paused")})
+}
+
+fn get_advisor(m: &ArgMatches) -> Option<Advisor>
+{
+	if !m.is_present("hints")
+	{
+		return None;
+	}
+
+	let network = m.value_of("hint-network").map(|file| match load_nn_from_file(file)
+	{
+		Ok(nn) => nn,
+		Err(e) => exit_with_error(&format!("Couldn't load {}: {}", file, e)),
+	});
+
+	let rollouts = get_usize(m, "hint-rollouts");
+
+	if network.is_none() && rollouts == 0
+	{
+		exit_with_error("The advisor needs --hint-network if it isn't playing any rollouts.");
+	}
+
+	Some(Advisor::new(network, rollouts))
+}
+
+fn exit_with_error(message: &str) -> !
+{
+	clap::Error::with_description(message, clap::ErrorKind::ArgumentConflict).exit()
+}
+
+fn is_usize(value: String) -> Result<(), String>
+{
+	value.parse::<usize>().map(|_| ()).map_err(|_| format!("'{}' isn't a whole number", value))
+}
+
+fn is_u64(value: String) -> Result<(), String>
+{
+	value.parse::<u64>().map(|_| ()).map_err(|_| format!("'{}' isn't a whole number", value))
+}
+
+fn is_f32(value: String) -> Result<(), String>
+{
+	value.parse::<f32>().map(|_| ()).map_err(|_| format!("'{}' isn't a number", value))
+}
+
+fn is_policy(value: String) -> Result<(), String>
+{
+	PolicyMode::from_name(&value).map(|_| ()).ok_or(format!("'{}' isn't a policy, use argmax, temperature=<t> or epsilon=<e>", value))
+}
+
+fn is_model(value: String) -> Result<(), String>
+{
+	NeuralNetworkModel::from_name(&value).map(|_| ()).ok_or(format!("'{}' isn't a model, use one of {}", value, get_model_names()))
+}
+
+fn is_display_mode(value: String) -> Result<(), String>
+{
+	DisplayMode::from_name(&value).map(|_| ()).ok_or(format!("'{}' isn't a display, use window or terminal", value))
+}
+
+fn is_save_format(value: String) -> Result<(), String>
+{
+	SaveFormat::from_name(&value).map(|_| ()).ok_or(format!("'{}' isn't a save format, use json or binary", value))
+}
+
+fn is_tournament_format(value: String) -> Result<(), String>
+{
+	TournamentFormat::from_name(&value).map(|_| ()).ok_or(format!("'{}' isn't a tournament format, use round_robin or swiss=<rounds>", value))
+}
+
+fn is_sprt_bounds(value: String) -> Result<(), String>
+{
+	SprtParameters::from_bounds(&value).map(|_| ()).ok_or(format!("'{}' isn't a pair of Elo differences, use <elo0>,<elo1> with elo0 below elo1", value))
+}
@@ -0,0 +1,125 @@
+use super::neural_network_game::*;
+use super::neural_network_game::game::player::card::*;
+use super::neural_network_game::observation::*;
+use super::server::*;
+
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::TcpStream;
+
+// Joins a game on a server and plays it, either as a bot or by asking in the terminal which card to pick. Returns
+// once the game is over or the server closes the connection.
+pub fn run_client(address: &str, name: &str, bot: Option<NeuralNetworkGamePlayer>) -> io::Result<()>
+{
+	let stream = TcpStream::connect(address)?;
+	stream.set_nodelay(true)?;
+	let mut reader = BufReader::new(stream.try_clone()?);
+	let mut writer = stream;
+
+	send(&mut writer, &ClientMessage::Join{name: name.to_string()})?;
+
+	let mut players = Vec::new();
+
+	loop
+	{
+		let mut line = String::new();
+
+		if reader.read_line(&mut line)? == 0
+		{
+			println!("The server closed the connection.");
+			return Ok(());
+		}
+
+		let message: ServerMessage = match serde_json::from_str(&line)
+		{
+			Ok(message) => message,
+			Err(e) =>
+			{
+				warn!("Couldn't read a message from the server: {}", e);
+				continue;
+			}
+		};
+
+		match message
+		{
+			ServerMessage::Joined{players_needed} => println!("Joined, waiting for {} more players", players_needed),
+			ServerMessage::GameStart{seat, players: names} =>
+			{
+				println!("The game has started, you are player {}. Players: {}", seat, names.join(", "));
+				players = names;
+			}
+			ServerMessage::YourTurn{observation} =>
+			{
+				let card = match &bot
+				{
+					Some(player) => pick_bot_card(&observation.to_game(), player),
+					None => ask_for_card(&observation, &players)?,
+				};
+
+				send(&mut writer, &ClientMessage::Action{card})?;
+			}
+			ServerMessage::ActionRejected{message} | ServerMessage::Error{message} => println!("The server said: {}", message),
+			ServerMessage::RoundEnd{round, scores, totals} => println!("Round {} is over, scores {:?}, totals {:?}", round + 1, scores, totals),
+			ServerMessage::GameOver{pudding_scores, final_scores, winner} =>
+			{
+				println!("The game is over. Puddings scored {:?}, final scores {:?}", pudding_scores, final_scores);
+				println!("Player {} ({}) wins!", winner, players.get(winner).map_or("", |x| x.as_str()));
+				return Ok(());
+			}
+		}
+	}
+}
+
+fn send(writer: &mut TcpStream, message: &ClientMessage) -> io::Result<()>
+{
+	let line = serde_json::to_string(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+	writeln!(writer, "{}", line)?;
+	writer.flush()
+}
+
+// Shows the table and reads a choice from stdin until a legal one is given.
+fn ask_for_card(observation: &Observation, players: &[String]) -> io::Result<Card>
+{
+	println!("\nRound {}", observation.round + 1);
+
+	for (i, x) in observation.chosen.iter().enumerate()
+	{
+		println!("Player {} ({}), score {}: {}", i, players.get(i).map_or("", |x| x.as_str()), observation.scores[i], format_cards(x));
+	}
+
+	if observation.using_chopsticks
+	{
+		println!("\nYou can use your chopsticks to pick a second card, or keep them for later.");
+	}
+
+	println!("\nYour hand:");
+
+	for (i, x) in observation.legal_actions.iter().enumerate()
+	{
+		match x
+		{
+			Card::None => println!("  {}. Keep my chopsticks", i),
+			card => println!("  {}. {}", i, card),
+		}
+	}
+
+	loop
+	{
+		println!("Enter the number of the card you want to pick:");
+
+		let mut choice = String::new();
+
+		if io::stdin().read_line(&mut choice)? == 0
+		{
+			return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stdin was closed"));
+		}
+
+		match choice.trim().parse::<usize>()
+		{
+			Ok(i) if i < observation.legal_actions.len() => return Ok(observation.legal_actions[i]),
+			_ => println!("That isn't one of the choices."),
+		}
+	}
+}
@@ -1,4 +1,6 @@
 use super::neural_network_game::*;
+use super::neural_network_game::model::*;
+use super::neural_network_game::policy::*;
 
 use std::time::Instant;
 use rayon::prelude::*;
@@ -15,7 +17,7 @@ pub fn run_competition()
 	let first_round_games = (NUMBER_OF_PLAYERS as i32).pow((NUMBER_OF_ROUNDS - 1) as u32);
 
 	// Create a new vector to hold the neural networks.
-	let mut nn = create_neural_networks(first_round_games as usize * NUMBER_OF_PLAYERS);
+	let mut nn = create_neural_networks(first_round_games as usize * NUMBER_OF_PLAYERS, NeuralNetworkModel::default(), PolicyMode::default());
 
 	// Finished creating neural networks.
 	println!("Created {0} in {1}", nn.len(), sec_from_time(start));
@@ -31,7 +33,7 @@ pub fn run_competition()
 
 		let mut games = Vec::new();
 		//---- Play out the round and get an array of the winners
-		create_and_play_games_parallel(&mut games, number_of_games as usize, &mut nn, false);
+		create_and_play_games_parallel(&mut games, number_of_games as usize, &mut nn, None);
 
 		//---- Round Finished
 		println!("Finished round {0} in {1}", NUMBER_OF_ROUNDS - i, sec_from_time(round_started));
@@ -50,5 +52,8 @@ pub fn run_competition()
 
 	let s = format!("winner_of_{0}_competition_{1}", NUMBER_OF_ROUNDS, nn[0].nn.get_id());
 
-	nn[0].nn.save_nn_to_file(s);
+	if let Err(e) = nn[0].nn.save_nn_to_file(s)
+	{
+		println!("Couldn't save the winner: {}", e);
+	}
 }
\ No newline at end of file
@@ -0,0 +1,345 @@
+use super::neural_network_game::*;
+use super::neural_network_game::game::*;
+use super::statistics::*;
+use super::tournament::*;
+
+// How many deals are played between each check of the sequential test.
+const DEALS_PER_BATCH: usize = 50;
+
+// The seats the candidate sits in for each of the two games played on a deal. The baseline takes the others, so
+// over the pair both agents sit in every seat with exactly the same cards.
+const CANDIDATE_SEATS: [[bool; NUMBER_OF_PLAYERS]; 2] = [[true, false, true, false], [false, true, false, true]];
+
+// The results of a head to head match between a candidate and a baseline agent played on paired deals.
+pub struct HeadToHeadResult
+{
+	pub deals: usize,
+
+	// Deals where the candidate's total score over both games was higher, the same, or lower than the baseline's.
+	pub wins: usize,
+	pub draws: usize,
+	pub losses: usize,
+
+	// Per deal, the candidate's mean score less the baseline's. Both agents played the same cards, so most of the
+	// luck of the deal cancels out.
+	pub score_differences: Vec<f64>,
+
+	// How many seats the candidate filled, and its share of the games won from those seats.
+	pub candidate_seats: usize,
+	pub candidate_seat_wins: f64,
+
+	pub sprt: Option<SprtResult>,
+}
+
+impl HeadToHeadResult
+{
+	fn new() -> HeadToHeadResult
+	{
+		HeadToHeadResult{deals: 0, wins: 0, draws: 0, losses: 0, score_differences: Vec::new(), candidate_seats: 0, candidate_seat_wins: 0.0, sprt: None}
+	}
+
+	// The candidate's score over deals, counting a drawn deal as half a win.
+	pub fn get_deal_score(&self) -> f64
+	{
+		if self.deals == 0 { 0.5 } else { (self.wins as f64 + 0.5 * self.draws as f64) / self.deals as f64 }
+	}
+
+	pub fn get_deal_score_interval(&self) -> (f64, f64)
+	{
+		wilson_interval(self.wins as f64 + 0.5 * self.draws as f64, self.deals as f64)
+	}
+
+	pub fn get_elo_estimate(&self) -> f64
+	{
+		score_to_elo(self.get_deal_score())
+	}
+
+	// How often a seat filled by the candidate won the game. Agents of equal strength would win 1 in NUMBER_OF_PLAYERS.
+	pub fn get_seat_win_rate(&self) -> f64
+	{
+		if self.candidate_seats == 0 { 0.0 } else { self.candidate_seat_wins / self.candidate_seats as f64 }
+	}
+
+	pub fn get_seat_win_rate_interval(&self) -> (f64, f64)
+	{
+		wilson_interval(self.candidate_seat_wins, self.candidate_seats as f64)
+	}
+}
+
+// Plays the candidate against the baseline on up to max_deals seeded deals, two games a deal with the seats swapped.
+// If sprt is given the match stops as soon as the test is decided.
+pub fn run_head_to_head(candidate: &TournamentEntrant, baseline: &TournamentEntrant, max_deals: usize, seed: u64, sprt: Option<SprtParameters>) -> HeadToHeadResult
+{
+	let mut result = HeadToHeadResult::new();
+
+	while result.deals < max_deals
+	{
+		let deals_in_batch = std::cmp::min(DEALS_PER_BATCH, max_deals - result.deals);
+
+		let mut games = Vec::new();
+
+		for i in 0..deals_in_batch
+		{
+			let deal_seed = seed.wrapping_add((result.deals + i) as u64);
+
+			for seats in CANDIDATE_SEATS.iter()
+			{
+				let mut game = SushiGoGame::new(NUMBER_OF_PLAYERS);
+				game.setup_with_seed(deal_seed);
+
+				let players = seats.iter().map(|x| if *x { candidate.player.clone() } else { baseline.player.clone() }).collect();
+
+				games.push(NeuralNetworkGame::new(game, players));
+			}
+		}
+
+		play_games_parallel(&mut games);
+
+		for deal in games.chunks(CANDIDATE_SEATS.len())
+		{
+			let mut candidate_total = 0;
+			let mut baseline_total = 0;
+
+			for (game, seats) in deal.iter().zip(CANDIDATE_SEATS.iter())
+			{
+				let scores = game.get_game().get_final_scores();
+				let (wins, _) = get_placements(&scores);
+
+				for seat in 0..NUMBER_OF_PLAYERS
+				{
+					if seats[seat]
+					{
+						candidate_total += scores[seat];
+						result.candidate_seats += 1;
+						result.candidate_seat_wins += wins[seat];
+					}
+					else
+					{
+						baseline_total += scores[seat];
+					}
+				}
+			}
+
+			// Each agent fills half the seats over the pair of games.
+			let seats_per_agent = (CANDIDATE_SEATS.len() * NUMBER_OF_PLAYERS / 2) as f64;
+			result.score_differences.push((candidate_total - baseline_total) as f64 / seats_per_agent);
+
+			if candidate_total > baseline_total
+			{
+				result.wins += 1;
+			}
+			else if candidate_total == baseline_total
+			{
+				result.draws += 1;
+			}
+			else
+			{
+				result.losses += 1;
+			}
+
+			result.deals += 1;
+		}
+
+		if let Some(sprt) = sprt
+		{
+			let outcome = sprt.test(result.wins as f64, result.losses as f64);
+			result.sprt = Some(outcome);
+
+			debug!("After {} deals the SPRT log likelihood ratio is {:.3}, {:?}", result.deals, sprt.get_llr(result.wins as f64, result.losses as f64), outcome);
+
+			if outcome != SprtResult::Continue
+			{
+				break;
+			}
+		}
+	}
+
+	result
+}
+
+pub fn print_head_to_head(candidate: &str, baseline: &str, result: &HeadToHeadResult, sprt: Option<SprtParameters>)
+{
+	let (score_low, score_high) = result.get_deal_score_interval();
+	let (seat_low, seat_high) = result.get_seat_win_rate_interval();
+	let (difference_low, difference_high) = mean_confidence_interval(&result.score_differences);
+
+	println!("\n{} against {} over {} deals ({} games):", candidate, baseline, result.deals, result.deals * CANDIDATE_SEATS.len());
+	println!("Deals won/drawn/lost: {}/{}/{}", result.wins, result.draws, result.losses);
+	println!("Deal score: {:.2}% (95% CI {:.2}-{:.2}), about {:.1} Elo", result.get_deal_score() * 100.0, score_low * 100.0, score_high * 100.0, result.get_elo_estimate());
+	println!("Seat win rate: {:.2}% (95% CI {:.2}-{:.2}), equal agents would win {:.2}%", result.get_seat_win_rate() * 100.0, seat_low * 100.0, seat_high * 100.0, 100.0 / NUMBER_OF_PLAYERS as f64);
+	println!("Mean score difference per game: {:.2} (95% CI {:.2}-{:.2})", mean(&result.score_differences), difference_low, difference_high);
+
+	// Only claim a difference if the interval doesn't cover the agents being equal.
+	if difference_low > 0.0
+	{
+		println!("{} is significantly stronger.", candidate);
+	}
+	else if difference_high < 0.0
+	{
+		println!("{} is significantly stronger.", baseline);
+	}
+	else
+	{
+		println!("The difference is not significant.");
+	}
+
+	if let (Some(parameters), Some(outcome)) = (sprt, result.sprt)
+	{
+		let llr = parameters.get_llr(result.wins as f64, result.losses as f64);
+		let (lower, upper) = parameters.get_bounds();
+
+		let verdict = match outcome
+		{
+			SprtResult::AcceptH0 => format!("accepted H0, {} is at most {} Elo stronger", candidate, parameters.elo0),
+			SprtResult::AcceptH1 => format!("accepted H1, {} is at least {} Elo stronger", candidate, parameters.elo1),
+			SprtResult::Continue => "undecided, the deal limit was reached first".to_string(),
+		};
+
+		println!("SPRT [{}, {}]: LLR {:.3} (bounds {:.3}, {:.3}), {}", parameters.elo0, parameters.elo1, llr, lower, upper, verdict);
+	}
+}
+
+// Each agent in a duplicate match, with its results aggregated per deal.
+pub struct DuplicateResult
+{
+	pub name: String,
+
+	// The agent's mean score over every seat it filled on each deal.
+	pub deal_scores: Vec<f64>,
+
+	// Deals where the agent had the best mean score, shared between agents who tie.
+	pub deal_wins: f64,
+
+	// How many seats the agent filled, and its share of the games won from those seats.
+	pub seats: usize,
+	pub seat_wins: f64,
+}
+
+impl DuplicateResult
+{
+	fn new(name: &str) -> DuplicateResult
+	{
+		DuplicateResult{name: name.to_string(), deal_scores: Vec::new(), deal_wins: 0.0, seats: 0, seat_wins: 0.0}
+	}
+
+	pub fn get_deal_win_rate(&self) -> f64
+	{
+		if self.deal_scores.is_empty() { 0.0 } else { self.deal_wins / self.deal_scores.len() as f64 }
+	}
+
+	pub fn get_deal_win_rate_interval(&self) -> (f64, f64)
+	{
+		wilson_interval(self.deal_wins, self.deal_scores.len() as f64)
+	}
+
+	pub fn get_seat_win_rate(&self) -> f64
+	{
+		if self.seats == 0 { 0.0 } else { self.seat_wins / self.seats as f64 }
+	}
+}
+
+// Returns which agent sits in each seat. If there are fewer agents than seats they take turns filling the spare seats.
+fn get_duplicate_lineup(number_of_agents: usize, rotation: usize) -> Vec<usize>
+{
+	(0..NUMBER_OF_PLAYERS).map(|seat| ((seat + rotation) % NUMBER_OF_PLAYERS) % number_of_agents).collect()
+}
+
+// Returns how many rotations it takes for every agent to have filled every seat. When the agents divide evenly into the
+// seats the lineup repeats sooner, so there is no point playing the repeats.
+fn get_duplicate_rotations(number_of_agents: usize) -> usize
+{
+	if NUMBER_OF_PLAYERS.is_multiple_of(number_of_agents) { number_of_agents } else { NUMBER_OF_PLAYERS }
+}
+
+// Plays every deal once for each rotation of the agents around the table, so every agent holds every seat's cards.
+// Scores are then compared deal by deal, which removes most of the luck of the shuffle.
+pub fn run_duplicate(agents: &[TournamentEntrant], deals: usize, seed: u64) -> Vec<DuplicateResult>
+{
+	assert!(agents.len() >= 2 && agents.len() <= NUMBER_OF_PLAYERS, "A duplicate match needs between 2 and {} agents.", NUMBER_OF_PLAYERS);
+
+	let mut results: Vec<DuplicateResult> = agents.iter().map(|x| DuplicateResult::new(&x.name)).collect();
+
+	let rotations = get_duplicate_rotations(agents.len());
+
+	let mut games = Vec::new();
+
+	for i in 0..deals
+	{
+		for rotation in 0..rotations
+		{
+			let mut game = SushiGoGame::new(NUMBER_OF_PLAYERS);
+			game.setup_with_seed(seed.wrapping_add(i as u64));
+
+			let players = get_duplicate_lineup(agents.len(), rotation).iter().map(|x| agents[*x].player.clone()).collect();
+
+			games.push(NeuralNetworkGame::new(game, players));
+		}
+	}
+
+	play_games_parallel(&mut games);
+
+	debug!("Played {} deals of {} rotations between {} agents", deals, rotations, agents.len());
+
+	for deal in games.chunks(rotations)
+	{
+		let mut totals = vec![0.0; agents.len()];
+		let mut seats = vec![0; agents.len()];
+
+		for (rotation, game) in deal.iter().enumerate()
+		{
+			let scores = game.get_game().get_final_scores();
+			let (wins, _) = get_placements(&scores);
+
+			for (seat, agent) in get_duplicate_lineup(agents.len(), rotation).iter().enumerate()
+			{
+				totals[*agent] += scores[seat] as f64;
+				seats[*agent] += 1;
+
+				results[*agent].seats += 1;
+				results[*agent].seat_wins += wins[seat];
+			}
+		}
+
+		let deal_scores: Vec<f64> = totals.iter().zip(seats.iter()).map(|(total, seats)| total / *seats as f64).collect();
+
+		// The deal goes to whoever did best with the same cards, shared if they tie.
+		let best = deal_scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+		let tied = deal_scores.iter().filter(|x| **x == best).count();
+
+		for (agent, score) in deal_scores.iter().enumerate()
+		{
+			results[agent].deal_scores.push(*score);
+
+			if *score == best
+			{
+				results[agent].deal_wins += 1.0 / tied as f64;
+			}
+		}
+	}
+
+	results
+}
+
+pub fn print_duplicate(results: &[DuplicateResult])
+{
+	let deals = results.first().map_or(0, |x| x.deal_scores.len());
+
+	println!("\nDuplicate match over {} deals ({} games):", deals, deals * get_duplicate_rotations(results.len()));
+	println!("\n{:<40} {:>8} {:>15} {:>8} {:>7} {:>15} {:>9} {:>15}", "Agent", "Deal %", "Deal % 95% CI", "Seat %", "Score", "Score 95% CI", "vs first", "vs first 95% CI");
+
+	for x in results
+	{
+		let (deal_low, deal_high) = x.get_deal_win_rate_interval();
+		let (score_low, score_high) = mean_confidence_interval(&x.deal_scores);
+
+		// Differences are taken deal by deal, so the luck of the deal cancels out of the interval.
+		let differences: Vec<f64> = x.deal_scores.iter().zip(results[0].deal_scores.iter()).map(|(a, b)| a - b).collect();
+		let (difference_low, difference_high) = mean_confidence_interval(&differences);
+
+		let deal_interval = format!("{:.2}-{:.2}", deal_low * 100.0, deal_high * 100.0);
+		let score_interval = format!("{:.2}-{:.2}", score_low, score_high);
+		let difference_interval = format!("{:.2}-{:.2}", difference_low, difference_high);
+
+		println!("{:<40} {:>8.2} {:>15} {:>8.2} {:>7.2} {:>15} {:>9.2} {:>15}", x.name, x.get_deal_win_rate() * 100.0, deal_interval, x.get_seat_win_rate() * 100.0, mean(&x.deal_scores), score_interval, mean(&differences), difference_interval);
+	}
+}
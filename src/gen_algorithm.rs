@@ -1,54 +1,279 @@
 use super::neural_network_game::*;
+use super::neural_network_game::neural_network::get_unix_time;
+
+use super::rating::*;
+use super::tournament::*;
+use super::training_config::*;
+use super::metrics::*;
+use super::benchmark::*;
 
 use std::time::Instant;
 use rand::Rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-pub fn run_generational(games: usize, generations: usize)
+// Trains networks as described by the config, saving the run in its own directory. If ratings are given and the config
+// asks for it, each generation's champion is rated against the opponents in them.
+pub fn run_generational(config: &TrainingConfig, ratings: Option<&mut RatingsTable>)
 {
 	//---- Spawn Neural Networks
 	// Keep track of the time so we can record how long everything took.
     let start = Instant::now();
 
+	// Fill in anything left to chance, so the saved config describes exactly what was run.
+	let mut config = config.clone();
+
+	if config.name.is_empty()
+	{
+		config.name = format!("generational_{}", get_unix_time());
+	}
+
+	let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen_range(0, MAX_SEED));
+	config.seed = Some(seed);
+
+	let run_directory = match config.create_run_directory()
+	{
+		Ok(directory) => directory,
+		Err(e) =>
+		{
+			println!("Couldn't create the directory for the run: {}", e);
+			return;
+		}
+	};
+
+	let games = config.genetic.games_per_generation;
+	let generations = config.genetic.generations;
+	let policy = config.get_policy();
+
+	let mut rng = StdRng::seed_from_u64(seed);
+
 	// Create a new vector to hold the neural networks.
-	let mut nn = create_neural_networks(games * NUMBER_OF_PLAYERS);
+	let mut nn = create_neural_networks_with_rng(games * NUMBER_OF_PLAYERS, &config.get_node_count(), policy, &mut rng);
+
+	for x in nn.iter_mut()
+	{
+		x.nn.get_metadata_mut().seed = Some(seed);
+	}
 
 	// Finished creating neural networks.
 	println!("Created {0} in {1}", nn.len(), sec_from_time(start));
 	let competition_started = Instant::now();
 
+	// The network picked to breed the most recent generation, this is what gets saved at the end.
+	let mut champion = nn[0].clone();
+
+	// Champions are rated as training goes so the progress of the run can be tracked.
+	let run_name = config.name.clone();
+	let rating_interval = std::cmp::max(1, generations / std::cmp::max(1, config.rating.count));
+	let mut previous_champion_name: Option<String> = None;
+
+	let mut ratings = ratings.filter(|_| config.rating.count > 0);
+	let mut opponents = Vec::new();
+
+	// The champion is measured against the same panel as training goes, and the one which did best can be kept.
+	let mut benchmark_panel = None;
+	let mut benchmark_writer = None;
+	let mut best_by_benchmark: Option<(NeuralNetworkGamePlayer, f64, usize)> = None;
+
+	if config.benchmark.interval > 0
+	{
+		let names: Vec<&str> = config.benchmark.opponents.iter().map(|x| x.as_str()).collect();
+
+		benchmark_panel = match load_entrants(&names, policy)
+		{
+			Some(opponents) => Some(BenchmarkPanel::new(opponents, config.benchmark.checkpoints, config.benchmark.games, config.benchmark.seed)),
+			None => return,
+		};
+
+		let benchmark_file = run_directory.join(BENCHMARK_FILE_NAME);
+
+		benchmark_writer = match BenchmarkWriter::new(&benchmark_file)
+		{
+			Ok(writer) => Some(writer),
+			Err(e) =>
+			{
+				println!("Couldn't create {}, benchmarks won't be saved: {}", benchmark_file.display(), e);
+				None
+			}
+		};
+	}
+
+	let metrics_file = run_directory.join(format!("{}.{}", METRICS_FILE_NAME, config.get_metrics_format()));
+
+	let mut metrics_writer = match MetricsWriter::new(&metrics_file, config.get_metrics_format())
+	{
+		Ok(writer) => Some(writer),
+		Err(e) =>
+		{
+			println!("Couldn't create {}, metrics won't be saved: {}", metrics_file.display(), e);
+			None
+		}
+	};
+
+	if config.rating.count > 0
+	{
+		let names: Vec<&str> = config.rating.opponents.iter().map(|x| x.as_str()).collect();
+
+		opponents = match load_entrants(&names, policy)
+		{
+			Some(opponents) => opponents,
+			None => return,
+		};
+	}
+
 	//---- Start a new round of the tournament
 	for i in 0..generations
 	{
-		//let round_started = Instant::now();
-		
+		let round_started = Instant::now();
+
+		// Measured before the networks are handed out to the games.
+		let diversity = get_population_diversity(&nn);
+
 		//---- Play out the round and get an array of the winners
 		let mut sushi_go_games = Vec::new();
-		create_and_play_games_parallel(&mut sushi_go_games, games, &mut nn, false);
+		create_and_play_games_parallel(&mut sushi_go_games, games, &mut nn, Some(rng.gen()));
 
 		//---- Round Finished
 		//println!("Finished generation {0} in {1}", i, sec_from_time(round_started));
 
 		// Reset the neural networks we are using to just use the winners.
-		nn = next_generation(&sushi_go_games);
+		let (chosen, new_nn) = next_generation(&sushi_go_games, i, config.genetic.mutate_amount, &mut rng);
+
+		champion = chosen;
+		nn = new_nn;
+
+		let mut metrics = GenerationMetrics::from_games(i, &sushi_go_games, diversity);
+
+		debug!("Generation {}: mean fitness {:.2}, max fitness {:.2}, diversity {:.4}, picked network {}", i, metrics.mean_fitness, metrics.max_fitness, diversity, champion.nn.get_id());
 
-		if i % (generations / 100) == 0
+		if let Some(panel) = benchmark_panel.as_mut()
 		{
-			println!("Progress: {}%", (i as f32 / generations as f32) * 100.0);
+			if (i + 1) % config.benchmark.interval == 0 || i + 1 == generations
+			{
+				let results = panel.run(&champion);
+				let score = get_benchmark_score(&results);
+
+				metrics.benchmark_win_rate = Some(score);
+
+				info!("Generation {} champion scored {:.2}% against the fixed benchmark opponents", i, score * 100.0);
+
+				if config.metrics.live_summary || i + 1 == generations
+				{
+					print_benchmark(i, &results);
+				}
+
+				if let Some(writer) = benchmark_writer.as_mut()
+				{
+					if let Err(e) = writer.write(i, &results)
+					{
+						println!("Couldn't write to {}, benchmarks won't be saved: {}", BENCHMARK_FILE_NAME, e);
+						benchmark_writer = None;
+					}
+				}
+
+				if best_by_benchmark.as_ref().is_none_or(|x| score > x.1)
+				{
+					best_by_benchmark = Some((champion.clone(), score, i));
+				}
+
+				// Later champions have to beat this one too.
+				panel.add_checkpoint(&format!("generation {}", i), &champion);
+			}
+		}
+
+		metrics.elapsed_seconds = sec_from_time(competition_started);
+		metrics.generation_seconds = sec_from_time(round_started);
+
+		if let Some(writer) = metrics_writer.as_mut()
+		{
+			if let Err(e) = writer.write(&metrics)
+			{
+				println!("Couldn't write to {}, metrics won't be saved: {}", metrics_file.display(), e);
+				metrics_writer = None;
+			}
+		}
+
+		if (i + 1) % rating_interval == 0 || i + 1 == generations
+		{
+			if let Some(ratings) = ratings.as_deref_mut()
+			{
+				let champion_name = format!("{} generation {}", run_name, i);
+
+				// Start each champion from the rating of the one before it.
+				if let Some(previous) = &previous_champion_name
+				{
+					let previous_rating = ratings.get_rating(previous);
+					ratings.set_rating(&champion_name, previous_rating);
+				}
+
+				rate_champion(&champion, &champion_name, &opponents, config.rating.games, ratings);
+
+				info!("{} is rated {:.0}", champion_name, ratings.get_rating(&champion_name));
+				ratings.record_history(&run_name, i, &champion_name);
+
+				previous_champion_name = Some(champion_name);
+			}
+		}
+
+		if i % config.get_progress_interval() == 0
+		{
+			if config.metrics.live_summary
+			{
+				metrics.print_summary();
+			}
+			else
+			{
+				println!("Progress: {}%", (i as f32 / generations as f32) * 100.0);
+			}
 		}
 	}
 	//---- Complete!
-	println!("The winner is {0} total time was {1}", nn[0].nn.get_id(), sec_from_time(competition_started));
+	println!("The winner is {0} total time was {1}", champion.nn.get_id(), sec_from_time(competition_started));
 
 	println!("Generations per second: {}", generations as f64 / sec_from_time(competition_started));
 
 	println!("Games per second: {}", (generations as f64 / sec_from_time(competition_started)) * games as f64);
 
-	let s = format!("{0}_games_in_{1}_generations_{2}", games, generations, nn[0].nn.get_id());
+	let s = format!("{0}_games_in_{1}_generations_{2}", games, generations, champion.nn.get_id());
 
-	nn[0].nn.save_nn_to_file(s);
+	if let Err(e) = champion.nn.save_nn_to_file_with_format(run_directory.join(s).to_string_lossy().to_string(), config.get_save_format())
+	{
+		println!("Couldn't save the winner: {}", e);
+	}
+
+	if config.benchmark.keep_best
+	{
+		if let Some((best, score, generation)) = best_by_benchmark
+		{
+			println!("The best champion against the fixed benchmark opponents was from generation {} with a score of {:.2}%", generation, score * 100.0);
+
+			let s = format!("best_by_benchmark_generation_{0}_{1}", generation, best.nn.get_id());
+
+			if let Err(e) = best.nn.save_nn_to_file_with_format(run_directory.join(s).to_string_lossy().to_string(), config.get_save_format())
+			{
+				println!("Couldn't save the best champion: {}", e);
+			}
+		}
+	}
+
+	if let Some(ratings) = ratings
+	{
+		ratings.print_history(&run_name);
+	}
+}
+
+// Plays the champion against the opponents, updating its rating. The same deals are used every time so champions from
+// different generations can be compared.
+fn rate_champion(champion: &NeuralNetworkGamePlayer, name: &str, opponents: &[TournamentEntrant], number_of_games: usize, ratings: &mut RatingsTable)
+{
+	for x in play_champion_games(champion, name, opponents, number_of_games, 0).iter()
+	{
+		ratings.update_from_game(&x.get_player_names(), &x.get_game().get_final_scores());
+	}
 }
 
-fn next_generation(games: &Vec<NeuralNetworkGame>) -> Vec<NeuralNetworkGamePlayer>
+// Picks a winner to breed from, weighted by their score, and returns it along with its mutated offspring.
+fn next_generation<R: Rng>(games: &[NeuralNetworkGame], generation: usize, mutate_amount: f32, rng: &mut R) -> (NeuralNetworkGamePlayer, Vec<NeuralNetworkGamePlayer>)
 {
 	//println!("Generating next generation:");
 	let total_score: f32 = games.iter().map(|game| game.get_game().get_winning_score() as f32)
@@ -77,30 +302,45 @@ fn next_generation(games: &Vec<NeuralNetworkGame>) -> Vec<NeuralNetworkGamePlaye
 
 	let mut new_nn = Vec::new();
 
-	let chosen = winners.remove(pick_one(&fitness));
+	let chosen_index = pick_one(&fitness, rng);
+	let mut chosen = winners.remove(chosen_index);
+
+	// Record how the chosen network did so it is saved with the network.
+	chosen.nn.get_metadata_mut().generation = Some(generation);
+	chosen.nn.get_metadata_mut().fitness = Some(games[chosen_index].get_game().get_winning_score() as f32);
 
-	//print_nn_info(&chosen);
+	let population = games.len() * NUMBER_OF_PLAYERS;
 
-	for _i in 0..games.len() * NUMBER_OF_PLAYERS
+	for i in 0..population
 	{
 		let mut mutated_nn = chosen.clone();
-		mutated_nn.nn.mutate();
+		mutated_nn.nn.mutate_with_rng(mutate_amount, rng);
+
+		// Give each child its own ID and remember which network it came from.
+		mutated_nn.nn.set_id((generation + 1) * population + i);
+
+		let metadata = mutated_nn.nn.get_metadata_mut();
+		metadata.created = Some(get_unix_time());
+		metadata.generation = Some(generation + 1);
+		metadata.fitness = None;
+		metadata.add_parent(chosen.nn.get_id());
+
 		new_nn.push(mutated_nn);
 	}
 
-	return new_nn;
+	(chosen, new_nn)
 }
 
-fn pick_one(fitness: &Vec<f32>) -> usize
+fn pick_one<R: Rng>(fitness: &[f32], rng: &mut R) -> usize
 {
 	let mut index = 0;
-	let mut r: f32 = rand::thread_rng().gen();
+	let mut r: f32 = rng.gen();
 
 	while r > 0.0
 	{
 		if index < fitness.len()
 		{
-			r = r - fitness[index];
+			r -= fitness[index];
 			index += 1;
 		}
 		else
@@ -111,5 +351,5 @@ fn pick_one(fitness: &Vec<f32>) -> usize
 
 	index -= 1;
 
-	return index;
+	index
 }
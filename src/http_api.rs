@@ -0,0 +1,445 @@
+// A local HTTP service for asking saved networks for moves and running evaluations, so tools which aren't written in
+// Rust can use them.
+//
+// Every request and response body is JSON. Networks are named by their path relative to the networks directory the
+// service was started with, and can't be outside it. Cards are named as in the replays, such as "SalmonNigiri".
+//
+//   GET /networks
+//       Lists every saved network in the networks directory and the folders inside it.
+//       {"networks":[{"file":"runs/x/champion.txt","id":12,"model":"model_c","hidden_nodes":20}]}
+//
+//   POST /move
+//       {"network":"runs/x/champion.txt","observation":{...},"policy":"argmax"}
+//       Asks the network which card to pick. The observation is the same as the game server's your_turn, and the
+//       policy is optional. Answers with the card and the network's rating of every card it could pick:
+//       {"card":"Tempura","cards":[{"card":"Tempura","weight":0.93,"probability":0.41},...]}
+//
+//   POST /evaluate
+//       {"players":["runs/x/champion.txt","random","random","heuristic"],"games":1000,"batches":1,"seed":7}
+//       Plays games between the players, one for each seat, each either 'random', 'heuristic' or a saved network.
+//       Batches, seed and policy are optional, and games are played at most 1000 at a time however big the batch.
+//       Answers once every game has been played, with the statistics of each seat:
+//       {"games":1000,"seats":[{"player":"random","wins":240,"win_rate":0.24,"win_rate_interval":[0.21,0.27],
+//       "mean_score":41.2,"score_interval":[40.6,41.8]},...]}
+//
+// Errors are answered with a 4xx or 5xx status and {"error":"..."}.
+
+use super::neural_network_game::*;
+use super::neural_network_game::game::player::card::*;
+use super::neural_network_game::model::*;
+use super::neural_network_game::neural_network::*;
+use super::neural_network_game::observation::*;
+use super::neural_network_game::policy::*;
+use super::statistics::*;
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+// Request bodies bigger than this are turned away.
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+// The most games one evaluation can ask for, so a single request can't keep the service busy for hours.
+pub const MAX_EVALUATION_GAMES: usize = 100000;
+
+// Evaluations are played this many games at a time, so a large batch doesn't hold every game in memory at once.
+const EVALUATION_CHUNK_SIZE: usize = 1000;
+
+// Folders which are never searched for networks.
+const SKIPPED_DIRECTORIES: [&str; 2] = ["target", "res"];
+
+#[derive(Serialize)]
+pub struct NetworkInfo
+{
+	pub file: String,
+	pub id: usize,
+	pub model: String,
+	pub hidden_nodes: usize,
+}
+
+#[derive(Serialize)]
+pub struct NetworksResponse
+{
+	pub networks: Vec<NetworkInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct MoveRequest
+{
+	pub network: String,
+	pub observation: Observation,
+
+	#[serde(default)]
+	pub policy: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MoveResponse
+{
+	pub card: Card,
+
+	// Every card the player could pick, most likely first.
+	pub cards: Vec<CardWeight>,
+}
+
+#[derive(Deserialize)]
+pub struct EvaluateRequest
+{
+	pub players: Vec<String>,
+	pub games: usize,
+
+	#[serde(default = "default_batches")]
+	pub batches: usize,
+
+	#[serde(default)]
+	pub seed: Option<u64>,
+
+	#[serde(default)]
+	pub policy: Option<String>,
+}
+
+fn default_batches() -> usize
+{
+	1
+}
+
+#[derive(Serialize)]
+pub struct SeatStatistics
+{
+	pub player: String,
+
+	// Only counts the player the game says won, ties aren't shared.
+	pub wins: usize,
+	pub win_rate: f64,
+	pub win_rate_interval: (f64, f64),
+	pub mean_score: f64,
+	pub score_interval: (f64, f64),
+}
+
+#[derive(Serialize)]
+pub struct EvaluateResponse
+{
+	pub games: usize,
+	pub seats: Vec<SeatStatistics>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse
+{
+	error: String,
+}
+
+// Why a request couldn't be answered, with the HTTP status to answer it with.
+pub struct ApiError
+{
+	pub status: u16,
+	pub message: String,
+}
+
+impl ApiError
+{
+	fn bad_request(message: String) -> ApiError
+	{
+		ApiError{status: 400, message}
+	}
+
+	fn not_found(message: String) -> ApiError
+	{
+		ApiError{status: 404, message}
+	}
+}
+
+// Answers requests until the program is stopped. Each request is handled on its own thread so a long evaluation
+// doesn't hold up quick requests. Returns if the address can't be listened on.
+pub fn run_api_server(address: &str, networks_dir: &str) -> io::Result<()>
+{
+	let server = Server::http(address).map_err(|e| io::Error::other(e.to_string()))?;
+	let networks_dir = Arc::new(PathBuf::from(networks_dir));
+
+	println!("Listening on http://{}, serving networks from {}", address, networks_dir.display());
+
+	for request in server.incoming_requests()
+	{
+		let networks_dir = networks_dir.clone();
+
+		thread::spawn(move || handle_request(request, &networks_dir));
+	}
+
+	Ok(())
+}
+
+fn handle_request(mut request: Request, networks_dir: &Path)
+{
+	let method = request.method().clone();
+	let url = request.url().split('?').next().unwrap_or("").to_string();
+
+	let mut body = String::new();
+
+	let result = match request.as_reader().take(MAX_BODY_SIZE).read_to_string(&mut body)
+	{
+		Err(e) => Err(ApiError::bad_request(format!("couldn't read the request: {}", e))),
+		Ok(_) => match (&method, url.as_str())
+		{
+			(Method::Get, "/networks") => list_networks(networks_dir).and_then(|networks| to_json(NetworksResponse{networks})),
+			(Method::Post, "/move") => parse_body(&body).and_then(|x| request_move(networks_dir, x)).and_then(to_json),
+			(Method::Post, "/evaluate") => parse_body(&body).and_then(|x| evaluate(networks_dir, x)).and_then(to_json),
+			(_, "/networks") | (_, "/move") | (_, "/evaluate") => Err(ApiError{status: 405, message: format!("{} isn't allowed for {}", method, url)}),
+			_ => Err(ApiError::not_found(format!("there's nothing at {}", url))),
+		},
+	};
+
+	let (status, body) = match result
+	{
+		Ok(value) => (200, value.to_string()),
+		Err(e) => (e.status, serde_json::to_string(&ErrorResponse{error: e.message}).unwrap_or_default()),
+	};
+
+	info!("{} {} answered {}", method, url, status);
+
+	let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("The content type header is invalid!");
+
+	if let Err(e) = request.respond(Response::from_string(body).with_status_code(status).with_header(header))
+	{
+		warn!("Couldn't answer {} {}: {}", method, url, e);
+	}
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, ApiError>
+{
+	serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("the request isn't valid: {}", e)))
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Result<serde_json::Value, ApiError>
+{
+	serde_json::to_value(value).map_err(|e| ApiError{status: 500, message: e.to_string()})
+}
+
+fn parse_policy(policy: &Option<String>) -> Result<PolicyMode, ApiError>
+{
+	match policy
+	{
+		Some(name) => PolicyMode::from_name(name).ok_or_else(|| ApiError::bad_request(format!("'{}' isn't a policy, use argmax, temperature=<t> or epsilon=<e>", name))),
+		None => Ok(PolicyMode::default()),
+	}
+}
+
+// Loads a network by its path inside the networks directory, refusing paths which lead out of it.
+fn load_network(networks_dir: &Path, file: &str) -> Result<NeuralNetwork, ApiError>
+{
+	let path = Path::new(file);
+
+	if path.components().any(|x| !matches!(x, Component::Normal(_) | Component::CurDir))
+	{
+		return Err(ApiError::bad_request(format!("{} has to be a path inside the networks directory", file)));
+	}
+
+	let full_path = networks_dir.join(path);
+
+	if !full_path.is_file()
+	{
+		return Err(ApiError::not_found(format!("there's no network called {}", file)));
+	}
+
+	load_nn_from_file(&full_path.to_string_lossy()).map_err(|e| ApiError::bad_request(format!("couldn't load {}: {}", file, e)))
+}
+
+// Finds every file in the directory and the folders inside it which loads as a network. Files which don't, such as
+// saved populations and metrics, are left out.
+pub fn list_networks(networks_dir: &Path) -> Result<Vec<NetworkInfo>, ApiError>
+{
+	let mut networks = Vec::new();
+	let mut directories = vec![networks_dir.to_path_buf()];
+
+	while let Some(directory) = directories.pop()
+	{
+		let entries = fs::read_dir(&directory).map_err(|e| ApiError{status: 500, message: format!("couldn't read {}: {}", directory.display(), e)})?;
+
+		for entry in entries.filter_map(|x| x.ok())
+		{
+			let path = entry.path();
+			let name = entry.file_name().to_string_lossy().to_string();
+
+			if path.is_dir()
+			{
+				if !name.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&name.as_str())
+				{
+					directories.push(path);
+				}
+
+				continue;
+			}
+
+			let extension = path.extension().map_or(String::new(), |x| x.to_string_lossy().to_string());
+
+			if extension != SaveFormat::Json.get_extension() && extension != SaveFormat::Binary.get_extension()
+			{
+				continue;
+			}
+
+			if let Ok(nn) = load_nn_from_file(&path.to_string_lossy())
+			{
+				let file = path.strip_prefix(networks_dir).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+
+				networks.push(NetworkInfo{file, id: nn.get_id(), model: nn.get_model().to_string(), hidden_nodes: nn.get_properties().hidden_node_count});
+			}
+		}
+	}
+
+	networks.sort_by(|a, b| a.file.cmp(&b.file));
+
+	Ok(networks)
+}
+
+// Asks a network which card the player who sees the observation should pick.
+pub fn request_move(networks_dir: &Path, request: MoveRequest) -> Result<MoveResponse, ApiError>
+{
+	request.observation.validate().map_err(|e| ApiError::bad_request(format!("the observation isn't valid: {}", e)))?;
+
+	let policy = parse_policy(&request.policy)?;
+	let nn = load_network(networks_dir, &request.network)?;
+
+	let game = request.observation.to_game();
+
+	let card = pick_cards(&game, &nn, policy);
+
+	let mut cards = get_card_weights(&game, &nn);
+	cards.sort_by(|a, b| b.probability.partial_cmp(&a.probability).unwrap_or(std::cmp::Ordering::Equal));
+
+	Ok(MoveResponse{card, cards})
+}
+
+// Plays the games a batch at a time and works out how well each seat did.
+pub fn evaluate(networks_dir: &Path, request: EvaluateRequest) -> Result<EvaluateResponse, ApiError>
+{
+	if request.players.len() != NUMBER_OF_PLAYERS
+	{
+		return Err(ApiError::bad_request(format!("there are {} seats, but {} players were given", NUMBER_OF_PLAYERS, request.players.len())));
+	}
+
+	let total_games = request.games.saturating_mul(request.batches);
+
+	if total_games == 0 || total_games > MAX_EVALUATION_GAMES
+	{
+		return Err(ApiError::bad_request(format!("games times batches has to be between 1 and {}", MAX_EVALUATION_GAMES)));
+	}
+
+	let policy = parse_policy(&request.policy)?;
+
+	let mut players = Vec::new();
+
+	for name in &request.players
+	{
+		let player = match name.trim()
+		{
+			"random" => NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_neural_network()),
+			"heuristic" => NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Heuristic, empty_neural_network()),
+			file =>
+			{
+				let mut player = NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, load_network(networks_dir, file)?, policy);
+				player.name = file.to_string();
+				player
+			}
+		};
+
+		players.push(player);
+	}
+
+	let mut wins = [0; NUMBER_OF_PLAYERS];
+	let mut scores: Vec<Vec<f64>> = vec![Vec::new(); NUMBER_OF_PLAYERS];
+
+	for batch in 0..request.batches
+	{
+		for chunk_start in (0..request.games).step_by(EVALUATION_CHUNK_SIZE)
+		{
+			let chunk_games = std::cmp::min(EVALUATION_CHUNK_SIZE, request.games - chunk_start);
+
+			// Games take their players from the end of the pool, so each game's players are added last seat first.
+			let mut pool = Vec::new();
+
+			for _ in 0..chunk_games
+			{
+				pool.extend(players.iter().rev().cloned());
+			}
+
+			// Chunks carry on the seeds from where the last one stopped, so the deals don't depend on the chunk size.
+			let first_seed = request.seed.map(|x| x.wrapping_add((batch * request.games + chunk_start) as u64));
+
+			let mut games = Vec::new();
+			create_and_play_games_parallel(&mut games, chunk_games, &mut pool, first_seed);
+
+			for x in &games
+			{
+				wins[x.get_winning_id()] += 1;
+
+				for (i, score) in x.get_game().get_final_scores().iter().enumerate()
+				{
+					scores[i].push(*score as f64);
+				}
+			}
+		}
+	}
+
+	let seats = (0..NUMBER_OF_PLAYERS).map(|i| SeatStatistics
+	{
+		player: request.players[i].trim().to_string(),
+		wins: wins[i],
+		win_rate: wins[i] as f64 / total_games as f64,
+		win_rate_interval: wilson_interval(wins[i] as f64, total_games as f64),
+		mean_score: mean(&scores[i]),
+		score_interval: mean_confidence_interval(&scores[i]),
+	}).collect();
+
+	Ok(EvaluateResponse{games: total_games, seats})
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn evaluate_request(players: &[&str], games: usize, batches: usize) -> EvaluateRequest
+	{
+		EvaluateRequest{players: players.iter().map(|x| x.to_string()).collect(), games, batches, seed: Some(7), policy: None}
+	}
+
+	fn evaluate_status(request: EvaluateRequest) -> Option<u16>
+	{
+		evaluate(Path::new("res"), request).err().map(|e| e.status)
+	}
+
+	#[test]
+	fn load_network_stays_inside_the_directory()
+	{
+		for file in ["../champion.txt", "runs/../../champion.txt", "/etc/passwd"]
+		{
+			assert_eq!(load_network(Path::new("res"), file).err().map(|e| e.status), Some(400), "{} was let through", file);
+		}
+	}
+
+	#[test]
+	fn evaluate_needs_a_player_for_every_seat()
+	{
+		assert_eq!(evaluate_status(evaluate_request(&["random", "random", "random"], 1, 1)), Some(400));
+		assert_eq!(evaluate_status(evaluate_request(&["random"; NUMBER_OF_PLAYERS + 1], 1, 1)), Some(400));
+	}
+
+	#[test]
+	fn evaluate_limits_the_number_of_games()
+	{
+		let players = ["random"; NUMBER_OF_PLAYERS];
+
+		assert_eq!(evaluate_status(evaluate_request(&players, 0, 1)), Some(400));
+		assert_eq!(evaluate_status(evaluate_request(&players, MAX_EVALUATION_GAMES + 1, 1)), Some(400));
+		assert_eq!(evaluate_status(evaluate_request(&players, MAX_EVALUATION_GAMES / 2 + 1, 2)), Some(400));
+		assert_eq!(evaluate_status(evaluate_request(&players, usize::MAX, usize::MAX)), Some(400));
+
+		let response = evaluate(Path::new("res"), evaluate_request(&players, 5, 2)).ok().expect("a small evaluation was refused");
+		assert_eq!(response.games, 10);
+		assert_eq!(response.seats.iter().map(|x| x.wins).sum::<usize>(), 10);
+	}
+}
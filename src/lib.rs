@@ -0,0 +1,61 @@
+// The Sushi Go engine, the networks which learn to play it and the tools for training and testing them. The sushi_go_ai
+// and sushi_go_gui programs are thin front-ends over this library.
+//
+// The window for playing and watching games needs the "gui" feature, which is on by default. Without it games are
+// shown in the terminal instead.
+
+extern crate rand;
+
+#[macro_use]
+extern crate serde_derive;
+extern crate serde;
+extern crate serde_json;
+extern crate bincode;
+extern crate toml;
+extern crate rayon;
+extern crate rulinalg;
+
+#[macro_use]
+extern crate log;
+
+#[cfg(feature = "gui")]
+extern crate radiant_rs;
+#[cfg(feature = "gui")]
+extern crate radiant_utils as ru;
+extern crate crossterm;
+extern crate png;
+extern crate tiny_http;
+
+#[macro_use]
+extern crate enum_map;
+
+// The game rules, the players and the networks.
+pub mod neural_network_game;
+
+pub mod competition;
+
+pub mod statistics;
+
+pub mod rating;
+
+pub mod tournament;
+
+pub mod evaluation;
+
+pub mod metrics;
+
+pub mod benchmark;
+
+pub mod training_config;
+
+pub mod gen_algorithm;
+
+pub mod replay_viewer;
+
+pub mod render;
+
+pub mod server;
+
+pub mod client;
+
+pub mod http_api;
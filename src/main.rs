@@ -1,58 +1,19 @@
-// An attribute to hide warnings for unused code.
-#![allow(dead_code)]
+// The command line front-end, everything it runs is in the library.
 
-extern crate rand;
-extern crate random_choice;
+extern crate sushi_go_ai;
 
-#[macro_use]
-extern crate serde_derive;
-extern crate serde;
-extern crate serde_json;
-extern crate rayon;
-extern crate rulinalg;
-
-extern crate radiant_rs;
-extern crate radiant_utils as ru;
+extern crate env_logger;
 
 #[macro_use]
-extern crate enum_map;
-
-mod neural_network_game;
-use neural_network_game::*;
+extern crate clap;
 
-mod competition;
+mod cli;
+use cli::*;
 
-mod gen_algorithm;
-use gen_algorithm::*;
-
-use std::io;
-
-fn main() 
+fn main()
 {
-	println!("Type 'Play' to play against the last generated ai.");
-
-    println!("Type 'Create' to create a new ai.");
+	let matches = build_cli().get_matches();
 
-    let mut guess = String::new();
-
-    io::stdin().read_line(&mut guess)
-        .expect("Failed to read line");
-
-	match guess.trim()
-	{
-		"Play" => start_game_setup(),
-		"Create" => picked_generational(),
-        _ => println!("You didn't enter 'Play', or 'Create'. These are your only options, don't try and find anything else, there isn't anything to find."),
-	}
-}
-
-//fn picked_generational()
-//{
-	//run_generational(64, 1000);
-//}
-
-fn picked_generational()
-{
-	run_generational(get_usize_from_player_input("How many games per generation?"),
-					 get_usize_from_player_input("How many generations?"));
+	init_logger(&matches);
+	run_command(&matches);
 }
@@ -0,0 +1,179 @@
+use super::neural_network_game::*;
+use super::statistics::*;
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufWriter;
+use std::path::Path;
+
+// The name metrics are saved under in the output directory of a run, without the extension.
+pub const METRICS_FILE_NAME: &str = "metrics";
+
+// The formats the training metrics can be written in.
+#[derive(Copy,Clone,Debug,PartialEq,Default)]
+pub enum MetricsFormat
+{
+	// A header row followed by a row for each generation, for spreadsheets and plotting tools.
+	#[default]
+	Csv,
+
+	// A JSON object for each generation on its own line.
+	Jsonl,
+}
+
+impl fmt::Display for MetricsFormat
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match *self
+		{
+			MetricsFormat::Csv => write!(f, "csv"),
+			MetricsFormat::Jsonl => write!(f, "jsonl"),
+		}
+	}
+}
+
+impl MetricsFormat
+{
+	pub fn from_name(name: &str) -> Option<MetricsFormat>
+	{
+		match name.trim()
+		{
+			"csv" => Some(MetricsFormat::Csv),
+			"jsonl" => Some(MetricsFormat::Jsonl),
+			_ => None,
+		}
+	}
+}
+
+// What happened in one generation of a training run. A network's fitness is its final score.
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct GenerationMetrics
+{
+	pub generation: usize,
+	pub mean_fitness: f64,
+	pub max_fitness: f64,
+	pub min_fitness: f64,
+	pub mean_winning_score: f64,
+
+	// The average distance of each network's weights from the average of the population.
+	pub diversity: f64,
+
+	// How often the champion won against the benchmark opponents, only measured on some generations.
+	pub benchmark_win_rate: Option<f64>,
+
+	// Seconds since training started, and how long this generation took.
+	pub elapsed_seconds: f64,
+	pub generation_seconds: f64,
+}
+
+const CSV_HEADER: &str = "generation,mean_fitness,max_fitness,min_fitness,mean_winning_score,diversity,benchmark_win_rate,elapsed_seconds,generation_seconds";
+
+impl GenerationMetrics
+{
+	// Works out the fitness of a generation from the games it played.
+	pub fn from_games(generation: usize, games: &[NeuralNetworkGame], diversity: f64) -> GenerationMetrics
+	{
+		let scores: Vec<f64> = games.iter().flat_map(|x| x.get_game().get_final_scores()).map(|x| x as f64).collect();
+		let winning_scores: Vec<f64> = games.iter().map(|x| x.get_game().get_winning_score() as f64).collect();
+
+		GenerationMetrics
+		{
+			generation,
+			mean_fitness: mean(&scores),
+			max_fitness: scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+			min_fitness: scores.iter().cloned().fold(f64::INFINITY, f64::min),
+			mean_winning_score: mean(&winning_scores),
+			diversity,
+			benchmark_win_rate: None,
+			elapsed_seconds: 0.0,
+			generation_seconds: 0.0,
+		}
+	}
+
+	fn to_csv(&self) -> String
+	{
+		let benchmark = self.benchmark_win_rate.map_or(String::new(), |x| x.to_string());
+
+		format!("{},{},{},{},{},{},{},{},{}", self.generation, self.mean_fitness, self.max_fitness, self.min_fitness, self.mean_winning_score,
+				self.diversity, benchmark, self.elapsed_seconds, self.generation_seconds)
+	}
+
+	// A one line summary for watching a run as it trains.
+	pub fn print_summary(&self)
+	{
+		let benchmark = self.benchmark_win_rate.map_or("-".to_string(), |x| format!("{:.2}%", x * 100.0));
+
+		println!("Generation {:>6} | fitness mean {:>6.2} max {:>4} min {:>4} | winning score {:>6.2} | diversity {:>8.4} | benchmark {:>7} | {:>8.2}s",
+				 self.generation, self.mean_fitness, self.max_fitness, self.min_fitness, self.mean_winning_score, self.diversity, benchmark, self.elapsed_seconds);
+	}
+}
+
+// Writes the metrics of each generation as it finishes, so a run can be plotted while it trains.
+pub struct MetricsWriter
+{
+	writer: BufWriter<File>,
+	format: MetricsFormat,
+}
+
+impl MetricsWriter
+{
+	pub fn new(file_name: &Path, format: MetricsFormat) -> io::Result<MetricsWriter>
+	{
+		let mut writer = BufWriter::new(File::create(file_name)?);
+
+		if format == MetricsFormat::Csv
+		{
+			writeln!(writer, "{}", CSV_HEADER)?;
+		}
+
+		Ok(MetricsWriter{writer, format})
+	}
+
+	pub fn write(&mut self, metrics: &GenerationMetrics) -> io::Result<()>
+	{
+		match self.format
+		{
+			MetricsFormat::Csv => writeln!(self.writer, "{}", metrics.to_csv())?,
+			MetricsFormat::Jsonl => writeln!(self.writer, "{}", serde_json::to_string(metrics)?)?,
+		}
+
+		self.writer.flush()
+	}
+}
+
+// The average Euclidean distance of each network's weights and biases from the average network of the population.
+pub fn get_population_diversity(population: &[NeuralNetworkGamePlayer]) -> f64
+{
+	let parameters: Vec<Vec<f32>> = population.iter().map(|x| x.nn.get_parameters()).collect();
+
+	let count = match parameters.first()
+	{
+		Some(x) if !x.is_empty() => x.len(),
+		_ => return 0.0,
+	};
+
+	let mut centroid = vec![0.0f64; count];
+
+	for x in parameters.iter()
+	{
+		for (i, value) in x.iter().enumerate()
+		{
+			centroid[i] += *value as f64;
+		}
+	}
+
+	for x in centroid.iter_mut()
+	{
+		*x /= parameters.len() as f64;
+	}
+
+	let distances: Vec<f64> = parameters.iter()
+		.map(|x| x.iter().zip(centroid.iter()).map(|(a, b)| (*a as f64 - b) * (*a as f64 - b)).sum::<f64>().sqrt())
+		.collect();
+
+	mean(&distances)
+}
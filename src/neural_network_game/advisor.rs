@@ -0,0 +1,168 @@
+use super::neural_network::*;
+use super::model::*;
+use super::policy::*;
+use super::heuristic::*;
+
+use super::game::*;
+use super::game::player::card::*;
+
+// What the advisor thinks of one of the cards in the hand.
+#[derive(Clone, Debug)]
+pub struct CardAdvice
+{
+	pub card: Card,
+
+	// How likely the advisor's network is to pick the card, if it has one.
+	pub probability: Option<f32>,
+
+	// The average final score after picking the card and playing out the rest of the game, if rollouts were played.
+	pub expected_score: Option<f32>,
+
+	// How many of those rollouts were won, with ties shared between the winners.
+	pub win_rate: Option<f32>,
+}
+
+impl CardAdvice
+{
+	pub fn describe(&self) -> String
+	{
+		format!("{:<15} {}", self.get_card_name(), self.get_summary())
+	}
+
+	pub fn get_card_name(&self) -> String
+	{
+		match self.card
+		{
+			Card::None => "Keep chopsticks".to_string(),
+			card => card.to_string(),
+		}
+	}
+
+	// What the advisor found out about the card, without its name.
+	pub fn get_summary(&self) -> String
+	{
+		let mut parts = Vec::new();
+
+		if let (Some(score), Some(win_rate)) = (self.expected_score, self.win_rate)
+		{
+			parts.push(format!("expected score {:.1}, wins {:.0}%", score, win_rate * 100.0));
+		}
+
+		if let Some(probability) = self.probability
+		{
+			parts.push(format!("network {:.0}%", probability * 100.0));
+		}
+
+		parts.join(", ")
+	}
+}
+
+// Suggests cards for the current player, for humans who want a hint. Giving advice never changes the game.
+#[derive(Clone)]
+pub struct Advisor
+{
+	// Rates the cards directly, and is used to play out the rollouts if there are any.
+	network: Option<NeuralNetwork>,
+
+	// How many times the rest of the game is played out for each card, 0 to only ask the network.
+	rollouts: usize,
+}
+
+impl Advisor
+{
+	pub fn new(network: Option<NeuralNetwork>, rollouts: usize) -> Advisor
+	{
+		assert!(network.is_some() || rollouts > 0, "An advisor needs a network or rollouts to give advice!");
+
+		Advisor{network, rollouts}
+	}
+
+	// Rates every card the current player could pick, best first. With rollouts the best card is the one with the
+	// highest expected score, otherwise it's the one the network is most likely to pick.
+	pub fn advise(&self, game: &SushiGoGame) -> Vec<CardAdvice>
+	{
+		let mut cards: Vec<Card> = Vec::new();
+
+		for x in game.get_current_player_hand()
+		{
+			if !cards.contains(&x)
+			{
+				cards.push(x);
+			}
+		}
+
+		let weights = self.network.as_ref().map(|nn| get_card_weights(game, nn));
+
+		let mut advice: Vec<CardAdvice> = cards.iter().map(|card|
+		{
+			// Position based models have an output for each copy of a card in the hand.
+			let probability = weights.as_ref().map(|weights| weights.iter().filter(|x| x.card == *card).map(|x| x.probability).sum());
+
+			let (expected_score, win_rate) = if self.rollouts > 0
+			{
+				let (score, win_rate) = self.evaluate_card(game, *card);
+				(Some(score), Some(win_rate))
+			}
+			else
+			{
+				(None, None)
+			};
+
+			CardAdvice{card: *card, probability, expected_score, win_rate}
+		}).collect();
+
+		let rating = |x: &CardAdvice| x.expected_score.or(x.probability).unwrap_or(0.0);
+
+		advice.sort_by(|a, b| rating(b).partial_cmp(&rating(a)).unwrap_or(std::cmp::Ordering::Equal));
+
+		advice
+	}
+
+	// Plays out the rest of the game after the current player picks the card, once for each rollout, with the cards
+	// they can't see shuffled each time. Returns their average final score and the share of rollouts they won.
+	fn evaluate_card(&self, game: &SushiGoGame, card: Card) -> (f32, f32)
+	{
+		let player = game.get_current_player_id();
+		let mut rng = rand::thread_rng();
+
+		let mut total_score = 0.0;
+		let mut wins = 0.0;
+
+		for _ in 0..self.rollouts
+		{
+			let mut rollout = game.clone();
+			rollout.shuffle_hidden_cards(player, &mut rng);
+
+			let mut result = rollout.step(card);
+
+			while !result.is_game_over() && !result.is_error()
+			{
+				let next = self.pick_rollout_card(&rollout);
+				result = rollout.step(next);
+			}
+
+			let scores = rollout.get_final_scores();
+			let best = scores.iter().cloned().max().unwrap_or(0);
+			let winners = scores.iter().filter(|x| **x == best).count();
+
+			total_score += scores[player] as f32;
+
+			if scores[player] == best
+			{
+				wins += 1.0 / winners as f32;
+			}
+		}
+
+		(total_score / self.rollouts as f32, wins / self.rollouts as f32)
+	}
+
+	// Everyone plays out the rollouts like the network if there is one, otherwise like the heuristic player.
+	fn pick_rollout_card(&self, game: &SushiGoGame) -> Card
+	{
+		match &self.network
+		{
+			Some(nn) => pick_cards(game, nn, PolicyMode::Argmax),
+			None => do_heuristic_turn(game),
+		}
+	}
+}
@@ -0,0 +1,185 @@
+use super::NUMBER_OF_PLAYERS;
+use super::neural_network::*;
+use super::model::*;
+use super::policy::*;
+
+use super::game::*;
+use super::game::player::card::*;
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// How much one input pushed the network towards the card it rates highest.
+#[derive(Clone, Debug)]
+pub struct InputAttribution
+{
+	pub input: usize,
+	pub name: String,
+	pub value: f32,
+
+	// The probability of the top card with the input as it is, minus the probability with the input flipped. Positive
+	// if the input made the network more sure of its choice.
+	pub effect: f32,
+}
+
+// Why a network prefers the cards it does in one position.
+pub struct DecisionExplanation
+{
+	// Every card the current player is allowed to pick, most likely first. Probabilities are from a temperature 1
+	// softmax over the legal cards, so they add up to one.
+	pub cards: Vec<CardWeight>,
+
+	// Every input, the ones which changed the top card's probability most first.
+	pub attributions: Vec<InputAttribution>,
+}
+
+impl DecisionExplanation
+{
+	pub fn get_top_card(&self) -> Card
+	{
+		self.cards[0].card
+	}
+}
+
+// Ranks the cards the current player could pick and works out which inputs mattered by flipping each one in turn and
+// seeing how much the top card's probability changes. Every input is either 0 or 1, so flipping is the same as
+// asking what if that card was or wasn't there.
+pub fn explain_decision(game: &SushiGoGame, nn: &NeuralNetwork) -> DecisionExplanation
+{
+	let model = nn.get_model();
+
+	let input = model.get_nn_input(game);
+	let legal_actions = model.get_legal_actions(game);
+
+	let output = nn.feed_forward(&input);
+	let probabilities = get_action_probabilities(&output, &legal_actions, 1.0);
+
+	// Most likely first. Position based models can have more than one output for the same card.
+	let mut actions: Vec<usize> = (0..output.len()).filter(|x| legal_actions[*x]).collect();
+	actions.sort_by(|a, b| probabilities[*b].partial_cmp(&probabilities[*a]).unwrap_or(std::cmp::Ordering::Equal));
+
+	let top_action = *actions.first().expect("There were no legal actions to explain!");
+	let cards = actions.iter().map(|x| CardWeight{card: model.get_action_card(game, *x), weight: output[*x], probability: probabilities[*x]}).collect();
+
+	let mut attributions = Vec::new();
+	let mut flipped = input.clone();
+
+	for i in 0..input.len()
+	{
+		flipped[i] = 1.0 - input[i];
+
+		let flipped_probabilities = get_action_probabilities(&nn.feed_forward(&flipped), &legal_actions, 1.0);
+
+		attributions.push(InputAttribution{input: i, name: model.get_input_name(i), value: input[i], effect: probabilities[top_action] - flipped_probabilities[top_action]});
+
+		flipped[i] = input[i];
+	}
+
+	attributions.sort_by(|a, b| b.effect.abs().partial_cmp(&a.effect.abs()).unwrap_or(std::cmp::Ordering::Equal));
+
+	DecisionExplanation{cards, attributions}
+}
+
+// Deals a game from the seed and plays random cards for a random number of turns, always stopping before the game
+// is over. Returns the position the next player has to pick a card in.
+pub fn create_sample_position(seed: u64) -> SushiGoGame
+{
+	let mut rng = StdRng::seed_from_u64(seed);
+
+	let mut game = SushiGoGame::new(NUMBER_OF_PLAYERS);
+	game.setup_with_seed(seed);
+
+	// Using chopsticks only adds turns, so there are always at least this many before the game ends.
+	let minimum_turns = NUMBER_OF_PLAYERS * game.get_current_player().get_hand_size() * game.get_number_of_rounds() as usize;
+
+	for _ in 0..rng.gen_range(0, minimum_turns)
+	{
+		let hand = game.get_current_player_hand();
+		let card = hand[rng.gen_range(0, hand.len())];
+
+		game.step(card);
+	}
+
+	game
+}
+
+pub fn print_decision_explanation(game: &SushiGoGame, explanation: &DecisionExplanation, top_inputs: usize)
+{
+	let player = game.get_current_player_id();
+
+	println!("Round {}, player {}{}", game.get_current_round() + 1, player, if game.is_using_chopsticks() { " using chopsticks" } else { "" });
+	println!("Hand: {}", format_cards(&game.get_current_player_hand()));
+	println!("Chosen: {}", format_cards(&game.get_player_chosen(player)));
+
+	println!("\nCards, most likely first:");
+
+	for x in &explanation.cards
+	{
+		println!("  {:<15} output {:>8.3} probability {:>6.2}%", x.card.to_string(), x.weight, x.probability * 100.0);
+	}
+
+	println!("\nInputs which changed the chance of picking {} most:", explanation.get_top_card());
+
+	for x in explanation.attributions.iter().take(top_inputs)
+	{
+		println!("  {:<30} {} {:>+7.2}%", x.name, if x.value > 0.0 { "on " } else { "off" }, x.effect * 100.0);
+	}
+}
+
+// Explains the network's choice in a sample of positions dealt from consecutive seeds, then sums up which cards it
+// preferred and which inputs it relied on across all of them.
+pub fn print_explanation_report(nn: &NeuralNetwork, positions: usize, first_seed: u64, top_inputs: usize)
+{
+	let model = nn.get_model();
+
+	let mut top_cards = vec![0; Card::CardMax as usize];
+	let mut total_confidence = 0.0;
+	let mut total_effects = vec![0.0; model.get_node_count().input_node_count];
+
+	for i in 0..positions
+	{
+		let seed = first_seed.wrapping_add(i as u64);
+		let game = create_sample_position(seed);
+		let explanation = explain_decision(&game, nn);
+
+		println!("\n---- Position {} (seed {})", i, seed);
+		print_decision_explanation(&game, &explanation, top_inputs);
+
+		top_cards[explanation.get_top_card() as usize] += 1;
+		total_confidence += explanation.cards[0].probability;
+
+		for x in &explanation.attributions
+		{
+			total_effects[x.input] += x.effect.abs();
+		}
+	}
+
+	if positions == 0
+	{
+		return;
+	}
+
+	println!("\n---- Summary of {} positions", positions);
+	println!("Average probability of the top card: {:.2}%", total_confidence / positions as f32 * 100.0);
+
+	println!("\nHow often each card was the top choice:");
+
+	let mut cards: Vec<usize> = (0..top_cards.len()).filter(|x| top_cards[*x] > 0).collect();
+	cards.sort_by(|a, b| top_cards[*b].cmp(&top_cards[*a]));
+
+	for x in cards
+	{
+		println!("  {:<15} {:>5} ({:.1}%)", Card::from(x).to_string(), top_cards[x], top_cards[x] as f32 / positions as f32 * 100.0);
+	}
+
+	println!("\nInputs with the largest average effect on the top card:");
+
+	let mut inputs: Vec<usize> = (0..total_effects.len()).collect();
+	inputs.sort_by(|a, b| total_effects[*b].partial_cmp(&total_effects[*a]).unwrap_or(std::cmp::Ordering::Equal));
+
+	for x in inputs.into_iter().take(top_inputs)
+	{
+		println!("  {:<30} {:>6.2}%", model.get_input_name(x), total_effects[x] / positions as f32 * 100.0);
+	}
+}
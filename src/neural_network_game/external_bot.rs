@@ -0,0 +1,433 @@
+// Lets bots written in any language play, by running them as a separate program and talking to them over its stdin
+// and stdout.
+//
+// The protocol is line delimited JSON like the server's: every message is one JSON object on its own line, with a
+// "type" field saying which message it is. Cards are named as in the replays, such as "SalmonNigiri", with "None"
+// meaning keep your chopsticks. Anything the bot writes to stderr is passed through, so it can be used for logging.
+//
+// Engine to bot:
+//   {"type":"hello","protocol_version":1}
+//       Sent once when the bot is started. Answer with ready.
+//   {"type":"choose","id":7,"observation":{"round":0,"player":1,"hand":[...],"chosen":[[...],...],"scores":[...],
+//       "puddings":[...],"using_chopsticks":false,"legal_actions":[...]}}
+//       Pick a card for the player who sees the observation, which is the same as the server's your_turn. Every
+//       request stands on its own: the same bot can be asked about many games, in any order.
+//   {"type":"quit"}
+//       Sent when the bot isn't needed any more. It is killed if it hasn't exited shortly after.
+//
+// Bot to engine:
+//   {"type":"ready","name":"my bot"}
+//       The name is optional and is shown when the bot plays.
+//   {"type":"action","id":7,"card":"Tempura"}
+//       The answer to the choose request with the same id. Must be one of its legal_actions.
+//
+// Lines which can't be understood, and answers to requests which have already timed out, are ignored. If the bot
+// answers late, picks a card it isn't allowed to or exits, a random card is picked for it instead.
+
+use super::game::*;
+use super::game::player::card::*;
+use super::observation::*;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// How long a bot has to answer the hello message after it is started.
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How long a bot has to pick each card.
+pub const DEFAULT_MOVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// How long a bot has to exit after being told to quit.
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EngineMessage
+{
+	Hello{protocol_version: u32},
+	Choose{id: u64, observation: Observation},
+	Quit,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage
+{
+	Ready{#[serde(default)] name: Option<String>},
+	Action{id: u64, card: Card},
+}
+
+#[derive(Debug)]
+pub enum ExternalBotError
+{
+	// The command couldn't be started.
+	Spawn(io::Error),
+	Io(io::Error),
+	Timeout,
+
+	// The bot closed its stdout, which usually means it has exited.
+	Closed,
+	IllegalCard(Card),
+	Protocol(String),
+}
+
+impl fmt::Display for ExternalBotError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			ExternalBotError::Spawn(e) => write!(f, "couldn't start the bot: {}", e),
+			ExternalBotError::Io(e) => write!(f, "couldn't talk to the bot: {}", e),
+			ExternalBotError::Timeout => write!(f, "the bot didn't answer in time"),
+			ExternalBotError::Closed => write!(f, "the bot has exited"),
+			ExternalBotError::IllegalCard(card) => write!(f, "the bot picked {}, which it isn't allowed to", card),
+			ExternalBotError::Protocol(message) => write!(f, "the bot didn't follow the protocol: {}", message),
+		}
+	}
+}
+
+impl Error for ExternalBotError {}
+
+impl From<io::Error> for ExternalBotError
+{
+	fn from(e: io::Error) -> ExternalBotError
+	{
+		ExternalBotError::Io(e)
+	}
+}
+
+// The running bot program. Only one request is sent to it at a time.
+struct BotProcess
+{
+	child: Child,
+	stdin: ChildStdin,
+
+	// The lines the bot writes to stdout, read on their own thread so waiting for them can time out.
+	lines: mpsc::Receiver<String>,
+
+	next_id: u64,
+
+	// Set once the bot has exited, after which it isn't asked again.
+	closed: bool,
+}
+
+impl BotProcess
+{
+	fn send(&mut self, message: &EngineMessage) -> Result<(), ExternalBotError>
+	{
+		let line = serde_json::to_string(message).map_err(|e| ExternalBotError::Protocol(e.to_string()))?;
+
+		writeln!(self.stdin, "{}", line)?;
+		self.stdin.flush()?;
+
+		Ok(())
+	}
+
+	// Reads messages until one is accepted by the filter, skipping lines which can't be understood. Fails if nothing
+	// is accepted before the timeout.
+	fn read_message<T, F>(&mut self, timeout: Duration, mut accept: F) -> Result<T, ExternalBotError>
+		where F: FnMut(BotMessage) -> Option<T>
+	{
+		let deadline = Instant::now() + timeout;
+
+		loop
+		{
+			let remaining = deadline.checked_duration_since(Instant::now()).ok_or(ExternalBotError::Timeout)?;
+
+			let line = match self.lines.recv_timeout(remaining)
+			{
+				Ok(line) => line,
+				Err(mpsc::RecvTimeoutError::Timeout) => return Err(ExternalBotError::Timeout),
+				Err(mpsc::RecvTimeoutError::Disconnected) =>
+				{
+					if !self.closed
+					{
+						warn!("A bot has exited, random cards are picked for it from now on");
+					}
+
+					self.closed = true;
+					return Err(ExternalBotError::Closed);
+				}
+			};
+
+			if line.trim().is_empty()
+			{
+				continue;
+			}
+
+			match serde_json::from_str(&line)
+			{
+				Ok(message) => if let Some(x) = accept(message)
+				{
+					return Ok(x);
+				},
+				Err(e) => warn!("Couldn't read a message from a bot: {}", e),
+			}
+		}
+	}
+
+	fn request_card(&mut self, observation: Observation, timeout: Duration) -> Result<Card, ExternalBotError>
+	{
+		if self.closed
+		{
+			return Err(ExternalBotError::Closed);
+		}
+
+		let id = self.next_id;
+		self.next_id += 1;
+
+		let legal_actions = observation.legal_actions.clone();
+
+		self.send(&EngineMessage::Choose{id, observation})?;
+
+		// Late answers to earlier requests are skipped.
+		let card = self.read_message(timeout, |x| match x
+		{
+			BotMessage::Action{id: answered, card} if answered == id => Some(card),
+			_ => None,
+		})?;
+
+		if legal_actions.contains(&card)
+		{
+			Ok(card)
+		}
+		else
+		{
+			Err(ExternalBotError::IllegalCard(card))
+		}
+	}
+}
+
+impl Drop for BotProcess
+{
+	fn drop(&mut self)
+	{
+		let _ = self.send(&EngineMessage::Quit);
+
+		let deadline = Instant::now() + QUIT_TIMEOUT;
+
+		while Instant::now() < deadline
+		{
+			match self.child.try_wait()
+			{
+				Ok(None) => thread::sleep(Duration::from_millis(10)),
+				_ => return,
+			}
+		}
+
+		let _ = self.child.kill();
+		let _ = self.child.wait();
+	}
+}
+
+// A bot running as another program. Clones share the same program, so a bot in many games at once answers them one
+// at a time.
+#[derive(Clone)]
+pub struct ExternalBot
+{
+	// The command line the bot was started with.
+	command: String,
+
+	// The name the bot gave itself, or the command if it didn't.
+	name: String,
+
+	move_timeout: Duration,
+
+	process: Arc<Mutex<BotProcess>>,
+}
+
+impl ExternalBot
+{
+	// Starts the program and waits for it to say it's ready. The command is split on whitespace into the program and
+	// its arguments, it isn't run through a shell.
+	pub fn spawn(command: &str, move_timeout: Duration) -> Result<ExternalBot, ExternalBotError>
+	{
+		let mut parts = command.split_whitespace();
+		let program = parts.next().ok_or_else(|| ExternalBotError::Spawn(io::Error::new(io::ErrorKind::InvalidInput, "the command is empty")))?;
+
+		let mut child = Command::new(program)
+			.args(parts)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::inherit())
+			.spawn()
+			.map_err(ExternalBotError::Spawn)?;
+
+		let stdin = child.stdin.take().expect("The bot's stdin wasn't piped!");
+		let stdout = child.stdout.take().expect("The bot's stdout wasn't piped!");
+
+		let (sender, lines) = mpsc::channel();
+
+		// Ends when the bot closes its stdout or there's no one left to read the lines.
+		thread::spawn(move ||
+		{
+			for line in BufReader::new(stdout).lines()
+			{
+				match line
+				{
+					Ok(line) => if sender.send(line).is_err()
+					{
+						break;
+					},
+					Err(_) => break,
+				}
+			}
+		});
+
+		let mut process = BotProcess{child, stdin, lines, next_id: 0, closed: false};
+
+		process.send(&EngineMessage::Hello{protocol_version: PROTOCOL_VERSION})?;
+
+		let name = process.read_message(READY_TIMEOUT, |x| match x
+		{
+			BotMessage::Ready{name} => Some(name),
+			_ => None,
+		})?;
+
+		let name = name.map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).unwrap_or_else(|| command.trim().to_string());
+
+		info!("Started bot {} with {}", name, command.trim());
+
+		Ok(ExternalBot{command: command.trim().to_string(), name, move_timeout, process: Arc::new(Mutex::new(process))})
+	}
+
+	pub fn get_name(&self) -> &str
+	{
+		&self.name
+	}
+
+	pub fn get_command(&self) -> &str
+	{
+		&self.command
+	}
+
+	// Asks the bot which card the current player should pick. The move timeout only starts once the bot is free: if it
+	// is busy answering other games this waits for them first, which is at most one move timeout for each game ahead.
+	pub fn request_card(&self, game: &SushiGoGame) -> Result<Card, ExternalBotError>
+	{
+		let observation = Observation::new(game, game.get_current_player_id());
+
+		// The lock is only poisoned if a game panicked while waiting, which doesn't leave the bot in a bad state.
+		let mut process = self.process.lock().unwrap_or_else(|e| e.into_inner());
+
+		process.request_card(observation, self.move_timeout)
+	}
+
+	// Asks the bot which card to pick, picking a random one instead if it can't give a legal answer in time.
+	pub fn pick_card(&self, game: &SushiGoGame) -> Card
+	{
+		match self.request_card(game)
+		{
+			Ok(card) => card,
+			Err(ExternalBotError::Closed) => super::do_random_turn(game),
+			Err(e) =>
+			{
+				warn!("Bot {} couldn't pick a card, a random one is picked instead: {}", self.name, e);
+				super::do_random_turn(game)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	use std::env;
+	use std::fs;
+	use std::process;
+
+	// Starts a bot which runs the given shell script, answering with canned lines.
+	fn spawn_script(name: &str, script: &str, move_timeout: Duration) -> Result<ExternalBot, ExternalBotError>
+	{
+		let path = env::temp_dir().join(format!("sushi_go_{}_{}.sh", name, process::id()));
+		fs::write(&path, script).unwrap();
+
+		let bot = ExternalBot::spawn(&format!("sh {}", path.display()), move_timeout);
+
+		// The shell has the script open by the time it says it's ready, so it can be removed already.
+		let _ = fs::remove_file(&path);
+
+		bot
+	}
+
+	// The first player picks from a hand of Tempura and Sashimi.
+	fn test_game() -> SushiGoGame
+	{
+		SushiGoGame::from_position(0, 0, vec![Card::Tempura, Card::Sashimi], vec![Vec::new(), Vec::new()], false)
+	}
+
+	// Only answers the hello message, then never says anything again.
+	const READY_ONLY: &str = "read line
+case \"$line\" in *'\"type\":\"hello\"'*) echo '{\"type\":\"ready\",\"name\":\"Scripted bot\"}';; esac
+cat > /dev/null
+";
+
+	#[test]
+	fn ready_after_hello()
+	{
+		let bot = spawn_script("ready", READY_ONLY, DEFAULT_MOVE_TIMEOUT).unwrap();
+
+		assert_eq!(bot.get_name(), "Scripted bot");
+	}
+
+	#[test]
+	fn timeout_picks_a_random_card()
+	{
+		let bot = spawn_script("timeout", READY_ONLY, Duration::from_millis(200)).unwrap();
+		let game = test_game();
+
+		assert!(matches!(bot.request_card(&game), Err(ExternalBotError::Timeout)));
+		assert!(game.get_player_hand(0).contains(&bot.pick_card(&game)));
+	}
+
+	#[test]
+	fn illegal_card_is_rejected()
+	{
+		let script = "read line
+echo '{\"type\":\"ready\"}'
+read line
+echo '{\"type\":\"action\",\"id\":0,\"card\":\"Pudding\"}'
+cat > /dev/null
+";
+		let bot = spawn_script("illegal", script, DEFAULT_MOVE_TIMEOUT).unwrap();
+
+		assert!(matches!(bot.request_card(&test_game()), Err(ExternalBotError::IllegalCard(Card::Pudding))));
+	}
+
+	#[test]
+	fn late_answer_is_skipped()
+	{
+		// Answers the first request after it has timed out, then the second straight away.
+		let script = "read line
+echo '{\"type\":\"ready\"}'
+read line
+sleep 0.5
+echo '{\"type\":\"action\",\"id\":0,\"card\":\"Sashimi\"}'
+read line
+echo '{\"type\":\"action\",\"id\":1,\"card\":\"Tempura\"}'
+cat > /dev/null
+";
+		let bot = spawn_script("late", script, Duration::from_millis(200)).unwrap();
+		let game = test_game();
+
+		assert!(matches!(bot.request_card(&game), Err(ExternalBotError::Timeout)));
+
+		// Give the late answer time to arrive, so it is waiting when the next request is read.
+		thread::sleep(Duration::from_secs(1));
+
+		assert_eq!(bot.request_card(&game).unwrap(), Card::Tempura);
+	}
+}
@@ -1,8 +1,10 @@
 use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::fmt;
 
 //----------------------------------- Game Rules ----------------------------------------------
-const NUMBER_OF_ROUNDS: u8 = 3;
+pub const NUMBER_OF_ROUNDS: u8 = 3;
 pub const MAX_HAND_SIZE: usize = 8;
 
 const PUDDING_COUNT: u8 =		10;
@@ -30,8 +32,6 @@ use player::card::*;
 
 use score::*;
 
-const PRINT_DATA: bool = false;
-
 //--------------------------------- Start Game Implementation ------------------------------------
 #[derive(Copy,Clone)]
 pub enum StepResult
@@ -63,27 +63,23 @@ impl fmt::Display for StepResult
 
 impl StepResult {
 	pub fn is_game_over(&self) -> bool {
-		match *self {
-			StepResult::GameOver => true,
-			_ => false,
-		}
+		matches!(*self, StepResult::GameOver)
 	}
 
 	pub fn is_chopsticks_available(&self) -> bool {
-		match *self {
-			StepResult::ChopsticksAvailable => true,
-			_ => false,
-		}
+		matches!(*self, StepResult::ChopsticksAvailable)
 	}
 
 	pub fn is_round_over(&self) -> bool {
-		match *self {
-			StepResult::RoundOver => true,
-			_ => false,
-		}
+		matches!(*self, StepResult::RoundOver)
+	}
+
+	pub fn is_error(&self) -> bool {
+		matches!(*self, StepResult::Error)
 	}
 }
 
+#[derive(Clone)]
 pub struct SushiGoGame
 {
 	deck: Vec<Card>,
@@ -94,17 +90,56 @@ pub struct SushiGoGame
 	number_of_players: usize,
 	winner: usize,
 	using_chopsticks: bool,
-	print_nn_weights: bool
+
+	// The seed used to shuffle the deck, if the game was seeded.
+	seed: Option<u64>
 }
 
 impl SushiGoGame
 {
 	pub fn new(number_of_players: usize) -> SushiGoGame
 	{
-		return SushiGoGame{deck: Vec::new(), players: Vec::new(), current_player: 0, game_over: false, current_round: 0, number_of_players, winner: 0, using_chopsticks: false, print_nn_weights: false};
+		SushiGoGame{deck: Vec::new(), players: Vec::new(), current_player: 0, game_over: false, current_round: 0, number_of_players, winner: 0, using_chopsticks: false, seed: None}
+	}
+
+	// Creates a game at the point where a player has to pick a card, knowing only their hand and the cards everyone
+	// has chosen this round. The other hands and the deck are empty, so the game can be used to pick a card but not
+	// played on.
+	pub fn from_position(current_player: usize, round: u8, hand: Vec<Card>, chosen: Vec<Vec<Card>>, using_chopsticks: bool) -> SushiGoGame
+	{
+		assert!(current_player < chosen.len(), "The current player isn't in the game!");
+
+		let mut game = SushiGoGame::new(chosen.len());
+
+		game.players = setup_players(chosen.len() as u8);
+
+		for (player, cards) in game.players.iter_mut().zip(chosen)
+		{
+			player.replace_chosen_cards(cards);
+		}
+
+		game.players[current_player].replace_hand(hand);
+		game.current_player = current_player;
+		game.current_round = round;
+		game.using_chopsticks = using_chopsticks;
+
+		game
 	}
 
 	pub fn setup(&mut self)
+	{
+		self.seed = None;
+		self.setup_with_rng(&mut rand::thread_rng());
+	}
+
+	// Sets up the game so the deck is always shuffled the same way for the same seed.
+	pub fn setup_with_seed(&mut self, seed: u64)
+	{
+		self.seed = Some(seed);
+		self.setup_with_rng(&mut StdRng::seed_from_u64(seed));
+	}
+
+	fn setup_with_rng<R: Rng>(&mut self, rng: &mut R)
 	{
 		//---- Setup
 		self.current_player = 0;
@@ -113,31 +148,19 @@ impl SushiGoGame
 		self.current_round = 0;
 
 		self.deck = setup_deck();
-		let mut rng = rand::thread_rng();
-		self.deck.shuffle(&mut rng);
+		self.deck.shuffle(rng);
 
 		// ---- Print all the cards in the deck.
-		if PRINT_DATA
-		{
-			self.print_deck();
-		}
+		self.print_deck();
 
 		//---- Create players.
 		self.players = setup_players(4);
 
 		//---- Deal players some cards.
-		if PRINT_DATA
-		{
-			print!("Dealing hands.");
-		}
-
 		deal_cards(&mut self.players, &mut self.deck);
 
-		if PRINT_DATA
-		{
-			self.print_hands();
-			print!("Starting Round {}.", self.current_round + 1);
-		}
+		self.print_hands();
+		debug!("Starting round {}", self.current_round + 1);
 	}
 
 	// Tries to play a specific card for the current player.
@@ -160,22 +183,7 @@ impl SushiGoGame
 		let mut result = StepResult::NoResult;
 		let player = &mut self.players[self.current_player];
 
-		if PRINT_DATA
-		{
-			print!("\nStarting turn for player {}.", player.get_id());
-
-			print!("\nCards in hand:\n");
-
-			print_cards(&player.get_hand());
-
-			print!("\nChosen cards:\n");
-			if PRINT_DATA
-			{
-				print_cards(&player.get_chosen_cards());
-			}
-
-			print!("\nChoosing cards...\n");
-		}
+		trace!("Starting turn for player {}, hand: {}, chosen: {}", player.get_id(), format_cards(&player.get_hand()), format_cards(&player.get_chosen_cards()));
 
 		// As long as we have not chosen to play no card, adjust the players hand.
 		if action != Card::None
@@ -191,22 +199,12 @@ impl SushiGoGame
 			result = if player.choose_card(action) { StepResult::Success } else { StepResult::Error };
 		}
 
-		if PRINT_DATA
-		{
-			print!("Cards in hand:\n");
-			print_cards(&player.get_hand());
-
-			print!("\nChosen cards:\n");
-			print_cards(&player.get_chosen_cards());
-		}
+		trace!("Player {} picked {}, hand: {}, chosen: {}", player.get_id(), action, format_cards(&player.get_hand()), format_cards(&player.get_chosen_cards()));
 
 		// Check to see if the current player has fresh chopsticks and give them another turn
 		if player.has_chopsticks() && !self.using_chopsticks
 		{
-			if PRINT_DATA
-			{
-				print!("We have chopsticks in our chosen cards, adding none option and letting us take another go.\n");
-			}
+			trace!("Player {} has chopsticks in their chosen cards, adding a none option and letting them take another go", player.get_id());
 
 			self.using_chopsticks = true;
             player.add_card_to_hand(Card::None);
@@ -240,7 +238,7 @@ impl SushiGoGame
 			self.swap_hands();
 		}
 
-		return result;
+		result
 	}
 
 	fn sanitize_hands(&mut self)
@@ -261,16 +259,13 @@ impl SushiGoGame
 			}
 		}
 
-		return true;
+		true
 	}
 
 	fn on_round_end(&mut self)
 	{
-		if PRINT_DATA
-		{
-			print!("Completed Round {}.\n", self.current_round + 1);
-			self.print_chosen();
-		}
+		debug!("Completed round {}", self.current_round + 1);
+		self.print_chosen();
 
 		self.current_round += 1;
 		self.current_player = 0;
@@ -291,10 +286,7 @@ impl SushiGoGame
 			deal_cards(&mut self.players, &mut self.deck);
 			self.print_hands();
 
-			if PRINT_DATA
-			{
-				print!("Starting Round {}:", self.current_round + 1);
-			}
+			debug!("Starting round {}", self.current_round + 1);
 		}
 	}
 
@@ -328,7 +320,7 @@ impl SushiGoGame
 		{
 			hand = self.players[id].get_hand();
 		}
-		return hand;
+		hand
 	}
 
 	// Returns the chosen cards of the player with the given ID
@@ -340,7 +332,7 @@ impl SushiGoGame
 		{
 			chosen = self.players[id].get_chosen_cards();
 		}
-		return chosen;
+		chosen
 	}
 
 	// Returns the ID of the current player
@@ -362,7 +354,7 @@ impl SushiGoGame
 
 	pub fn get_winning_score(&self) -> i32
 	{
-		return self.players[self.winner].get_final_score();
+		self.players[self.winner].get_final_score()
 	}
 
 	pub fn get_winner(&self) -> usize
@@ -370,50 +362,140 @@ impl SushiGoGame
 		self.winner
 	}
 
-	fn print_deck(&self)
+	// Returns the final score of every player, indexed by player ID.
+	pub fn get_final_scores(&self) -> Vec<i32>
+	{
+		self.players.iter().map(|x| x.get_final_score()).collect()
+	}
+
+	pub fn get_seed(&self) -> Option<u64>
+	{
+		self.seed
+	}
+
+	// Returns which round is being played, starting from 0.
+	pub fn get_current_round(&self) -> u8
+	{
+		self.current_round
+	}
+
+	// True if the current player is picking a second card by using their chopsticks.
+	pub fn is_using_chopsticks(&self) -> bool
+	{
+		self.using_chopsticks
+	}
+
+	// Returns the hand of every player, indexed by player ID.
+	pub fn get_hands(&self) -> Vec<Vec<Card>>
+	{
+		self.players.iter().map(|x| x.get_hand()).collect()
+	}
+
+	// Returns the score every player got for a round which has finished, not counting puddings.
+	pub fn get_round_scores(&self, round: u8) -> Vec<i32>
+	{
+		assert!(round < self.current_round, "Tried to get the scores for a round which hasn't finished!");
+		calc_scores_for_round(&self.players, round)
+	}
+
+	// Returns the score every player gets for their puddings at the end of the game.
+	pub fn get_pudding_scores(&self) -> Vec<i32>
+	{
+		calc_pudding_scores_for_game(&self.players, NUMBER_OF_ROUNDS)
+	}
+
+	// Returns each player's score from the rounds which have finished, not counting puddings.
+	pub fn get_running_scores(&self) -> Vec<i32>
 	{
-		if !PRINT_DATA
+		let mut scores = vec![0; self.players.len()];
+
+		for round in 0..std::cmp::min(self.current_round, NUMBER_OF_ROUNDS)
 		{
-			return;
+			for (i, x) in calc_scores_for_round(&self.players, round).iter().enumerate()
+			{
+				scores[i] += *x;
+			}
 		}
 
-		print!("Cards in deck:");
-		for x in &self.deck 
+		scores
+	}
+
+	// Returns how many puddings each player kept in the rounds which have finished.
+	pub fn get_pudding_counts(&self) -> Vec<i16>
+	{
+		let rounds = std::cmp::min(self.current_round, NUMBER_OF_ROUNDS) as usize;
+
+		self.players.iter().map(|x| (0..rounds).map(|i| x.get_round_result(i).pudding_count).sum()).collect()
+	}
+
+	pub fn get_number_of_rounds(&self) -> u8
+	{
+		NUMBER_OF_ROUNDS
+	}
+
+	// Replaces the hand of every player, indexed by player ID. Used to play back a recorded deal.
+	pub fn set_hands(&mut self, hands: Vec<Vec<Card>>)
+	{
+		assert!(hands.len() == self.players.len(), "The number of hands does not match the game!");
+
+		for (player, hand) in self.players.iter_mut().zip(hands)
 		{
-			print!("{}", x);
+			player.replace_hand(hand);
 		}
-		print!("\n");
 	}
 
-	fn print_hands(&self)
+	// Shuffles together the cards the viewer can't see, which are every other player's hand and the rest of the deck,
+	// then deals them back out so everyone keeps the same number of cards. Used to guess at what is hidden without
+	// cheating, although hands the viewer has already seen are shuffled too.
+	pub fn shuffle_hidden_cards<R: Rng>(&mut self, viewer: usize, rng: &mut R)
 	{
-		if !PRINT_DATA
+		let mut hidden = self.deck.clone();
+
+		for (i, x) in self.players.iter().enumerate()
 		{
-			return;
+			if i != viewer
+			{
+				hidden.extend(x.get_hand());
+			}
 		}
 
-		//---- Print all the cards each player has.
-		for x in self.players.iter()
+		hidden.shuffle(rng);
+
+		let deck_size = self.deck.len();
+		self.deck = hidden.split_off(hidden.len() - deck_size);
+
+		for (i, x) in self.players.iter_mut().enumerate()
 		{
-			print!("Hand for player {}:", x.get_id());
-			print_cards(&x.get_hand());
-			print!("\n");
+			if i != viewer
+			{
+				let hand_size = x.get_hand_size();
+				let hand = hidden.split_off(hidden.len() - hand_size);
+
+				x.replace_hand(hand);
+			}
 		}
 	}
 
-	fn print_chosen(&self)
+	fn print_deck(&self)
 	{
-		if !PRINT_DATA
+		trace!("Cards in deck: {}", format_cards(&self.deck));
+	}
+
+	fn print_hands(&self)
+	{
+		//---- Print all the cards each player has.
+		for x in self.players.iter()
 		{
-			return;
+			trace!("Hand for player {}: {}", x.get_id(), format_cards(&x.get_hand()));
 		}
+	}
 
+	fn print_chosen(&self)
+	{
 		//---- Print all the cards each player has.
 		for x in self.players.iter()
 		{
-			print!("Chosen for player {}:", x.get_id());
-			print_cards(&x.get_chosen_cards());
-			print!("\n");
+			trace!("Chosen for player {}: {}", x.get_id(), format_cards(&x.get_chosen_cards()));
 		}
 	}
 
@@ -432,7 +514,7 @@ impl SushiGoGame
 			// Add the scores together.
 			for (i, x) in round_scores.iter().enumerate()
 			{
-				print!("Score for player {0}: {1}\n", i, *x);
+				println!("Score for player {0}: {1}", i, *x);
 			}
 		}
 	}
@@ -442,12 +524,12 @@ impl SushiGoGame
 		print!("\nPudding results:\n");
 		for x in calc_pudding_counts_for_game(&self.players, NUMBER_OF_ROUNDS).iter()
 		{
-			print!("Pudding count for player {0}: {1}\n", x.0, x.1);
+			println!("Pudding count for player {0}: {1}", x.0, x.1);
 		}
 		// Add on score for puddings.
 		for (i, x) in calc_pudding_scores_for_game(&self.players, NUMBER_OF_ROUNDS).iter().enumerate()
 		{
-			print!("Pudding scores for player {0}: {1}\n", i, *x);
+			println!("Pudding scores for player {0}: {1}", i, *x);
 		}
 	}
 
@@ -458,16 +540,13 @@ impl SushiGoGame
 		//---- Print all the final scores
 		for x in self.players.iter()
 		{
-			print!("Final score for player {0}: {1}\n", x.get_id(), x.get_final_score());
+			println!("Final score for player {0}: {1}", x.get_id(), x.get_final_score());
 		}
 	}
 
 	fn calculate_winner(&mut self) -> usize
 	{
-		if PRINT_DATA
-		{
-			print!("Finished Playing!\n");
-		}
+		debug!("Finished playing");
 
 		//---- Scoring the game
 		let mut final_scores = vec![0; self.players.len()];
@@ -488,10 +567,7 @@ impl SushiGoGame
 		// Add on score for puddings.
 		for (i, x) in calc_pudding_scores_for_game(&self.players, NUMBER_OF_ROUNDS).iter().enumerate()
 		{
-			if PRINT_DATA
-			{
-				print!("Pudding scores for player {0}: {1}\n", i, *x);
-			}
+			trace!("Pudding score for player {0}: {1}", i, *x);
 
 			final_scores[i] += *x;
 		}
@@ -520,31 +596,21 @@ impl SushiGoGame
 			}
 		}
 
-		if PRINT_DATA
-		{
-			self.print_final_scores();
-		}
+		debug!("Final scores: {:?}", final_scores);
 
-		if winners.len() > 0
+		if !winners.is_empty()
 		{
-			// Pick a random winner amongst the players with the highest score.
-			let mut rng = rand::thread_rng();
-			winners.shuffle(&mut rng);
+			// Pick a random winner amongst the players with the highest score. Seeded games always pick the same one.
+			match self.seed
+			{
+				Some(seed) => winners.shuffle(&mut StdRng::seed_from_u64(seed)),
+				None => winners.shuffle(&mut rand::thread_rng()),
+			}
 			return winners[0];
 		}
 
-		println!("No winner!");
-		return 0;
-	}
-
-	pub fn set_print_nn_weights(&mut self, print_nn_weights: bool)
-	{
-		self.print_nn_weights = print_nn_weights;
-	}
-
-	pub fn should_print_nn_weights(&self) -> bool
-	{
-		self.print_nn_weights
+		warn!("No winner!");
+		0
 	}
 }
 
@@ -576,7 +642,7 @@ fn setup_deck() -> Vec<Card>
 	deck.append(&mut squid_nigri_vec);	
 	deck.append(&mut wasabi_vec);
 
-	return deck;
+	deck
 }
 
 fn deal_cards<'a>(players: &'a mut Vec<Player>, deck: &'a mut Vec<Card>)
@@ -590,7 +656,7 @@ fn deal_cards<'a>(players: &'a mut Vec<Player>, deck: &'a mut Vec<Card>)
 			match next_card_option
 			{
 				Some(y) => x.add_card_to_hand(y),
-				None => assert!(false, "There was no cards left in the deck, this should be impossible!")
+				None => panic!("There was no cards left in the deck, this should be impossible!")
 			}
 		}
 	}
@@ -603,26 +669,5 @@ fn setup_players(num_players: u8) -> Vec<Player>
 	{
 		players.push(new_player(i as usize));
 	}
-	return players;
-}
-
-fn return_chopsticks(player: &mut Player)
-{
-	player.add_card_to_hand(Card::Chopsticks);
-	if player.has_chosen_card(Card::Chopsticks)
-	{
-		player.remove_chosen_card(Card::Chopsticks);
-		return;
-	}
-	assert!(false, "We assumed we had chopsticks cause we played two cards, but we don't have any chopsticks!");
+	players
 }
-
-fn debug_print(message: &str)
-{
-	if !PRINT_DATA
-	{
-		return;
-	}
-
-	print!("{}", message);
-}
\ No newline at end of file
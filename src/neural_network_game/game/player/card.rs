@@ -1,6 +1,9 @@
 use std::fmt;
 
+// CardMax counts the types of card rather than being one.
 #[derive(Copy,Clone,Debug,Enum)]
+#[derive(Serialize, Deserialize)]
+#[allow(clippy::enum_variant_names)]
 pub enum Card 
 {
     Pudding,
@@ -87,6 +90,29 @@ pub fn print_cards(cards: &[Card])
 	}
 }
 
+// Returns the cards as a comma separated list.
+pub fn format_cards(cards: &[Card]) -> String
+{
+	if cards.is_empty()
+	{
+		return "nothing".to_string();
+	}
+
+	cards.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+// How many maki rolls a card is worth.
+pub fn get_maki_roll_value(card: Card) -> usize
+{
+	match card
+	{
+		Card::MakiRoll1 => 1,
+		Card::MakiRoll2 => 2,
+		Card::MakiRoll3 => 3,
+		_ => 0,
+	}
+}
+
 pub fn get_sprite_filename(card: &Card) -> &str
 {
 	match card
@@ -2,9 +2,8 @@ pub mod card;
 
 use card::*;
 
-use crate::neural_network_game::game::PRINT_DATA;
-
 // This struct is used to track results at the end of the round i.e. by tracking the total number of maki rolls, as opposed to the number of each maki roll card (1,2, and 3).
+#[derive(Clone)]
 pub struct SushiResult
 {
 	pub pudding_count: i16,
@@ -56,15 +55,13 @@ impl SushiResult
 }
 
 // This is the internal representation of the player which is tracked by the game. The state of this player is one part of the input fed into the neural network.
+#[derive(Clone)]
 pub struct Player
 {
 	// A unique ID for this player (in regards to the game)
 	id: usize,
     hand: Vec<Card>,
 	chosen_cards: Vec<Card>,
-	weights: Vec<f64>,
-	sushi_go: bool,
-	prev_chosen_cards: Vec<Card>,
 
 	// This vector holds the number of cards a player has at the end of a given round, it is used to calculate the score
 	round_results: Vec<SushiResult>,
@@ -84,13 +81,12 @@ impl Player
 		{
 			if x == &card_to_remove
 			{
-				self.hand.remove(i as usize);
+				self.hand.remove(i);
 				return true;
 			}
 		}
 
-		assert!(false, "Trying to remove a card we don't have! {0}", card_to_remove);
-		return false;
+		panic!("Trying to remove a card we don't have! {0}", card_to_remove);
 	}
 
     pub fn has_card_in_hand(&self, card: Card) -> bool
@@ -103,7 +99,7 @@ impl Player
                 }
             }
 
-        return false;
+        false
     }
 
 	pub fn has_chosen_card(&self, card: Card) -> bool
@@ -116,15 +112,12 @@ impl Player
 			}
 		}
 
-		return false;
+		false
 	}
 
 	pub fn choose_card(&mut self, card: Card) -> bool
 	{
-        if PRINT_DATA
-        {
-            print!("The card chosen was {0}.\n", card);
-        }
+        trace!("The card chosen was {0}", card);
 
 		// Find the card we played and remove it from the hand.
 		if !self.remove_card_from_hand(card)
@@ -134,17 +127,17 @@ impl Player
 		
 		self.chosen_cards.push(card);
 
-		return true;
+		true
 	}
 
 	pub fn get_hand(&self) -> Vec<Card>
 	{
-		return self.hand.clone();
+		self.hand.clone()
 	}
 
 	pub fn get_hand_size(&self) -> usize
 	{
-		return self.hand.len();
+		self.hand.len()
 	}
 
     // This replaces the players hand and refreshes any chopsticks.
@@ -182,10 +175,7 @@ impl Player
 	{
 		if self.remove_chosen_card(Card::Chopsticks)
 		{
-			if PRINT_DATA
-			{
-				print!("Removing chopsticks from our chosen cards and adding them back into our hand.\n");
-			}
+			trace!("Removing chopsticks from our chosen cards and adding them back into our hand");
 
 			self.add_card_to_hand(Card::Chopsticks);
 		}
@@ -197,7 +187,7 @@ impl Player
 		{
 			return self.hand[position] as usize == *in_card;
 		}
-		return false; // Card cannot be in our hand since we don't have any cards above this point.
+		false // Card cannot be in our hand since we don't have any cards above this point.
 	}
 
 	pub fn card_is_at_position_chosen_cards(&self, position: usize, in_card: &usize) -> bool
@@ -206,17 +196,22 @@ impl Player
 		{
 			return self.chosen_cards[position] as usize == *in_card;
 		}
-		return false; // Card cannot be in our hand since we don't have any cards above this point.
+		false // Card cannot be in our hand since we don't have any cards above this point.
 	}
 
 	pub fn get_chosen_cards(&self) -> Vec<Card>
 	{
-		return self.chosen_cards.clone();
+		self.chosen_cards.clone()
+	}
+
+	pub fn replace_chosen_cards(&mut self, replacement: Vec<Card>)
+	{
+		self.chosen_cards = replacement;
 	}
 
 	pub fn get_chosen_cards_size(&self) -> usize
 	{
-		return self.chosen_cards.len();
+		self.chosen_cards.len()
 	}
 
 	pub fn clear_chosen_cards(&mut self)
@@ -230,17 +225,17 @@ impl Player
 		{
 			if x == &card_to_remove
 			{
-				self.chosen_cards.remove(i as usize);
+				self.chosen_cards.remove(i);
 				return true;
 			}
 		}
 
-		return false;
+		false
 	}
 
 	pub fn get_id(&self) -> usize
 	{
-		return self.id;
+		self.id
 	}
 
     // Adds a struct with our current card count to the vector of results. We should end up with 3
@@ -279,12 +274,12 @@ impl Player
 			}
 		}
 
-		return round_results;
+		round_results
 	}
 
 	pub fn get_round_result(&self, index: usize) -> &SushiResult
 	{
-		return &self.round_results[index as usize];
+		&self.round_results[index]
 	}
 
 	pub fn set_final_score(&mut self, new_final_score: i32)
@@ -294,11 +289,11 @@ impl Player
 
 	pub fn get_final_score(&self) -> i32
 	{
-		return self.final_score;
+		self.final_score
 	}
 }
 
 pub fn new_player(id: usize) -> Player
 {
-	return Player{id: id as usize, hand: Vec::new(), chosen_cards: Vec::new(), sushi_go: false, weights: vec![1.0; Card::CardMax as usize], round_results: Vec::new(), prev_chosen_cards: Vec::new(), final_score: 0}
+	Player{id, hand: Vec::new(), chosen_cards: Vec::new(), round_results: Vec::new(), final_score: 0}
 }
\ No newline at end of file
@@ -9,9 +9,6 @@ const DUMPLING_POINTS: [i16; 5] =		[1, 3, 6, 10, 15];
 const SALMON_NIGRI_POINTS: i16 =		2;
 const EGG_NIGRI_POINTS: i16 =			1;
 const SQUID_NIGRI_POINTS: i16 =			3;
-const WASABI_SALMON_NIGRI_POINTS: i16 =	9;
-const WASABI_EGG_NIGRI_POINTS: i16 =	3;
-const WASABI_SQUID_NIGRI_POINTS: i16 =	6;
 
 const MOST_PUDDING_SCORE: i16 = 		6;
 const LEAST_PUDDING_SCORE: i16 = 		-6;
@@ -32,7 +29,7 @@ fn add_score(id: u8, amount: i16, scores: &mut [i32])
 	scores[id as usize] += amount as i32;
 }
 
-pub fn calc_scores_for_round(players: &Vec<Player>, round: u8) -> Vec<i32>
+pub fn calc_scores_for_round(players: &[Player], round: u8) -> Vec<i32>
 {
 	// Gather the results for each player.
 	let mut round_results: Vec<&SushiResult> = Vec::new();
@@ -77,16 +74,12 @@ pub fn calc_scores_for_round(players: &Vec<Player>, round: u8) -> Vec<i32>
         add_score(i as u8, x.salmon_nigri_count * SALMON_NIGRI_POINTS, &mut scores);
         add_score(i as u8, x.egg_nigri_count * EGG_NIGRI_POINTS, &mut scores);
         add_score(i as u8, x.squid_nigri_count * SQUID_NIGRI_POINTS, &mut scores);
-//        add_score(i as u8, x.wasabi_salmon_nigri_count * WASABI_SALMON_NIGRI_POINTS, &mut scores);
-//        add_score(i as u8, x.wasabi_egg_nigri_count * WASABI_EGG_NIGRI_POINTS, &mut scores);
-//        add_score(i as u8, x.wasabi_squid_nigri_count * WASABI_SQUID_NIGRI_POINTS, &mut scores);
     }
 
-
-	return scores;
+	scores
 }
 
-pub fn calc_pudding_counts_for_game(players: &Vec<Player>, total_rounds: u8) -> Vec<(usize, i16)>
+pub fn calc_pudding_counts_for_game(players: &[Player], total_rounds: u8) -> Vec<(usize, i16)>
 {
 	let mut pudding_counts: Vec<(usize, i16)> = Vec::new();
 
@@ -103,10 +96,10 @@ pub fn calc_pudding_counts_for_game(players: &Vec<Player>, total_rounds: u8) ->
 		pudding_counts.push(pair);
 	}
 
-	pudding_counts.sort_by(|a,b| a.1.cmp(&b.1));
-	return pudding_counts;
+	pudding_counts.sort_by_key(|a| a.1);
+	pudding_counts
 }
-pub fn calc_pudding_scores_for_game(players: &Vec<Player>, total_rounds: u8) -> Vec<i32>
+pub fn calc_pudding_scores_for_game(players: &[Player], total_rounds: u8) -> Vec<i32>
 {
 	let mut scores = vec![0; players.len()];
 
@@ -151,7 +144,7 @@ pub fn calc_pudding_scores_for_game(players: &Vec<Player>, total_rounds: u8) ->
 		}
 	}
 
-	return scores;
+	scores
 }
 
 fn add_maki_roll_score(scores: &mut [i32], results: &[&SushiResult])
@@ -169,7 +162,7 @@ fn add_maki_roll_score(scores: &mut [i32], results: &[&SushiResult])
 
 		for x in most_maki_rolls
 		{
-			add_score(x as u8, maki_roll_points_each, scores);
+			add_score(x, maki_roll_points_each, scores);
 		}
 
 		// If there was no tie for first, give some points for second place.
@@ -177,13 +170,13 @@ fn add_maki_roll_score(scores: &mut [i32], results: &[&SushiResult])
 		{
 			let num_second_most_maki_players = second_most_maki_rolls.len() as i8;
 
-			if second_most_maki_rolls.len() > 0
+			if !second_most_maki_rolls.is_empty()
 			{
 				let second_maki_roll_points_each = (SECOND_MOST_MAKI_POINTS / num_second_most_maki_players) as i16;
 
 				for x in second_most_maki_rolls
 				{
-					add_score(x as u8, second_maki_roll_points_each, scores);
+					add_score(x, second_maki_roll_points_each, scores);
 				}
 			}
 		}
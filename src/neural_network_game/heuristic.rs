@@ -0,0 +1,83 @@
+use super::game::*;
+use super::game::player::card::*;
+
+// A simple rule based player, used as a benchmark that is stronger than picking at random. It takes whichever card
+// in its hand adds the most to the cards it has already chosen this round.
+pub fn do_heuristic_turn(game: &SushiGoGame) -> Card
+{
+	let player = game.get_current_player();
+
+	let hand = player.get_hand();
+	let chosen = player.get_chosen_cards();
+
+	let mut best_card = hand[0];
+	let mut best_value = f32::NEG_INFINITY;
+
+	for x in hand.iter()
+	{
+		let value = get_card_value(*x, &chosen, hand.len());
+
+		if value > best_value
+		{
+			best_card = *x;
+			best_value = value;
+		}
+	}
+
+	best_card
+}
+
+// Roughly how many points a card is worth given the cards already chosen and how many cards are left in the hand.
+pub fn get_card_value(card: Card, chosen: &[Card], hand_size: usize) -> f32
+{
+	let count = |card: Card| chosen.iter().filter(|x| **x == card).count();
+
+	// The next nigiri goes on the wasabi if one has been played since the last nigiri.
+	let mut has_wasabi = false;
+
+	for x in chosen
+	{
+		match x
+		{
+			Card::Wasabi => has_wasabi = true,
+			Card::SalmonNigiri | Card::EggNigiri | Card::SquidNigiri => has_wasabi = false,
+			_ => {},
+		}
+	}
+
+	let wasabi_multiplier = if has_wasabi { 3.0 } else { 1.0 };
+
+	match card
+	{
+		Card::SquidNigiri => 3.0 * wasabi_multiplier,
+		Card::SalmonNigiri => 2.0 * wasabi_multiplier,
+		Card::EggNigiri => 1.0 * wasabi_multiplier,
+
+		// Only worth it if there is time to finish the set.
+		Card::Sashimi =>
+		{
+			let needed = 3 - count(Card::Sashimi) % 3;
+			if needed <= hand_size { 10.0 / needed as f32 } else { 0.0 }
+		}
+		Card::Tempura =>
+		{
+			let needed = 2 - count(Card::Tempura) % 2;
+			if needed <= hand_size { 5.0 / needed as f32 } else { 0.0 }
+		}
+
+		// Each dumpling is worth one more than the last, up to five.
+		Card::Dumpling => if count(Card::Dumpling) < 5 { (count(Card::Dumpling) + 1) as f32 } else { 0.0 },
+
+		Card::MakiRoll1 => 1.0,
+		Card::MakiRoll2 => 2.0,
+		Card::MakiRoll3 => 3.0,
+
+		Card::Pudding => 1.5,
+
+		// Only useful if there is still time to put a nigiri on it.
+		Card::Wasabi => if !has_wasabi && hand_size > 2 { 3.0 } else { 0.5 },
+		Card::Chopsticks => if hand_size > 3 { 1.0 } else { 0.0 },
+
+		Card::None | Card::CardMax => f32::NEG_INFINITY,
+	}
+}
@@ -10,173 +10,365 @@ use rayon::prelude::*;
 
 use std::time::Instant;
 
-use radiant_rs::{Display, Renderer, Layer, Sprite, Color};
-
-use enum_map::EnumMap;
-
 use std::io;
+use std::fmt;
 
 //use std::{thread, time};
 
+mod model_a;
+mod model_b;
 mod model_c;
-use model_c::*;
+pub mod model;
+use model::*;
+pub mod explanation;
+pub mod policy;
+use policy::*;
+pub mod heuristic;
+use heuristic::*;
+pub mod replay;
+use replay::*;
+pub mod advisor;
+use advisor::*;
+pub mod spectator;
+pub mod observation;
+use spectator::*;
+pub mod external_bot;
+use external_bot::*;
+pub mod terminal_ui;
+#[cfg(feature = "gui")]
+pub mod window_ui;
 use rand::Rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::rating::*;
 
 //---- Constants ----
 pub const NUMBER_OF_PLAYERS: usize = 4;
 
+// Mixed into the seed of a game to seed the bots' random choices, so they don't follow the shuffle of the deck.
+const BOT_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
 #[derive(Copy,Clone)]
 pub enum NeuralNetworkGamePlayerType
 {
 	NeuralNetwork,
 	Human,
-	Random
+	Random,
+	Heuristic,
+
+	// A bot running as another program.
+	External,
+}
+
+impl fmt::Display for NeuralNetworkGamePlayerType
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let printable = match *self
+		{
+			NeuralNetworkGamePlayerType::NeuralNetwork => "network",
+			NeuralNetworkGamePlayerType::Human => "human",
+			NeuralNetworkGamePlayerType::Random => "random",
+			NeuralNetworkGamePlayerType::Heuristic => "heuristic",
+			NeuralNetworkGamePlayerType::External => "external",
+		};
+		write!(f, "{}", printable)
+	}
 }
 
 impl NeuralNetworkGamePlayerType {
 	pub fn is_random(&self) -> bool {
-		match *self {
-			NeuralNetworkGamePlayerType::Random => true,
-			_ => false,
-		}
+		matches!(*self, NeuralNetworkGamePlayerType::Random)
+	}
+
+	pub fn is_neural_network(&self) -> bool {
+		matches!(*self, NeuralNetworkGamePlayerType::NeuralNetwork)
 	}
 
 	pub fn is_human(&self) -> bool {
-		match *self {
-			NeuralNetworkGamePlayerType::Human => true,
-			_ => false,
+		matches!(*self, NeuralNetworkGamePlayerType::Human)
+	}
+}
+
+// How games with human players are shown.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum DisplayMode
+{
+	// An OpenGL window showing the cards, with choices typed in the terminal.
+	Window,
+
+	// Everything drawn in the terminal with the cards picked using the keyboard, which works without a display.
+	Terminal,
+}
+
+impl fmt::Display for DisplayMode
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match *self
+		{
+			DisplayMode::Window => write!(f, "window"),
+			DisplayMode::Terminal => write!(f, "terminal"),
+		}
+	}
+}
+
+impl Default for DisplayMode
+{
+	// The window is only there if the gui feature is.
+	fn default() -> DisplayMode
+	{
+		if cfg!(feature = "gui") { DisplayMode::Window } else { DisplayMode::Terminal }
+	}
+}
+
+impl DisplayMode
+{
+	pub fn from_name(name: &str) -> Option<DisplayMode>
+	{
+		match name.trim()
+		{
+			"window" => Some(DisplayMode::Window),
+			"terminal" => Some(DisplayMode::Terminal),
+			_ => None,
 		}
 	}
+
+	pub fn is_terminal(&self) -> bool
+	{
+		*self == DisplayMode::Terminal
+	}
 }
 
+#[derive(Clone)]
 pub struct NeuralNetworkGamePlayer
 {
 	pub player_type: NeuralNetworkGamePlayerType,
-	pub nn: NeuralNetwork
+	pub nn: NeuralNetwork,
+
+	// How the outputs of the network are turned into a card choice.
+	pub policy: PolicyMode,
+
+	// The name this player is known by in results and ratings.
+	pub name: String,
+
+	// The program which picks the cards for external players.
+	pub bot: Option<ExternalBot>
 }
 
 impl NeuralNetworkGamePlayer
 {
 	pub fn new(player_type: NeuralNetworkGamePlayerType, nn: NeuralNetwork) -> NeuralNetworkGamePlayer
 	{
-		NeuralNetworkGamePlayer{player_type, nn}
+		NeuralNetworkGamePlayer::with_policy(player_type, nn, PolicyMode::default())
+	}
+
+	pub fn with_policy(player_type: NeuralNetworkGamePlayerType, nn: NeuralNetwork, policy: PolicyMode) -> NeuralNetworkGamePlayer
+	{
+		let name = match player_type
+		{
+			NeuralNetworkGamePlayerType::NeuralNetwork => format!("network {}", nn.get_id()),
+			_ => player_type.to_string(),
+		};
+
+		NeuralNetworkGamePlayer{player_type, nn, policy, name, bot: None}
+	}
+
+	// A player whose cards are picked by a bot running as another program.
+	pub fn external(bot: ExternalBot) -> NeuralNetworkGamePlayer
+	{
+		let mut player = NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::External, empty_neural_network());
+		player.name = bot.get_name().to_string();
+		player.bot = Some(bot);
+
+		player
 	}
+}
 
-	pub fn clone(&self) -> NeuralNetworkGamePlayer
+// Built without the gui feature there's no window, so games are shown in the terminal instead.
+#[cfg(not(feature = "gui"))]
+impl NeuralNetworkGame
+{
+	pub fn play_with_display(&mut self) -> io::Result<GameOutcome>
 	{
-		return NeuralNetworkGamePlayer{player_type: self.player_type, nn: self.nn.clone()};
+		warn!("This was built without the gui feature, so the game is shown in the terminal");
+		self.play_in_terminal()
+	}
+
+	pub fn spectate_in_window(&mut self, settings: SpectatorSettings)
+	{
+		warn!("This was built without the gui feature, so the game is shown in the terminal");
+		self.spectate_in_terminal(settings);
 	}
 }
 
+// How a game with human players ended.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum GameOutcome
+{
+	Finished,
+
+	// A human quit or closed the window before the game was over.
+	Abandoned,
+}
+
 // This is a struct which links a game to a set of neural network players.
 pub struct NeuralNetworkGame
 {
 	game: SushiGoGame,
-	players: Vec<NeuralNetworkGamePlayer>
+	players: Vec<NeuralNetworkGamePlayer>,
+
+	// Filled in as the game is played if recording has been turned on.
+	replay: Option<GameReplay>,
+
+	// How the game is shown if any of the players are human, or if it is being watched.
+	display_mode: DisplayMode,
+
+	// Set if a game between bots should be shown turn by turn rather than played at full speed.
+	spectator: Option<SpectatorSettings>,
+
+	// Gives hints to human players who ask for them.
+	advisor: Option<Advisor>,
+
+	// Draws the random choices of the bots in seeded games, so they play out the same every time.
+	rng: Option<StdRng>,
 }
 
 impl NeuralNetworkGame
 {
-	pub fn play(&mut self)
+	pub fn new(game: SushiGoGame, players: Vec<NeuralNetworkGamePlayer>) -> NeuralNetworkGame
 	{
-		if self.has_human()
-		{
-			self.play_with_display();
-		}
-		else
-		{
-			let mut result = StepResult::NoResult;
+		assert!(game.get_num_players() == players.len(), "The number of players does not match the game!");
 
-			// Keep stepping through the game until we have a winner.
-			while !result.is_game_over()
-			{
-				result = self.step_game(result);
-			}
+		let rng = game.get_seed().map(|x| StdRng::seed_from_u64(x ^ BOT_SEED_SALT));
 
-			//---- Game Finished
-		}
+		NeuralNetworkGame{game, players, replay: None, display_mode: DisplayMode::default(), spectator: None, advisor: None, rng}
 	}
 
-	pub fn play_with_display(&mut self)
+	// Records everything that happens in the game from now on so it can be saved as a replay. Should be called before
+	// the game is played.
+	pub fn enable_replay(&mut self)
 	{
-		let display = Display::builder().dimensions((1280, 720)).vsync().title("Window!").build().unwrap();
+		self.replay = Some(GameReplay::new(&self.game, self.get_player_names()));
+	}
 
-		let renderer = Renderer::new(&display).unwrap();
+	pub fn get_replay(&self) -> Option<&GameReplay>
+	{
+		self.replay.as_ref()
+	}
 
-		let sprite_map = load_card_sprites(&renderer);
+	pub fn set_display_mode(&mut self, display_mode: DisplayMode)
+	{
+		self.display_mode = display_mode;
+	}
 
-		let layer = Layer::new((1920.0, 1080.0));
+	// Lets human players ask the advisor which card to pick.
+	pub fn set_advisor(&mut self, advisor: Advisor)
+	{
+		self.advisor = Some(advisor);
+	}
 
-		let mut result = StepResult::NoResult;
+	// Asks the advisor about the current player's hand. Returns None if there isn't an advisor.
+	pub fn get_advice(&self) -> Option<Vec<CardAdvice>>
+	{
+		self.advisor.as_ref().map(|x| x.advise(&self.game))
+	}
 
-		let start_draw_y = 200.0;
-		let start_draw_x = 160.0;
-		let draw_card_size = 64.0;
-		let draw_player_gap = 200.0;
+	// Shows the game with the display mode as it is played, if none of the players are human.
+	pub fn enable_spectating(&mut self, settings: SpectatorSettings)
+	{
+		self.spectator = Some(settings);
+	}
 
-		// Keep stepping through the game until we have a winner.
-		while !result.is_game_over() && !display.poll_events().was_closed()
+	// Plays the game, showing it if any of the players are human or it is being watched. Fails if the game couldn't be
+	// shown to the humans playing it.
+	pub fn play(&mut self) -> io::Result<GameOutcome>
+	{
+		if self.has_human()
 		{
-			// Print previous round information.
-			if result.is_round_over()
+			return match self.display_mode
 			{
-				self.game.print_prev_round_results();
-			}
-
-			result = self.step_game(result);
-
-			// Clear the layer (layers could also be drawn multiple times, e.g. a static UI might not need to be updated each frame)
-			layer.clear();
+				DisplayMode::Window => self.play_with_display(),
+				DisplayMode::Terminal => self.play_in_terminal(),
+			};
+		}
 
-			for i in 0..self.game.get_num_players()
+		if let Some(settings) = self.spectator
+		{
+			match self.display_mode
 			{
-				for (j, card) in self.game.get_player_hand(i).iter().enumerate()
-				{
-					sprite_map[*card].draw(&layer, 0, (start_draw_x + (draw_card_size * j as f32), start_draw_y + (draw_player_gap * i as f32)), Color::WHITE);
-				}
-
-				for (j, card) in self.game.get_player_chosen(i).iter().enumerate()
-				{
-					sprite_map[*card].draw(&layer, 0, (start_draw_x + (draw_card_size * j as f32), start_draw_y + draw_card_size + (draw_player_gap * i as f32)), Color::WHITE);
-				}
+				DisplayMode::Window => self.spectate_in_window(settings),
+				DisplayMode::Terminal => self.spectate_in_terminal(settings),
 			}
+		}
+		else
+		{
+			self.finish_game();
+		}
 
+		Ok(GameOutcome::Finished)
+	}
 
-			// draw the layer to the frame after clearing it with solid black.
-			display.clear_frame(Color::BLACK);
-			renderer.draw_layer(&layer, 0);
-
-			display.swap_frame();
+	// Plays the rest of the game at full speed without showing it.
+	pub fn finish_game(&mut self)
+	{
+		let mut result = StepResult::NoResult;
 
-			//let ten_millis = time::Duration::from_millis(10);
-			//let now = time::Instant::now();
-			//thread::sleep(ten_millis);
+		// Keep stepping through the game until we have a winner.
+		while !result.is_game_over()
+		{
+			result = self.step_game();
 		}
-		
+
 		//---- Game Finished
-		// Game is finished, print some final info.
-		self.get_game().print_prev_round_results();
-		self.get_game().print_pudding_scores();
-		self.get_game().print_final_scores();
 	}
 
-	pub fn step_game(&mut self, prev_result: StepResult) -> StepResult
+	pub fn step_game(&mut self) -> StepResult
 	{
-		self.game.step(self.take_turn(prev_result))
+		let card = self.take_turn();
+
+		self.play_card(card)
 	}
 
-	fn take_turn(&self, prev_result: StepResult) -> Card
+	// Plays a card for the current player, recording it if the game is being recorded.
+	pub fn play_card(&mut self, card: Card) -> StepResult
+	{
+		if self.replay.is_none()
+		{
+			return self.game.step(card);
+		}
+
+		let mut snapshot = StepSnapshot::new(&self.game);
+		let player = &self.players[snapshot.player];
+
+		if player.player_type.is_neural_network()
+		{
+			snapshot.weights = Some(get_card_weights(&self.game, &player.nn));
+		}
+
+		let result = self.game.step(card);
+
+		if let Some(replay) = self.replay.as_mut()
+		{
+			replay.record_step(&snapshot, card, result, &self.game);
+		}
+
+		result
+	}
+
+	fn take_turn(&mut self) -> Card
 	{
 		// Get the player who should be taking their turn.
 		let current_player = &self.players[self.game.get_current_player_id()];
 
 		// Switch behaviour based on if they are human etc.
-		match current_player.player_type
+		match (current_player.player_type, self.rng.as_mut())
 		{
-			NeuralNetworkGamePlayerType::NeuralNetwork => return pick_cards(&self.game, &current_player.nn),
-			NeuralNetworkGamePlayerType::Human => return do_player_turn(&self.game, prev_result),
-			_ => return do_random_turn(&self.game),
-		};
+			(NeuralNetworkGamePlayerType::Human, _) => panic!("Human players pick their cards on the display!"),
+			(_, Some(rng)) => pick_bot_card_with_rng(&self.game, current_player, rng),
+			(_, None) => pick_bot_card(&self.game, current_player),
+		}
 	}
 
 	pub fn get_winning_nn(&self) -> NeuralNetworkGamePlayer
@@ -194,6 +386,12 @@ impl NeuralNetworkGame
 		&self.game
 	}
 
+	// Returns the name of the player in each seat.
+	pub fn get_player_names(&self) -> Vec<String>
+	{
+		self.players.iter().map(|x| x.name.clone()).collect()
+	}
+
 	pub fn has_human(&self) -> bool
 	{
 		for x in &self.players
@@ -208,121 +406,52 @@ impl NeuralNetworkGame
 	}
 }
 
-fn load_card_sprites(renderer: &Renderer) -> EnumMap<Card, Sprite>
-{
-	enum_map!
-	{
-			Card::Pudding => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Pudding)).unwrap(),
-			Card::Dumpling => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Dumpling)).unwrap(),
-			Card::Sashimi => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Sashimi)).unwrap(),
-			Card::Tempura => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Tempura)).unwrap(),
-			Card::MakiRoll1 => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::MakiRoll1)).unwrap(),
-			Card::MakiRoll2 => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::MakiRoll2)).unwrap(),
-			Card::MakiRoll3 => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::MakiRoll3)).unwrap(),
-			Card::Chopsticks => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Chopsticks)).unwrap(),
-			Card::SalmonNigiri => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::SalmonNigiri)).unwrap(),
-			Card::EggNigiri => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::EggNigiri)).unwrap(),
-			Card::SquidNigiri => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::SquidNigiri)).unwrap(),
-			Card::Wasabi => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Wasabi)).unwrap(),
-			_ => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Chopsticks)).unwrap(),
-    	}
-}
-
-pub fn get_usize_from_player_input(message: &str) -> usize
+// What was seen over the games played by play_batches.
+pub struct BatchResults
 {
-	println!("{}", message);
+	// The name of the player in each seat.
+	pub player_names: Vec<String>,
 
-	let mut line_in = String::new();
+	// How many games each seat won.
+	pub wins: [usize; NUMBER_OF_PLAYERS],
 
-	io::stdin().read_line(&mut line_in)
-		.expect("Failed to read line");
+	// The final score of every game, by seat.
+	pub seat_scores: Vec<Vec<f64>>,
 
-	let mut usize_out = 0;
-
-	match line_in.trim().parse::<usize>()
-	{
-		Ok(n) => usize_out = n,
-		Err(_e) => println!("That was an invalid choice. Dealing with user error is not currently implemented."),
-	}
-
-	usize_out
+	// Set if a human quit part way through. The games of the batch they quit in aren't counted and no more are played.
+	pub abandoned: bool,
 }
 
-pub fn start_game_setup()
+impl BatchResults
 {
-	let mut human_players = get_usize_from_player_input("Enter how many players will be human (0 - 4):");
-
-	if human_players > NUMBER_OF_PLAYERS
-	{
-		human_players = NUMBER_OF_PLAYERS;
-	}
-
-	// Filename of the neural network to load.
-	let mut in_file_name = String::new();
-
-	let mut random_players = 0;
-
-	if human_players < NUMBER_OF_PLAYERS
-	{
-		random_players = get_usize_from_player_input("Enter how many players will be random (0 - 4):");
-
-		if random_players > NUMBER_OF_PLAYERS
-		{
-			random_players = NUMBER_OF_PLAYERS;
-		}
-
-		if random_players + human_players < NUMBER_OF_PLAYERS
-		{
-			println!("Enter name of neural network to load:");
-
-			io::stdin().read_line(&mut in_file_name)
-				.expect("Failed to read line");
-		}
-	}
-
-	let mut number_of_games = 1;
-	let mut batch_count = 1;
-
-	if human_players <= 0
+	pub fn get_games(&self) -> usize
 	{
-		number_of_games = get_usize_from_player_input("Enter number of games to play:");
-		batch_count = get_usize_from_player_input("Enter number of batches to do, this will reduce memory overhead:");
+		self.seat_scores[0].len()
 	}
-
-	let mut print_nn_weights = false;
-
-	if human_players + random_players < NUMBER_OF_PLAYERS
-	{
-		print_nn_weights = get_usize_from_player_input("Print neural network weights?") > 0;
-	}
-
-	start_game(batch_count, number_of_games, in_file_name, random_players, human_players, print_nn_weights);
 }
 
-// Plays three neural networks against an AI picking random choices.
+// Plays games between the humans, random players, bots and copies of the network, seated in that order.
 // Plays number_of_games games per batch. Batches is used to reduce memory usage.
-pub fn start_game(batches: usize, number_of_games: usize, in_file_name: String, number_of_random_players: usize, number_of_human_players: usize, print_nn_weights: bool)
+// If replay_writer is given every game is recorded and written to it.
+// If ratings is given they are updated from every game.
+// If spectator is given games between bots are shown as they are played.
+// If advisor is given humans can ask it for hints.
+// Fails if a game couldn't be shown to the humans playing it.
+#[allow(clippy::too_many_arguments)]
+pub fn play_batches(batches: usize, number_of_games: usize, network: Option<&NeuralNetworkGamePlayer>, number_of_random_players: usize, number_of_human_players: usize, external_bots: &[ExternalBot], display_mode: DisplayMode, spectator: Option<SpectatorSettings>, advisor: Option<Advisor>, mut replay_writer: Option<&mut ReplayWriter>, mut ratings: Option<&mut RatingsTable>) -> io::Result<BatchResults>
 {
-	let random_started = Instant::now();
+	// The seats which aren't human, random or bots are filled by the network.
+	let number_of_neural_networks = NUMBER_OF_PLAYERS - number_of_random_players - number_of_human_players - external_bots.len();
+	assert!(number_of_neural_networks == 0 || network.is_some(), "There's no network to fill the rest of the seats!");
 
-	let mut win_counter: [usize; NUMBER_OF_PLAYERS] = [0; NUMBER_OF_PLAYERS];
+	let mut results = BatchResults{player_names: Vec::new(), wins: [0; NUMBER_OF_PLAYERS], seat_scores: vec![Vec::new(); NUMBER_OF_PLAYERS], abandoned: false};
 
 	for j in 0..batches
 	{
-		print!("Starting batch: {}\n", j);
+		info!("Starting batch: {}", j);
 		let batch_started = Instant::now();
 		let mut new_game_nn = Vec::new();
-
-		// Work out how many neural networks we need, and create an empty one for if we
-		let number_of_neural_networks = NUMBER_OF_PLAYERS - number_of_random_players - number_of_human_players;
-		let empty_nn = new_neural_network(0,0,0,0);
-
-		// Load in the neural network if we have any neural network players.
-		let mut loaded_nn = empty_nn.clone();
-		if number_of_neural_networks > 0
-		{
-			loaded_nn = load_nn_from_file(in_file_name.trim());
-		}
+		let empty_nn = empty_neural_network();
 
 		// Pull the players from the pool of neural networks.
 		for _j in 0..number_of_games
@@ -337,55 +466,125 @@ pub fn start_game(batches: usize, number_of_games: usize, in_file_name: String,
 				new_game_nn.push(NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_nn.clone()))
 			}
 
-			for _k in 0..number_of_neural_networks
+			for x in external_bots
 			{
-				new_game_nn.push(NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::NeuralNetwork, loaded_nn.clone()));
+				new_game_nn.push(NeuralNetworkGamePlayer::external(x.clone()))
+			}
+
+			if let Some(network) = network
+			{
+				for _k in 0..number_of_neural_networks
+				{
+					new_game_nn.push(network.clone());
+				}
 			}
 		}
 
-		print!("Created {} for batch in {}\n", new_game_nn.len(), sec_from_time(batch_started));
+		info!("Created {} for batch in {}", new_game_nn.len(), sec_from_time(batch_started));
 
 		let mut games: Vec<NeuralNetworkGame> = Vec::new();
 
-		create_and_play_games_parallel(&mut games, number_of_games, &mut new_game_nn, print_nn_weights);
+		create_games(&mut games, number_of_games, &mut new_game_nn, None);
 
-		for x in &games
+		for x in games.iter_mut()
 		{
-			win_counter[x.get_winning_id()] += 1;
+			x.set_display_mode(display_mode);
+
+			if let Some(settings) = spectator
+			{
+				x.enable_spectating(settings);
+			}
+
+			if let Some(advisor) = &advisor
+			{
+				x.set_advisor(advisor.clone());
+			}
+
+			if replay_writer.is_some()
+			{
+				x.enable_replay();
+			}
 		}
 
-		print!("Batch finished, total time: {}\n", sec_from_time(batch_started));
-	}
+		let outcomes: Vec<io::Result<GameOutcome>> = games.par_iter_mut().map(|x| x.play()).collect();
 
-	let total_games = number_of_games * batches;
+		for x in outcomes
+		{
+			if x? == GameOutcome::Abandoned
+			{
+				results.abandoned = true;
+			}
+		}
 
-	for i in 0..NUMBER_OF_PLAYERS
-	{
-		let percentage_wins: f32 = (win_counter[i] as f32 / total_games  as f32) * 100.0;
-		print!("Player {} won {}% of games\n", i, percentage_wins);
+		if results.abandoned
+		{
+			break;
+		}
+
+		for x in &games
+		{
+			if let (Some(writer), Some(replay)) = (replay_writer.as_mut(), x.get_replay())
+			{
+				if let Err(e) = writer.write(replay)
+				{
+					warn!("Couldn't write a replay, the rest of the games won't be recorded: {}", e);
+					replay_writer = None;
+				}
+			}
+
+			results.wins[x.get_winning_id()] += 1;
+
+			for (i, score) in x.get_game().get_final_scores().iter().enumerate()
+			{
+				results.seat_scores[i].push(*score as f64);
+			}
+
+			if let Some(ratings) = ratings.as_deref_mut()
+			{
+				ratings.update_from_game(&x.get_player_names(), &x.get_game().get_final_scores());
+			}
+		}
+
+		if let Some(x) = games.first()
+		{
+			results.player_names = x.get_player_names();
+		}
+
+		info!("Batch finished, total time: {}", sec_from_time(batch_started));
 	}
 
-	println!("\nGames per second: {}\n", total_games as f64 / sec_from_time(random_started));
-	println!("Average batch time {}\n", sec_from_time(random_started) / batches as f64);
+	Ok(results)
 }
 
 // Creates the number of games required for a round and returns the id's of the winning neural networks.
+// If first_seed is given the games are dealt from consecutive seeds starting at it.
 pub fn create_and_play_games_parallel(	games: &mut Vec<NeuralNetworkGame>,
 										number_of_games: usize,
 									  	nn: &mut Vec<NeuralNetworkGamePlayer>,
-										print_nn_weights: bool)
+										first_seed: Option<u64>)
+{
+	create_games(games, number_of_games, nn, first_seed);
+
+	//---- Play the games in this round.
+	play_games_parallel(games);
+}
+
+// Creates the games without playing them, taking the players from the end of the pool.
+pub fn create_games(games: &mut Vec<NeuralNetworkGame>, number_of_games: usize, nn: &mut Vec<NeuralNetworkGamePlayer>, first_seed: Option<u64>)
 {
 	// Add the number of games we need.
-	for _j in 0..number_of_games
+	for i in 0..number_of_games
 	{
 		// Create a new game and a vector to hold the neural network players.
 		let mut new_game = SushiGoGame::new(NUMBER_OF_PLAYERS);
 
 		// Setup the game.
-		new_game.setup();
+		match first_seed
+		{
+			Some(seed) => new_game.setup_with_seed(seed.wrapping_add(i as u64)),
+			None => new_game.setup(),
+		}
 
-		// Tell the game whether or not to print nn weights.
-		new_game.set_print_nn_weights(print_nn_weights);
 
 		let mut new_game_nn = Vec::new();
 
@@ -393,122 +592,80 @@ pub fn create_and_play_games_parallel(	games: &mut Vec<NeuralNetworkGame>,
 		for _j in 0..NUMBER_OF_PLAYERS
 		{
 			// Check there are nn's in the input array.
-			if nn.len() <= 0
-			{
-				assert!(false, "Ran out of nn players!");
-			}
+			assert!(!nn.is_empty(), "Ran out of nn players!");
 
 			// Add the nn to the list of nn's to be used in this game.
 			new_game_nn.push(nn.remove(nn.len() - 1));
 		}
 
 		// Add the game to the list of games in this round.
-		games.push(NeuralNetworkGame{game: new_game, players: new_game_nn});
+		games.push(NeuralNetworkGame::new(new_game, new_game_nn));
 	}
 
 	//print!("Setup {} games. Starting parallel play. \n", number_of_games);
-
-	//---- Play the games in this round.
-	play_games_parallel(games);
 }
 
-fn play_games(games: &mut Vec<NeuralNetworkGame>)
-{
-	//---- Play the games in this round
-    games.iter_mut().for_each(|game| game.play());
-}
-
-fn play_games_parallel(games: &mut Vec<NeuralNetworkGame>)
+// Plays games between bots without showing them.
+pub fn play_games_parallel(games: &mut [NeuralNetworkGame])
 {
 	//---- Play the games in this round, but in parallel :O
-	games.par_iter_mut().for_each(|game| game.play());
+	games.par_iter_mut().for_each(|game| game.finish_game());
 }
 
 pub fn sec_from_time(time: std::time::Instant) -> f64
 {
 	let elapsed = time.elapsed();
-	return (elapsed.as_secs() as f64) + (elapsed.subsec_nanos() as f64 / 1000_000_000.0);
+	(elapsed.as_secs() as f64) + (elapsed.subsec_nanos() as f64 / 1_000_000_000.0)
 }
 
-pub fn create_neural_networks(number: usize) -> Vec<NeuralNetworkGamePlayer>
+pub fn create_neural_networks(number: usize, model: NeuralNetworkModel, policy: PolicyMode) -> Vec<NeuralNetworkGamePlayer>
 {
-	let mut nn = Vec::new();
+	create_neural_networks_with_rng(number, &model.get_node_count(), policy, &mut rand::thread_rng())
+}
 
-	let node_count = get_node_count();
+pub fn create_neural_networks_with_rng<R: Rng>(number: usize, node_count: &NeuralNetworkProperties, policy: PolicyMode, rng: &mut R) -> Vec<NeuralNetworkGamePlayer>
+{
+	let mut nn = Vec::new();
 
 	for i in 0..number	
 	{
-		nn.push(NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::NeuralNetwork, neural_network::new_neural_network(i as usize, node_count.input_node_count, node_count.hidden_node_count, node_count.output_node_count)));
+		nn.push(NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, neural_network::new_neural_network_with_rng(i, node_count, rng), policy));
 	}
 
 	nn
 }
 
-fn do_player_turn(game: &SushiGoGame, prev_result: StepResult) -> Card
+// Picks a card for the current player of the game, who is played by a bot of the given type.
+pub fn pick_bot_card(game: &SushiGoGame, player: &NeuralNetworkGamePlayer) -> Card
 {
-	print!("The result for the previous round was {}\n", prev_result);
-
-	println!("\nPrinting Current Game State:");
+	pick_bot_card_with_rng(game, player, &mut rand::thread_rng())
+}
 
-	for i in 0..game.get_num_players()
+// The same as pick_bot_card, drawing any random choices from the given generator.
+pub fn pick_bot_card_with_rng<R: Rng>(game: &SushiGoGame, player: &NeuralNetworkGamePlayer, rng: &mut R) -> Card
+{
+	match player.player_type
 	{
-		println!("\nChosen Cards for Player {0}", i);
-
-		let mut visible_cards: Vec<Card> = Vec::new();
-		for (i, x) in game.get_player_chosen(i).iter().enumerate()
+		NeuralNetworkGamePlayerType::NeuralNetwork => pick_cards_with_rng(game, &player.nn, player.policy, rng),
+		NeuralNetworkGamePlayerType::Heuristic => do_heuristic_turn(game),
+		NeuralNetworkGamePlayerType::External => match &player.bot
 		{
-			if i < game.get_player_chosen(game.get_current_player_id()).len()
-			{
-				visible_cards.push(*x);
-			}
-		}
-		print_cards(&visible_cards);
-	}
-
-	let current_hand = game.get_current_player().get_hand();
-
-	println!("\nYour chosen cards:");
-	print_cards(&game.get_current_player().get_chosen_cards());
-
-	println!("\nYour hand:");
-	print_cards(&current_hand);
-
-	if prev_result.is_chopsticks_available()
-	{
-		println!("You have the option to pick another card to replace your chopsticks:\n");
-	}
-
-	println!("Enter the card you want to pick:");
-
-	let mut chosen_card_slot = String::new();
-
-	io::stdin().read_line(&mut chosen_card_slot)
-		.expect("Failed to read line");
-
-	let mut chosen_slot = 0;
-
-	match chosen_card_slot.trim().parse::<usize>()
-	{
-		Ok(n) => chosen_slot = n,
-		Err(_e) => println!("That was an invalid choice. Dealing with user error is not currently implemented."),
-	}
-
-	if chosen_slot < current_hand.len()
-	{
-		return current_hand[chosen_slot];
-	}
-	else
-	{
-		println!("Automatically choosing the first slot because of user error.");
-		return current_hand[0];
+			Some(bot) => bot.pick_card(game),
+			None => do_random_turn_with_rng(game, rng),
+		},
+		_ => do_random_turn_with_rng(game, rng),
 	}
 }
 
 fn do_random_turn(game: &SushiGoGame) -> Card
+{
+	do_random_turn_with_rng(game, &mut rand::thread_rng())
+}
+
+fn do_random_turn_with_rng<R: Rng>(game: &SushiGoGame, rng: &mut R) -> Card
 {
 	let current_hand = game.get_current_player().get_hand();
 
-	let mut rng = rand::thread_rng();
 	let mut rand_index: usize = 0;
 
 	if current_hand.len() - 1 > 0
@@ -516,40 +673,72 @@ fn do_random_turn(game: &SushiGoGame) -> Card
 		rand_index = rng.gen_range(0, current_hand.len() - 1);
 	}
 
-	return current_hand[rand_index];
+	current_hand[rand_index]
 }
 
-pub fn print_nn_info(nn: &NeuralNetwork)
+// Prints what is known about a saved network from its header.
+pub fn print_nn_summary(nn: &NeuralNetwork)
 {
-	let mut game = SushiGoGame::new(NUMBER_OF_PLAYERS);
+	let properties = nn.get_properties();
+	let metadata = nn.get_metadata();
 
-	game.setup();
+	match nn.get_file_info()
+	{
+		Some(file_info) => println!("Format version: {}", file_info.format_version),
+		None => println!("Format version: not loaded from a file"),
+	}
 
-	println!("\nPlayer Hand:");
+	println!("Model: {}", properties.model);
+	println!("Nodes: {} input, {} hidden, {} output", properties.input_node_count, properties.hidden_node_count, properties.output_node_count);
 
-	for x in game.get_current_player().get_hand()
+	if let Some(file_info) = nn.get_file_info()
 	{
-		println!("{}", x);
+		println!("Activation: {}", file_info.activation);
+		println!("Checksum: {}", file_info.checksum);
 	}
 
-	let input = get_nn_input(&game);
+	let print_optional = |name: &str, value: Option<String>| println!("{}: {}", name, value.unwrap_or_else(|| "unknown".to_string()));
 
-	let output = nn.feed_forward(&input);
+	print_optional("Created", metadata.created.map(|x| x.to_string()));
+	print_optional("Generation", metadata.generation.map(|x| x.to_string()));
+	print_optional("Fitness", metadata.fitness.map(|x| x.to_string()));
+	print_optional("Seed", metadata.seed.map(|x| x.to_string()));
 
-	println!("\nNode Outputs:");
+	print_optional("Parent", metadata.get_parent().map(|x| x.to_string()));
 
-	let mut to_chose_from: Vec<f32> = Vec::new();
+	// Only the most recent ancestors are kept, so a full lineage may have been cut short.
+	let earlier = if metadata.lineage.len() >= MAX_LINEAGE_LENGTH { "... -> " } else { "" };
+
+	println!("Lineage: {}{}", earlier, metadata.lineage.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" -> "));
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
 
-	for (i, x) in output.iter().enumerate()
+	// Plays seeded games in parallel between networks using a stochastic policy and random players.
+	fn play_seeded_games(first_seed: u64) -> Vec<Vec<i32>>
 	{
-		println!("card: {0} weight: {1}",Card::from(i), *x);
-		if game.get_current_player().get_hand().contains(&(Card::from(i))) {to_chose_from.push(*x)} else {to_chose_from.push(0.0)};
-	}
+		let mut rng = StdRng::seed_from_u64(1);
+		let properties = NeuralNetworkModel::default().get_node_count();
+
+		let mut pool = create_neural_networks_with_rng(16, &properties, PolicyMode::Temperature(1.0), &mut rng);
+
+		for x in pool.iter_mut().step_by(4)
+		{
+			x.player_type = NeuralNetworkGamePlayerType::Random;
+		}
+
+		let mut games = Vec::new();
+		create_and_play_games_parallel(&mut games, 4, &mut pool, Some(first_seed));
 
-	println!("\nPossible Outputs:");
+		games.iter().map(|x| x.get_game().get_final_scores()).collect()
+	}
 
-	for (i, x) in to_chose_from.iter().enumerate()
+	#[test]
+	fn seeded_games_play_out_the_same()
 	{
-		println!("card: {0} weight: {1}",Card::from(i), *x);
+		assert_eq!(play_seeded_games(7), play_seeded_games(7));
 	}
-}
\ No newline at end of file
+}
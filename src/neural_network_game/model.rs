@@ -0,0 +1,175 @@
+use super::neural_network::*;
+use super::policy::*;
+
+use super::game::*;
+use super::game::player::card::*;
+
+use super::model_a;
+use super::model_b;
+use super::model_c;
+
+use rand::Rng;
+
+use std::fmt;
+
+// This is the registry of network models. A model describes how the game is encoded as input to the network, the
+// topology of the network, and how the outputs of the network are decoded into a card choice.
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[derive(Serialize, Deserialize, Default)]
+pub enum NeuralNetworkModel
+{
+	// One hot encoding of the hand and chosen cards by position, outputs pick a position in the hand.
+	#[serde(rename = "model_a")]
+	ModelA,
+
+	// One hot encoding of the hand and chosen cards by position, outputs pick a type of card.
+	#[serde(rename = "model_b")]
+	ModelB,
+
+	// Encodes which types of card are in the hand, outputs pick a type of card.
+	// Networks saved before models could be selected were all created with model c, so it is the default.
+	#[serde(rename = "model_c")]
+	#[default]
+	ModelC,
+}
+
+pub const MODELS: [NeuralNetworkModel; 3] = [NeuralNetworkModel::ModelA, NeuralNetworkModel::ModelB, NeuralNetworkModel::ModelC];
+
+impl fmt::Display for NeuralNetworkModel
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let printable = match *self
+		{
+			NeuralNetworkModel::ModelA => "model_a",
+			NeuralNetworkModel::ModelB => "model_b",
+			NeuralNetworkModel::ModelC => "model_c",
+		};
+		write!(f, "{}", printable)
+	}
+}
+
+impl NeuralNetworkModel
+{
+	// Looks up a model by the name it is saved with.
+	pub fn from_name(name: &str) -> Option<NeuralNetworkModel>
+	{
+		MODELS.iter().find(|x| x.to_string() == name.trim()).cloned()
+	}
+
+	// Returns the number of nodes in each layer of a network using this model.
+	pub fn get_node_count(&self) -> NeuralNetworkProperties
+	{
+		match *self
+		{
+			NeuralNetworkModel::ModelA => model_a::get_node_count(),
+			NeuralNetworkModel::ModelB => model_b::get_node_count(),
+			NeuralNetworkModel::ModelC => model_c::get_node_count(),
+		}
+	}
+
+	// Encodes the state of the game from the point of view of the current player.
+	pub fn get_nn_input(&self, game: &SushiGoGame) -> Vec<f32>
+	{
+		match *self
+		{
+			NeuralNetworkModel::ModelA => model_a::get_nn_input(game),
+			NeuralNetworkModel::ModelB => model_b::get_nn_input(game),
+			NeuralNetworkModel::ModelC => model_c::get_nn_input(game),
+		}
+	}
+
+	// Describes what one of the inputs given by get_nn_input means.
+	pub fn get_input_name(&self, input: usize) -> String
+	{
+		match *self
+		{
+			NeuralNetworkModel::ModelA => model_a::get_input_name(input),
+			NeuralNetworkModel::ModelB => model_b::get_input_name(input),
+			NeuralNetworkModel::ModelC => model_c::get_input_name(input),
+		}
+	}
+
+	// Returns which of the network outputs correspond to a card the current player is allowed to pick.
+	pub fn get_legal_actions(&self, game: &SushiGoGame) -> Vec<bool>
+	{
+		match *self
+		{
+			NeuralNetworkModel::ModelA => model_a::get_legal_actions(game),
+			NeuralNetworkModel::ModelB => model_b::get_legal_actions(game),
+			NeuralNetworkModel::ModelC => model_c::get_legal_actions(game),
+		}
+	}
+
+	// Returns the card a network output corresponds to.
+	pub fn get_action_card(&self, game: &SushiGoGame, action: usize) -> Card
+	{
+		match *self
+		{
+			NeuralNetworkModel::ModelA => model_a::get_action_card(game, action),
+			NeuralNetworkModel::ModelB => model_b::get_action_card(game, action),
+			NeuralNetworkModel::ModelC => model_c::get_action_card(game, action),
+		}
+	}
+}
+
+// Use the network to pick a card from the current players hand, decoding the outputs with the given policy.
+pub fn pick_cards(game: &SushiGoGame, nn: &NeuralNetwork, policy: PolicyMode) -> Card
+{
+	pick_cards_with_rng(game, nn, policy, &mut rand::thread_rng())
+}
+
+// The same as pick_cards, with the policy drawing from the given generator.
+pub fn pick_cards_with_rng<R: Rng>(game: &SushiGoGame, nn: &NeuralNetwork, policy: PolicyMode, rng: &mut R) -> Card
+{
+	let model = nn.get_model();
+
+	let output = nn.feed_forward(&model.get_nn_input(game));
+	let legal_actions = model.get_legal_actions(game);
+
+	let action = policy.choose_action_with_rng(&output, &legal_actions, rng);
+
+	model.get_action_card(game, action)
+}
+
+// How the network rated one of the cards the current player could pick.
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct CardWeight
+{
+	pub card: Card,
+
+	// The raw network output for the card.
+	pub weight: f32,
+
+	// How likely the card is to be picked by a temperature 1 policy.
+	pub probability: f32,
+}
+
+// Returns the network output for every card the current player is allowed to pick, in the order of the outputs.
+pub fn get_card_weights(game: &SushiGoGame, nn: &NeuralNetwork) -> Vec<CardWeight>
+{
+	let model = nn.get_model();
+
+	let output = nn.feed_forward(&model.get_nn_input(game));
+	let legal_actions = model.get_legal_actions(game);
+	let probabilities = get_action_probabilities(&output, &legal_actions, 1.0);
+
+	let mut weights = Vec::new();
+
+	for (i, x) in output.iter().enumerate()
+	{
+		if legal_actions[i]
+		{
+			weights.push(CardWeight{card: model.get_action_card(game, i), weight: *x, probability: probabilities[i]});
+		}
+	}
+
+	weights
+}
+
+// Returns a comma separated list of the models which can be selected.
+pub fn get_model_names() -> String
+{
+	MODELS.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")
+}
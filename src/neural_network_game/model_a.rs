@@ -1,35 +1,24 @@
 use super::neural_network::*;
+use super::model::*;
 
 use super::game::*;
 use super::game::player::card::*;
 
-// Use the feed forward algorithm to pick a card from the players current hand.
-pub fn pick_cards(game: &SushiGoGame, nn: &NeuralNetwork) -> Vec<Card>
+// Each output picks a position in the players current hand, so only positions holding a card are legal.
+pub fn get_legal_actions(game: &SushiGoGame) -> Vec<bool>
 {
-	let mut chosen_cards = Vec::new();
+	let hand_size = game.get_current_player().get_hand_size();
 
-    let player_hand = game.get_player_hand(game.get_current_player_id());
-	
-	let output = nn.feed_forward(&get_nn_input(game));
-
-	let mut highest_index = 0;
-	let mut highest_value = -1.0;
-
-	for (i, x) in output.iter().enumerate()
-	{
-		if *x > highest_value && i < player_hand.len()
-		{
-            highest_index = i;
-            highest_value = *x;
-		}
-	}
-
-	chosen_cards.push(player_hand[highest_index]);
+	(0..get_node_count().output_node_count).map(|i| i < hand_size).collect()
+}
 
-    chosen_cards
+// Returns the card at the chosen position of the players hand.
+pub fn get_action_card(game: &SushiGoGame, action: usize) -> Card
+{
+	game.get_current_player_hand()[action]
 }
 
-fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
+pub fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
 {
 	// This will be the vector of inputs fed into the neural network.
 	// Size is twice the number 
@@ -73,11 +62,28 @@ fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
 	player_state
 }
 
-pub fn get_node_count() -> NodeCount
+// Describes what an input means, in the order get_nn_input encodes them.
+pub fn get_input_name(input: usize) -> String
+{
+	let cards = Card::CardMax as usize;
+	let slot = (input / cards) % MAX_HAND_SIZE;
+	let card = Card::from(input % cards);
+
+	if input < MAX_HAND_SIZE * cards
+	{
+		format!("hand slot {} is {}", slot, card)
+	}
+	else
+	{
+		format!("chosen slot {} is {}", slot, card)
+	}
+}
+
+pub fn get_node_count() -> NeuralNetworkProperties
 {
-	let input_size: usize = MAX_HAND_SIZE * (Card::CardMax as usize) * 2;
-	let hidden_layer_size: usize = 136;
-	let output_layer_size: usize = 8;
+	let input_node_count: usize = MAX_HAND_SIZE * (Card::CardMax as usize) * 2;
+	let hidden_node_count: usize = 136;
+	let output_node_count: usize = 8;
 
-	NodeCount{input_size, hidden_layer_size, output_layer_size}
+	NeuralNetworkProperties {input_node_count, hidden_node_count, output_node_count, model: NeuralNetworkModel::ModelA}
 }
\ No newline at end of file
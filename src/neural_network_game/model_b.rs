@@ -1,41 +1,21 @@
 use super::neural_network::*;
+use super::model::*;
 
 use super::game::*;
 use super::game::player::card::*;
 
-// Use the feed forward algorithm to pick a card from the players current hand.
-pub fn pick_cards(game: &SushiGoGame, nn: &NeuralNetwork) -> Vec<Card>
+// Each output picks a type of card, so only types of card in the players current hand are legal.
+pub fn get_legal_actions(game: &SushiGoGame) -> Vec<bool>
 {
-	let mut chosen_cards = Vec::new();
+	let player_hand = game.get_current_player_hand();
 
-    let player_hand = game.get_player_hand(game.get_current_player_id());
-	
-	let output = nn.feed_forward(&get_nn_input(game));
-
-	let mut highest_index = 0;
-	let mut highest_value = -1.0;
-
-	let mut to_chose_from: Vec<f32> = Vec::new();
-
-	for (i, x) in output.iter().enumerate()
-	{
-		if player_hand.contains(&(Card::from(i))) {to_chose_from.push(*x)} else {to_chose_from.push(0.0)};
-	}
-
-	for (i, x) in to_chose_from.iter().enumerate()
-	{
-		if *x > highest_value
-		{
-            highest_index = i;
-            highest_value = *x;
-		}
-	}
-
-	//println!("Chosen Index is {0} and card is {1}", highest_index, Card::from(highest_index));
-	
-	chosen_cards.push(Card::from(highest_index));
+	(0..get_node_count().output_node_count).map(|i| player_hand.contains(&(Card::from(i)))).collect()
+}
 
-    chosen_cards
+// Returns the type of card the chosen output represents.
+pub fn get_action_card(_game: &SushiGoGame, action: usize) -> Card
+{
+	Card::from(action)
 }
 
 pub fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
@@ -82,11 +62,28 @@ pub fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
 	player_state
 }
 
-pub fn get_node_count() -> NodeCount
+// Describes what an input means, in the order get_nn_input encodes them.
+pub fn get_input_name(input: usize) -> String
+{
+	let cards = Card::CardMax as usize;
+	let slot = (input / cards) % MAX_HAND_SIZE;
+	let card = Card::from(input % cards);
+
+	if input < MAX_HAND_SIZE * cards
+	{
+		format!("hand slot {} is {}", slot, card)
+	}
+	else
+	{
+		format!("chosen slot {} is {}", slot, card)
+	}
+}
+
+pub fn get_node_count() -> NeuralNetworkProperties
 {
-	let input_size: usize = MAX_HAND_SIZE * (Card::CardMax as usize) * 2;
-	let hidden_layer_size: usize = 136;
-	let output_layer_size: usize = Card::CardMax as usize;
+	let input_node_count: usize = MAX_HAND_SIZE * (Card::CardMax as usize) * 2;
+	let hidden_node_count: usize = 136;
+	let output_node_count: usize = Card::CardMax as usize;
 
-	NodeCount{input_size, hidden_layer_size, output_layer_size}
+	NeuralNetworkProperties {input_node_count, hidden_node_count, output_node_count, model: NeuralNetworkModel::ModelB}
 }
\ No newline at end of file
@@ -1,50 +1,21 @@
 use super::neural_network::*;
+use super::model::*;
 
 use super::game::*;
 use super::game::player::card::*;
 
-// Use the feed forward algorithm to pick a card from the players current hand.
-pub fn pick_cards(game: &SushiGoGame, nn: &NeuralNetwork) -> Card
+// Each output picks a type of card, so only types of card in the players current hand are legal.
+pub fn get_legal_actions(game: &SushiGoGame) -> Vec<bool>
 {
-	// Get an array of weights based on the cards in our hand.
-    let player_hand = game.get_current_player_hand();
-	let output = nn.feed_forward(&get_nn_input(game));
+	let player_hand = game.get_current_player_hand();
 
-	// Work out what the highest weighted card we have is.
-	let mut highest_index = 0;
-	let mut highest_value = -1.0;
-
-	// This vector holds valid choices.
-	let mut to_chose_from: Vec<f32> = Vec::new();
-
-	for (i, x) in output.iter().enumerate()
-	{
-		if player_hand.contains(&(Card::from(i))) {to_chose_from.push(*x)} else {to_chose_from.push(0.0)};
-	}
-
-	// Find the highest value valid choice.
-	for (i, x) in to_chose_from.iter().enumerate()
-	{
-		if *x > highest_value
-		{
-            highest_index = i;
-            highest_value = *x;
-		}
-	}
-
-	// Print some info about what we chose if the game has that option set.
-	if game.should_print_nn_weights()
-	{
-		print!("Printing weights:\n");
-		for (i, x) in to_chose_from.iter().enumerate()
-		{
-			print!("With the given hand, weight for {} is: {}\n", Card::from(i), x);
-		}
+	(0..get_node_count().output_node_count).map(|i| player_hand.contains(&(Card::from(i)))).collect()
+}
 
-		println!("Chosen Index is {0} and card is {1}", highest_index, Card::from(highest_index));
-	}
-	
-	Card::from(highest_index)
+// Returns the type of card the chosen output represents.
+pub fn get_action_card(_game: &SushiGoGame, action: usize) -> Card
+{
+	Card::from(action)
 }
 
 pub fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
@@ -61,11 +32,17 @@ pub fn get_nn_input(game: &SushiGoGame) -> Vec<f32>
 	player_state
 }
 
+// Describes what an input means, in the order get_nn_input encodes them.
+pub fn get_input_name(input: usize) -> String
+{
+	format!("hand has {}", Card::from(input))
+}
+
 pub fn get_node_count() -> NeuralNetworkProperties
 {
 	let input_node_count: usize = Card::CardMax as usize;
 	let hidden_node_count: usize = 20;
 	let output_node_count: usize = Card::CardMax as usize;
 
-	NeuralNetworkProperties {input_node_count, hidden_node_count, output_node_count}
+	NeuralNetworkProperties {input_node_count, hidden_node_count, output_node_count, model: NeuralNetworkModel::ModelC}
 }
\ No newline at end of file
@@ -1,12 +1,76 @@
-use rulinalg::matrix::{Matrix, BaseMatrixMut};
+use rulinalg::matrix::{Matrix, BaseMatrix, BaseMatrixMut};
 use rand::Rng;
 
+use super::model::NeuralNetworkModel;
+
 use std::error::Error;
+use std::fmt;
+use std::io;
 use std::io::prelude::*;
 use std::fs::File;
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MUTATE_AMOUNT: f32 = 0.01;
+
+// The version of the network file format written by save_nn_to_file. Version 1 is the original headerless format.
+pub const NN_FORMAT_VERSION: u32 = 2;
+
+// The only activation function currently implemented.
+const ACTIVATION: &str = "sigmoid";
+
+// How many ancestors a network remembers, so the lineage of a long run doesn't grow with every generation.
+pub const MAX_LINEAGE_LENGTH: usize = 32;
+
+// Binary files start with one of these so they can be told apart from JSON.
+const BINARY_NN_MAGIC: &[u8; 4] = b"SGNN";
+const BINARY_POPULATION_MAGIC: &[u8; 4] = b"SGNP";
+
+// The encodings networks can be saved in.
+#[derive(Copy,Clone,Debug,PartialEq,Default)]
+pub enum SaveFormat
+{
+    // Human readable JSON, saved with a .txt extension.
+    #[default]
+    Json,
+
+    // Compact little endian bincode, saved with a .bin extension.
+    Binary,
+}
 
-const MUTATE_AMOUNT: f32 = 0.01;
+impl fmt::Display for SaveFormat
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self
+        {
+            SaveFormat::Json => write!(f, "json"),
+            SaveFormat::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+impl SaveFormat
+{
+    pub fn from_name(name: &str) -> Option<SaveFormat>
+    {
+        match name.trim()
+        {
+            "json" => Some(SaveFormat::Json),
+            "binary" => Some(SaveFormat::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn get_extension(&self) -> &str
+    {
+        match *self
+        {
+            SaveFormat::Json => "txt",
+            SaveFormat::Binary => "bin",
+        }
+    }
+}
 
 #[derive(Clone)]
 #[derive(Serialize, Deserialize)]
@@ -20,6 +84,10 @@ pub struct NeuralNetworkProperties
 
     // A vector of outputs used to determine what the nn does. 
     pub output_node_count: usize,
+
+    // The model used to encode inputs and decode outputs for this network.
+    #[serde(default)]
+    pub model: NeuralNetworkModel,
 }
 
 #[derive(Clone)]
@@ -39,14 +107,164 @@ struct NeuralNetworkData
     bias_o: Vec<f32>,
 }
 
+// Information about where a network came from, saved alongside it.
+#[derive(Clone, Default)]
+#[derive(Serialize, Deserialize)]
+pub struct NeuralNetworkMetadata
+{
+    // Seconds since the unix epoch when the network was created, if known.
+    pub created: Option<u64>,
+
+    // The generation of training this network was taken from.
+    pub generation: Option<usize>,
+
+    // The fitness this network had when it was saved.
+    pub fitness: Option<f32>,
+
+    // The seed used to generate this network, if it was seeded.
+    pub seed: Option<u64>,
+
+    // The ID's of the most recent networks this network was bred from, oldest first. The last one is its parent.
+    pub lineage: Vec<usize>,
+}
+
+impl NeuralNetworkMetadata
+{
+    // Records the network this one was bred from, forgetting the oldest ancestor once the lineage is full.
+    pub fn add_parent(&mut self, id: usize)
+    {
+        if self.lineage.len() >= MAX_LINEAGE_LENGTH
+        {
+            let excess = self.lineage.len() + 1 - MAX_LINEAGE_LENGTH;
+            self.lineage.drain(..excess);
+        }
+
+        self.lineage.push(id);
+    }
+
+    pub fn get_parent(&self) -> Option<usize>
+    {
+        self.lineage.last().cloned()
+    }
+}
+
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct NeuralNetworkHeader
+{
+    pub format_version: u32,
+
+    // The node counts and model used to encode the inputs and decode the outputs.
+    pub architecture: NeuralNetworkProperties,
+
+    // The activation function applied to the hidden and output layers.
+    pub activation: String,
+
+    pub metadata: NeuralNetworkMetadata,
+
+    // A checksum of the weights and biases, used to detect corrupted files.
+    pub checksum: String,
+}
+
+// The parts of the header a network was loaded with which aren't otherwise kept on the network.
+#[derive(Clone, Debug)]
+pub struct NeuralNetworkFileInfo
+{
+    pub format_version: u32,
+    pub activation: String,
+
+    // The checksum stored in the file, which the loaded weights and biases matched.
+    pub checksum: String,
+}
+
 #[derive(Clone)]
 #[derive(Serialize, Deserialize)]
 pub struct NeuralNetworkSerializable
+{
+    header: NeuralNetworkHeader,
+    data: NeuralNetworkData
+}
+
+impl NeuralNetworkSerializable
+{
+    pub fn get_header(&self) -> &NeuralNetworkHeader
+    {
+        &self.header
+    }
+}
+
+// A whole population of networks, saved along with the ID of each network.
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct NeuralNetworkPopulationSerializable
+{
+    format_version: u32,
+    networks: Vec<(usize, NeuralNetworkSerializable)>,
+}
+
+// The original file format, which only held node counts and weights.
+#[derive(Serialize, Deserialize)]
+struct LegacyNeuralNetworkSerializable
 {
     properties: NeuralNetworkProperties,
     data: NeuralNetworkData
 }
 
+#[derive(Debug)]
+pub enum NeuralNetworkFileError
+{
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Binary(bincode::Error),
+    UnsupportedVersion(u32),
+    UnsupportedActivation(String),
+    Dimensions(String),
+    Checksum{expected: String, found: String},
+}
+
+impl fmt::Display for NeuralNetworkFileError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            NeuralNetworkFileError::Io(e) => write!(f, "couldn't read or write the network file: {}", e),
+            NeuralNetworkFileError::Parse(e) => write!(f, "the network file is not valid: {}", e),
+            NeuralNetworkFileError::Binary(e) => write!(f, "the binary network file is not valid: {}", e),
+            NeuralNetworkFileError::UnsupportedVersion(v) => write!(f, "network file version {} is newer than this program supports ({})", v, NN_FORMAT_VERSION),
+            NeuralNetworkFileError::UnsupportedActivation(a) => write!(f, "network uses the activation '{}' which is not implemented", a),
+            NeuralNetworkFileError::Dimensions(message) => write!(f, "network dimensions don't match: {}", message),
+            NeuralNetworkFileError::Checksum{expected, found} => write!(f, "network checksum is {} but the file says it should be {}", found, expected),
+        }
+    }
+}
+
+impl Error for NeuralNetworkFileError {}
+
+impl From<io::Error> for NeuralNetworkFileError
+{
+    fn from(e: io::Error) -> NeuralNetworkFileError
+    {
+        NeuralNetworkFileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for NeuralNetworkFileError
+{
+    fn from(e: serde_json::Error) -> NeuralNetworkFileError
+    {
+        NeuralNetworkFileError::Parse(e)
+    }
+}
+
+impl From<bincode::Error> for NeuralNetworkFileError
+{
+    fn from(e: bincode::Error) -> NeuralNetworkFileError
+    {
+        NeuralNetworkFileError::Binary(e)
+    }
+}
+
 #[derive(Clone)]
 pub struct NeuralNetwork
 {
@@ -67,6 +285,12 @@ pub struct NeuralNetwork
     
     // A matrix of biases for the output layer.
     bias_o: Matrix<f32>,
+
+    // Where this network came from.
+    metadata: NeuralNetworkMetadata,
+
+    // The header the network was loaded with, if it was loaded from a file.
+    file_info: Option<NeuralNetworkFileInfo>,
 }
 
 
@@ -108,18 +332,12 @@ impl NeuralNetwork
             output_vector.push(*x);
         }
 
-        return output_vector;
-    }
-
-    pub fn clone(&self) -> NeuralNetwork
-    {
-        //return NeuralNetwork{id: self.id, input_node_count: self.input_node_count, hidden_node_count: self.hidden_node_count, output_node_count: self.output_node_count, weights_ih: self.weights_ih.clone(), weights_ho: self.weights_ho.clone(), bias_h: self.bias_h.clone(), bias_o: self.bias_o.clone()};
-        return NeuralNetwork{id: self.id, properties: self.properties.clone(), weights_ih: self.weights_ih.clone(), weights_ho: self.weights_ho.clone(), bias_h: self.bias_h.clone(), bias_o: self.bias_o.clone()};
+        output_vector
     }
 
     pub fn get_id(&self) -> usize
     {
-        return self.id;
+        self.id
     }
 
     pub fn set_id(&mut self, new_id: usize)
@@ -127,7 +345,32 @@ impl NeuralNetwork
         self.id = new_id;
     }
 
-    fn get_save_data(&self) -> String
+    pub fn get_model(&self) -> NeuralNetworkModel
+    {
+        self.properties.model
+    }
+
+    pub fn get_properties(&self) -> &NeuralNetworkProperties
+    {
+        &self.properties
+    }
+
+    pub fn get_metadata(&self) -> &NeuralNetworkMetadata
+    {
+        &self.metadata
+    }
+
+    pub fn get_metadata_mut(&mut self) -> &mut NeuralNetworkMetadata
+    {
+        &mut self.metadata
+    }
+
+    pub fn get_file_info(&self) -> Option<&NeuralNetworkFileInfo>
+    {
+        self.file_info.as_ref()
+    }
+
+    pub fn get_serializable(&self) -> NeuralNetworkSerializable
     {
         // Copy all the matrix data into vectors.
         let wih = self.weights_ih.data().clone();
@@ -135,54 +378,91 @@ impl NeuralNetwork
         let bh = self.bias_h.data().clone();
         let bo = self.bias_o.data().clone();
 
-        // Construct the properties and data
-        let properties = NeuralNetworkProperties{input_node_count: self.properties.input_node_count, hidden_node_count: self.properties.hidden_node_count, output_node_count: self.properties.output_node_count};
         let data = NeuralNetworkData{weights_ih: wih, weights_ho: who, bias_h: bh, bias_o: bo};
 
-        // Create a neural network serializable struct
-        let nns = NeuralNetworkSerializable{properties: properties, data: data};
+        // Construct the header describing the data.
+        let header = NeuralNetworkHeader{format_version: NN_FORMAT_VERSION, architecture: self.properties.clone(), activation: ACTIVATION.to_string(), metadata: self.metadata.clone(), checksum: data.get_checksum()};
+
+        NeuralNetworkSerializable{header, data}
+    }
+
+    fn get_save_data(&self, format: SaveFormat) -> Result<Vec<u8>, NeuralNetworkFileError>
+    {
+        let nns = self.get_serializable();
 
         // Serialize and return.
-        return serde_json::to_string(&nns).unwrap();
+        match format
+        {
+            SaveFormat::Json => Ok(serde_json::to_string_pretty(&nns)?.into_bytes()),
+            SaveFormat::Binary =>
+            {
+                let mut bytes = BINARY_NN_MAGIC.to_vec();
+                bytes.append(&mut bincode::serialize(&nns)?);
+                Ok(bytes)
+            }
+        }
     }
 
-    pub fn save_nn_to_file(&self, file_name: String)
+    pub fn save_nn_to_file(&self, file_name: String) -> Result<(), NeuralNetworkFileError>
     {
-        let s = file_name + ".txt";
+        self.save_nn_to_file_with_format(file_name, SaveFormat::Json)
+    }
+
+    // Saves the network, adding the extension for the format to the file name.
+    pub fn save_nn_to_file_with_format(&self, file_name: String, format: SaveFormat) -> Result<(), NeuralNetworkFileError>
+    {
+        let s = format!("{}.{}", file_name, format.get_extension());
         let path = Path::new(&s);
-        let display = path.display();
-
-        // Open a file in write-only mode, returns `io::Result<File>`
-        let mut file = match File::create(&path) {
-            Err(why) => panic!("couldn't create {}: {}",
-                            display,
-                            why.description()),
-            Ok(file) => file,
-        };
-
-        // Write the `LOREM_IPSUM` string to `file`, returns `io::Result<()>`
-        match file.write_all(self.get_save_data().as_bytes()) {
-            Err(why) => {
-                panic!("couldn't write to {}: {}", display,
-                                                why.description())
-            },
-            Ok(_) => println!("successfully wrote to {}", display),
-        }
+
+        let mut file = File::create(path)?;
+        file.write_all(&self.get_save_data(format)?)?;
+
+        info!("successfully wrote to {}", path.display());
+
+        Ok(())
+    }
+
+    // Returns every weight and bias in the network as one list, always in the same order.
+    pub fn get_parameters(&self) -> Vec<f32>
+    {
+        self.weights_ih.iter().chain(self.weights_ho.iter()).chain(self.bias_h.iter()).chain(self.bias_o.iter()).cloned().collect()
     }
 
     pub fn mutate(&mut self)
     {
-        self.bias_o = self.bias_o.clone().apply(&mutate);
-        self.bias_h = self.bias_h.clone().apply(&mutate);
+        self.mutate_with_rng(MUTATE_AMOUNT, &mut rand::thread_rng());
+    }
+
+    // Moves every weight and bias by a random amount up to mutate_amount in either direction.
+    pub fn mutate_with_rng<R: Rng>(&mut self, mutate_amount: f32, rng: &mut R)
+    {
+        let matrices = vec![&mut self.bias_o, &mut self.bias_h, &mut self.weights_ho, &mut self.weights_ih];
+
+        for matrix in matrices
+        {
+            for x in matrix.iter_mut()
+            {
+                *x += rng.gen_range(-mutate_amount, mutate_amount);
+            }
+        }
 
-        self.weights_ho = self.weights_ho.clone().apply(&mutate);
-        self.weights_ih = self.weights_ih.clone().apply(&mutate);
+        // The weights no longer match the file the network was loaded from.
+        self.file_info = None;
     }
 }
 
-pub fn new_neural_network(id: usize, in_input_node_count: usize, in_hidden_node_count: usize, in_output_node_count: usize) -> NeuralNetwork
+pub fn new_neural_network(id: usize, properties: &NeuralNetworkProperties) -> NeuralNetwork
+{
+    new_neural_network_with_rng(id, properties, &mut rand::thread_rng())
+}
+
+// Creates a network with random weights and biases drawn from the given generator, so seeded runs start the same.
+pub fn new_neural_network_with_rng<R: Rng>(id: usize, properties: &NeuralNetworkProperties, rng: &mut R) -> NeuralNetwork
 {
-    let mut rng = rand::thread_rng();
+
+    let in_input_node_count = properties.input_node_count;
+    let in_hidden_node_count = properties.hidden_node_count;
+    let in_output_node_count = properties.output_node_count;
 
     let initial_weights_ih: Vec<f32> = (0..in_input_node_count * in_hidden_node_count).map(|_| 
     {
@@ -210,41 +490,378 @@ pub fn new_neural_network(id: usize, in_input_node_count: usize, in_hidden_node_
     let bias_h = Matrix::new(in_hidden_node_count, 1, initial_bias_h);
     let bias_o = Matrix::new(in_output_node_count, 1, initial_bias_o);
 
-    let properties = NeuralNetworkProperties{input_node_count: in_input_node_count, hidden_node_count: in_hidden_node_count, output_node_count: in_output_node_count};
+    let metadata = NeuralNetworkMetadata{created: Some(get_unix_time()), ..Default::default()};
+
+    NeuralNetwork{id, properties: properties.clone(), weights_ih, weights_ho, bias_h, bias_o, metadata, file_info: None}
+}
+
+// Creates a network with no nodes, used by players who don't need a network.
+pub fn empty_neural_network() -> NeuralNetwork
+{
+    new_neural_network(0, &NeuralNetworkProperties{input_node_count: 0, hidden_node_count: 0, output_node_count: 0, model: NeuralNetworkModel::default()})
+}
+
+impl NeuralNetworkData
+{
+    // Computes an FNV-1a hash of the bits of every weight and bias.
+    fn get_checksum(&self) -> String
+    {
+        let mut hash: u64 = 0xcbf29ce484222325;
+
+        for x in self.weights_ih.iter().chain(self.weights_ho.iter()).chain(self.bias_h.iter()).chain(self.bias_o.iter())
+        {
+            for byte in x.to_bits().to_le_bytes().iter()
+            {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        format!("{:016x}", hash)
+    }
+}
+
+// Checks a vector has the number of values a matrix of the given size needs.
+fn check_dimensions(name: &str, values: &[f32], rows: usize, cols: usize) -> Result<(), NeuralNetworkFileError>
+{
+    if values.len() != rows * cols
+    {
+        return Err(NeuralNetworkFileError::Dimensions(format!("{} has {} values but a {}x{} matrix was expected", name, values.len(), rows, cols)));
+    }
 
-    return NeuralNetwork{id: id, properties: properties, weights_ih: weights_ih, weights_ho: weights_ho, bias_h: bias_h, bias_o: bias_o};
+    Ok(())
 }
 
-pub fn load_save_data(id: usize, data_string: String) -> NeuralNetwork
+// Builds a network from its saved form, checking the header against the data.
+pub fn load_serializable(id: usize, loaded_data: NeuralNetworkSerializable) -> Result<NeuralNetwork, NeuralNetworkFileError>
 {
-    let loaded_data: NeuralNetworkSerializable = serde_json::from_str(&data_string).unwrap();
+    let header = loaded_data.header;
+    let data = loaded_data.data;
+
+    if header.format_version > NN_FORMAT_VERSION
+    {
+        return Err(NeuralNetworkFileError::UnsupportedVersion(header.format_version));
+    }
 
-    let properties = NeuralNetworkProperties{input_node_count: loaded_data.properties.input_node_count, hidden_node_count: loaded_data.properties.hidden_node_count, output_node_count: loaded_data.properties.output_node_count};
+    if header.activation != ACTIVATION
+    {
+        return Err(NeuralNetworkFileError::UnsupportedActivation(header.activation));
+    }
 
-    let weights_ih = Matrix::new(properties.hidden_node_count, properties.input_node_count, loaded_data.data.weights_ih);
-    let weights_ho = Matrix::new(properties.output_node_count, properties.hidden_node_count, loaded_data.data.weights_ho);
-    let bias_h = Matrix::new(properties.hidden_node_count, 1, loaded_data.data.bias_h);
-    let bias_o = Matrix::new(properties.output_node_count, 1, loaded_data.data.bias_o);
+    let properties = header.architecture;
 
-    return NeuralNetwork{id: id, properties: properties, weights_ih: weights_ih, weights_ho: weights_ho, bias_h: bias_h, bias_o: bias_o};
+    // The model decides how many inputs and outputs the network must have.
+    let model_node_count = properties.model.get_node_count();
+
+    if properties.input_node_count != model_node_count.input_node_count || properties.output_node_count != model_node_count.output_node_count
+    {
+        return Err(NeuralNetworkFileError::Dimensions(format!("{} needs {} inputs and {} outputs but the network has {} and {}",
+            properties.model, model_node_count.input_node_count, model_node_count.output_node_count, properties.input_node_count, properties.output_node_count)));
+    }
+
+    check_dimensions("weights_ih", &data.weights_ih, properties.hidden_node_count, properties.input_node_count)?;
+    check_dimensions("weights_ho", &data.weights_ho, properties.output_node_count, properties.hidden_node_count)?;
+    check_dimensions("bias_h", &data.bias_h, properties.hidden_node_count, 1)?;
+    check_dimensions("bias_o", &data.bias_o, properties.output_node_count, 1)?;
+
+    let checksum = data.get_checksum();
+
+    if checksum != header.checksum
+    {
+        return Err(NeuralNetworkFileError::Checksum{expected: header.checksum, found: checksum});
+    }
+
+    let weights_ih = Matrix::new(properties.hidden_node_count, properties.input_node_count, data.weights_ih);
+    let weights_ho = Matrix::new(properties.output_node_count, properties.hidden_node_count, data.weights_ho);
+    let bias_h = Matrix::new(properties.hidden_node_count, 1, data.bias_h);
+    let bias_o = Matrix::new(properties.output_node_count, 1, data.bias_o);
+
+    let file_info = NeuralNetworkFileInfo{format_version: header.format_version, activation: header.activation, checksum: header.checksum};
+
+    Ok(NeuralNetwork{id, properties, weights_ih, weights_ho, bias_h, bias_o, metadata: header.metadata, file_info: Some(file_info)})
 }
 
-fn sigmoid(x: f32) -> f32
+// Converts a network saved in the original headerless format into the current format.
+fn migrate_legacy_save_data(legacy: LegacyNeuralNetworkSerializable) -> NeuralNetworkSerializable
 {
-	return 1.0 / (1.0 + (-x).exp());
+    let checksum = legacy.data.get_checksum();
+
+    let header = NeuralNetworkHeader{format_version: 1, architecture: legacy.properties, activation: ACTIVATION.to_string(), metadata: NeuralNetworkMetadata::default(), checksum};
+
+    NeuralNetworkSerializable{header, data: legacy.data}
 }
 
-fn mutate(x: f32) -> f32
+// Loads a network from either format, binary data is recognised by its magic number.
+pub fn load_save_bytes(id: usize, bytes: &[u8]) -> Result<NeuralNetwork, NeuralNetworkFileError>
 {
-    let mut rng = rand::thread_rng();
-    return x + rng.gen_range(MUTATE_AMOUNT * -1.0, MUTATE_AMOUNT);
+    if bytes.starts_with(BINARY_NN_MAGIC)
+    {
+        return load_serializable(id, bincode::deserialize(&bytes[BINARY_NN_MAGIC.len()..])?);
+    }
+
+    match String::from_utf8(bytes.to_vec())
+    {
+        Ok(data_string) => load_save_data(id, data_string),
+        Err(e) => Err(NeuralNetworkFileError::Io(io::Error::new(io::ErrorKind::InvalidData, e))),
+    }
 }
 
-pub fn load_nn_from_file(file_name: &str) -> NeuralNetwork
+pub fn load_save_data(id: usize, data_string: String) -> Result<NeuralNetwork, NeuralNetworkFileError>
+{
+    let value: serde_json::Value = serde_json::from_str(&data_string)?;
+
+    // Files without a header were written before the format was versioned.
+    let loaded_data = if value.get("header").is_some()
+    {
+        serde_json::from_value::<NeuralNetworkSerializable>(value)?
+    }
+    else
+    {
+        migrate_legacy_save_data(serde_json::from_value::<LegacyNeuralNetworkSerializable>(value)?)
+    };
+
+    load_serializable(id, loaded_data)
+}
+
+fn sigmoid(x: f32) -> f32
+{
+	1.0 / (1.0 + (-x).exp())
+}
+
+pub fn load_nn_from_file(file_name: &str) -> Result<NeuralNetwork, NeuralNetworkFileError>
 {
 	let path = Path::new(file_name);
-	let mut data = String::new();
-    let mut f = File::open(&path).expect("Unable to open file");
-    f.read_to_string(&mut data).expect("Unable to read string");
-    return load_save_data(0, data);
-}
\ No newline at end of file
+	let mut data = Vec::new();
+    let mut f = File::open(path)?;
+    f.read_to_end(&mut data)?;
+    load_save_bytes(0, &data)
+}
+
+// Saves a whole population of networks to a single file, adding the extension for the format to the file name.
+pub fn save_population_to_file(nn: &[NeuralNetwork], file_name: String, format: SaveFormat) -> Result<(), NeuralNetworkFileError>
+{
+    let population = NeuralNetworkPopulationSerializable{format_version: NN_FORMAT_VERSION, networks: nn.iter().map(|x| (x.get_id(), x.get_serializable())).collect()};
+
+    let bytes = match format
+    {
+        SaveFormat::Json => serde_json::to_string_pretty(&population)?.into_bytes(),
+        SaveFormat::Binary =>
+        {
+            let mut bytes = BINARY_POPULATION_MAGIC.to_vec();
+            bytes.append(&mut bincode::serialize(&population)?);
+            bytes
+        }
+    };
+
+    let s = format!("{}.{}", file_name, format.get_extension());
+    let path = Path::new(&s);
+
+    let mut file = File::create(path)?;
+    file.write_all(&bytes)?;
+
+    info!("successfully wrote {} networks to {}", nn.len(), path.display());
+
+    Ok(())
+}
+
+pub fn load_population_from_file(file_name: &str) -> Result<Vec<NeuralNetwork>, NeuralNetworkFileError>
+{
+    let path = Path::new(file_name);
+    let mut bytes = Vec::new();
+    let mut f = File::open(path)?;
+    f.read_to_end(&mut bytes)?;
+
+    let population: NeuralNetworkPopulationSerializable = if bytes.starts_with(BINARY_POPULATION_MAGIC)
+    {
+        bincode::deserialize(&bytes[BINARY_POPULATION_MAGIC.len()..])?
+    }
+    else
+    {
+        serde_json::from_slice(&bytes)?
+    };
+
+    if population.format_version > NN_FORMAT_VERSION
+    {
+        return Err(NeuralNetworkFileError::UnsupportedVersion(population.format_version));
+    }
+
+    population.networks.into_iter().map(|(id, x)| load_serializable(id, x)).collect()
+}
+
+pub fn get_unix_time() -> u64
+{
+    match SystemTime::now().duration_since(UNIX_EPOCH)
+    {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn test_network(id: usize) -> NeuralNetwork
+    {
+        new_neural_network(id, &NeuralNetworkModel::default().get_node_count())
+    }
+
+    // A file name in the temp directory which no other test uses.
+    fn temp_file_name(name: &str) -> String
+    {
+        std::env::temp_dir().join(format!("sushi_go_ai_test_{}_{}", std::process::id(), name)).to_string_lossy().into_owned()
+    }
+
+    // Every weight and bias of a network, in the order they are saved.
+    fn get_parameters(nn: &NeuralNetwork) -> Vec<f32>
+    {
+        let data = nn.get_serializable().data;
+
+        data.weights_ih.iter().chain(data.weights_ho.iter()).chain(data.bias_h.iter()).chain(data.bias_o.iter()).cloned().collect()
+    }
+
+    fn assert_same_parameters(a: &NeuralNetwork, b: &NeuralNetwork)
+    {
+        let a = get_parameters(a);
+        let b = get_parameters(b);
+
+        assert_eq!(a.len(), b.len());
+
+        for (x, y) in a.iter().zip(b.iter())
+        {
+            assert_eq!(x.to_bits(), y.to_bits());
+        }
+    }
+
+    fn network_round_trip(format: SaveFormat)
+    {
+        let nn = test_network(3);
+        let file_name = temp_file_name(&format!("network_{}", format));
+
+        nn.save_nn_to_file_with_format(file_name.clone(), format).unwrap();
+
+        let path = format!("{}.{}", file_name, format.get_extension());
+        let loaded = load_nn_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_same_parameters(&nn, &loaded);
+
+        let file_info = loaded.get_file_info().unwrap();
+        assert_eq!(file_info.format_version, NN_FORMAT_VERSION);
+        assert_eq!(file_info.checksum, nn.get_serializable().get_header().checksum);
+    }
+
+    fn population_round_trip(format: SaveFormat)
+    {
+        let population: Vec<NeuralNetwork> = (0..4).map(|x| test_network(x * 10)).collect();
+        let file_name = temp_file_name(&format!("population_{}", format));
+
+        save_population_to_file(&population, file_name.clone(), format).unwrap();
+
+        let path = format!("{}.{}", file_name, format.get_extension());
+        let loaded = load_population_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(population.len(), loaded.len());
+
+        for (a, b) in population.iter().zip(loaded.iter())
+        {
+            assert_eq!(a.get_id(), b.get_id());
+            assert_same_parameters(a, b);
+        }
+    }
+
+    #[test]
+    fn json_network_round_trip()
+    {
+        network_round_trip(SaveFormat::Json);
+    }
+
+    #[test]
+    fn binary_network_round_trip()
+    {
+        network_round_trip(SaveFormat::Binary);
+    }
+
+    #[test]
+    fn json_population_round_trip()
+    {
+        population_round_trip(SaveFormat::Json);
+    }
+
+    #[test]
+    fn binary_population_round_trip()
+    {
+        population_round_trip(SaveFormat::Binary);
+    }
+
+    #[test]
+    fn legacy_network_migrates()
+    {
+        let nn = test_network(0);
+        let serializable = nn.get_serializable();
+        let legacy = LegacyNeuralNetworkSerializable{properties: nn.get_properties().clone(), data: serializable.data};
+
+        let loaded = load_save_data(0, serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        assert_same_parameters(&nn, &loaded);
+        assert!(loaded.get_metadata().created.is_none());
+        assert_eq!(loaded.get_file_info().unwrap().format_version, 1);
+    }
+
+    #[test]
+    fn lineage_keeps_the_most_recent_ancestors()
+    {
+        let mut metadata = NeuralNetworkMetadata::default();
+
+        for x in 0..MAX_LINEAGE_LENGTH * 3
+        {
+            metadata.add_parent(x);
+        }
+
+        assert_eq!(metadata.lineage.len(), MAX_LINEAGE_LENGTH);
+        assert_eq!(metadata.lineage[0], MAX_LINEAGE_LENGTH * 2);
+        assert_eq!(metadata.get_parent(), Some(MAX_LINEAGE_LENGTH * 3 - 1));
+    }
+
+    #[test]
+    fn corrupted_checksum_is_rejected()
+    {
+        let mut serializable = test_network(0).get_serializable();
+        serializable.data.bias_o[0] += 1.0;
+
+        match load_save_data(0, serde_json::to_string(&serializable).unwrap())
+        {
+            Err(NeuralNetworkFileError::Checksum{..}) => (),
+            _ => panic!("a JSON network with a bad checksum was loaded"),
+        }
+
+        let mut bytes = BINARY_NN_MAGIC.to_vec();
+        bytes.append(&mut bincode::serialize(&serializable).unwrap());
+
+        match load_save_bytes(0, &bytes)
+        {
+            Err(NeuralNetworkFileError::Checksum{..}) => (),
+            _ => panic!("a binary network with a bad checksum was loaded"),
+        }
+    }
+
+    #[test]
+    fn bad_magic_is_rejected()
+    {
+        let mut bytes = b"SGNX".to_vec();
+        bytes.append(&mut bincode::serialize(&test_network(0).get_serializable()).unwrap());
+
+        assert!(load_save_bytes(0, &bytes).is_err());
+
+        let file_name = temp_file_name("bad_magic_population.bin");
+        std::fs::write(&file_name, &bytes).unwrap();
+        let loaded = load_population_from_file(&file_name);
+        std::fs::remove_file(&file_name).unwrap();
+
+        assert!(loaded.is_err());
+    }
+}
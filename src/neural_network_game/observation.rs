@@ -0,0 +1,120 @@
+use super::NUMBER_OF_PLAYERS;
+use super::game::*;
+use super::game::player::card::*;
+
+// Everything one player can see of the game, sent to players who aren't in the same process.
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct Observation
+{
+	// The round being played, starting from 0.
+	pub round: u8,
+
+	// The seat of the player this is seen by.
+	pub player: usize,
+
+	// Includes the none card, which keeps the chopsticks, if the player is using their chopsticks.
+	pub hand: Vec<Card>,
+
+	// The cards each player has chosen this round, indexed by seat. Everyone reveals the card they picked at the same
+	// time, so other players' cards are only shown up to as many as this player has picked.
+	pub chosen: Vec<Vec<Card>>,
+
+	// Each player's score from the rounds which have finished, not counting puddings.
+	pub scores: Vec<i32>,
+
+	// How many puddings each player kept in the rounds which have finished.
+	pub puddings: Vec<i16>,
+
+	pub using_chopsticks: bool,
+
+	// The cards which can be picked, each listed once.
+	pub legal_actions: Vec<Card>,
+}
+
+impl Observation
+{
+	pub fn new(game: &SushiGoGame, player: usize) -> Observation
+	{
+		let hand = game.get_player_hand(player);
+		let revealed = game.get_player_chosen(player).len();
+
+		let chosen = (0..game.get_num_players()).map(|i| game.get_player_chosen(i).into_iter().take(revealed).collect()).collect();
+
+		let mut legal_actions: Vec<Card> = Vec::new();
+
+		for x in &hand
+		{
+			if !legal_actions.contains(x)
+			{
+				legal_actions.push(*x);
+			}
+		}
+
+		Observation
+		{
+			round: game.get_current_round(),
+			player,
+			hand,
+			chosen,
+			scores: game.get_running_scores(),
+			puddings: game.get_pudding_counts(),
+			using_chopsticks: player == game.get_current_player_id() && game.is_using_chopsticks(),
+			legal_actions,
+		}
+	}
+
+	// Rebuilds as much of the game as the observation shows, so bots can pick a card from it.
+	pub fn to_game(&self) -> SushiGoGame
+	{
+		SushiGoGame::from_position(self.player, self.round, self.hand.clone(), self.chosen.clone(), self.using_chopsticks)
+	}
+
+	pub fn is_legal(&self, card: Card) -> bool
+	{
+		self.legal_actions.contains(&card)
+	}
+
+	// Checks an observation from outside the program could have come from a real game, so it can be turned back into
+	// one. Returns what is wrong with it if not.
+	pub fn validate(&self) -> Result<(), String>
+	{
+		if self.chosen.len() != NUMBER_OF_PLAYERS
+		{
+			return Err(format!("chosen has {} players, it needs {}", self.chosen.len(), NUMBER_OF_PLAYERS));
+		}
+
+		if self.player >= NUMBER_OF_PLAYERS
+		{
+			return Err(format!("player {} isn't one of the {} seats", self.player, NUMBER_OF_PLAYERS));
+		}
+
+		if self.round >= NUMBER_OF_ROUNDS
+		{
+			return Err(format!("round {} is past the last round", self.round));
+		}
+
+		// The none card for keeping the chopsticks is the only thing added to a full hand.
+		if self.hand.is_empty() || self.hand.len() > MAX_HAND_SIZE + 1
+		{
+			return Err(format!("the hand has {} cards", self.hand.len()));
+		}
+
+		if self.chosen.iter().any(|x| x.len() > MAX_HAND_SIZE)
+		{
+			return Err("a player has chosen more cards than are dealt in a round".to_string());
+		}
+
+		if self.hand.iter().chain(self.chosen.iter().flatten()).any(|x| *x == Card::CardMax)
+		{
+			return Err("CardMax isn't a card".to_string());
+		}
+
+		if !self.using_chopsticks && self.hand.contains(&Card::None)
+		{
+			return Err("the hand can only have None in it when using chopsticks".to_string());
+		}
+
+		Ok(())
+	}
+}
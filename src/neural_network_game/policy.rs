@@ -0,0 +1,268 @@
+use rand::Rng;
+
+use std::fmt;
+
+// The ways in which the outputs of a network can be turned into an action.
+#[derive(Copy,Clone,Debug,PartialEq,Default)]
+pub enum PolicyMode
+{
+	// Always take the legal action with the highest output.
+	#[default]
+	Argmax,
+
+	// Sample from the softmax of the legal outputs, lower temperatures get closer to argmax.
+	Temperature(f32),
+
+	// Take a uniformly random legal action with the given probability, otherwise take the argmax.
+	EpsilonGreedy(f32),
+}
+
+impl fmt::Display for PolicyMode
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match *self
+		{
+			PolicyMode::Argmax => write!(f, "argmax"),
+			PolicyMode::Temperature(t) => write!(f, "temperature={}", t),
+			PolicyMode::EpsilonGreedy(e) => write!(f, "epsilon={}", e),
+		}
+	}
+}
+
+impl PolicyMode
+{
+	// Parses a policy written as 'argmax', 'temperature=<t>' or 'epsilon=<e>'.
+	pub fn from_name(name: &str) -> Option<PolicyMode>
+	{
+		let mut parts = name.trim().splitn(2, '=');
+
+		let mode = parts.next().unwrap_or("");
+		let value = parts.next().map(|x| x.trim().parse::<f32>());
+
+		match (mode, value)
+		{
+			("argmax", None) => Some(PolicyMode::Argmax),
+			("temperature", Some(Ok(t))) if t > 0.0 => Some(PolicyMode::Temperature(t)),
+			("epsilon", Some(Ok(e))) if (0.0..=1.0).contains(&e) => Some(PolicyMode::EpsilonGreedy(e)),
+			_ => None,
+		}
+	}
+
+	// Picks the index of an action given the outputs of a network and which of those outputs are legal.
+	pub fn choose_action(&self, outputs: &[f32], legal_actions: &[bool]) -> usize
+	{
+		self.choose_action_with_rng(outputs, legal_actions, &mut rand::thread_rng())
+	}
+
+	// Picks an action drawing any random choices from the given generator, so seeded games pick the same actions.
+	pub fn choose_action_with_rng<R: Rng>(&self, outputs: &[f32], legal_actions: &[bool], rng: &mut R) -> usize
+	{
+		assert!(outputs.len() == legal_actions.len(), "The number of outputs does not match the number of actions.");
+
+		match *self
+		{
+			PolicyMode::Argmax => get_best_action(outputs, legal_actions),
+			PolicyMode::Temperature(t) => sample_action(&get_action_probabilities(outputs, legal_actions, t), rng),
+			PolicyMode::EpsilonGreedy(e) =>
+			{
+				if rng.gen::<f32>() < e
+				{
+					let legal: Vec<usize> = (0..legal_actions.len()).filter(|i| legal_actions[*i]).collect();
+					legal[rng.gen_range(0, legal.len())]
+				}
+				else
+				{
+					get_best_action(outputs, legal_actions)
+				}
+			}
+		}
+	}
+}
+
+// Returns the legal action with the highest output.
+pub fn get_best_action(outputs: &[f32], legal_actions: &[bool]) -> usize
+{
+	let mut best_index = None;
+
+	for (i, x) in outputs.iter().enumerate()
+	{
+		if !legal_actions[i]
+		{
+			continue;
+		}
+
+		match best_index
+		{
+			Some(best) if outputs[best] >= *x => {},
+			_ => best_index = Some(i),
+		}
+	}
+
+	best_index.expect("There were no legal actions to choose from!")
+}
+
+// Applies a softmax over the legal outputs. Illegal actions are given a probability of exactly zero.
+pub fn get_action_probabilities(outputs: &[f32], legal_actions: &[bool], temperature: f32) -> Vec<f32>
+{
+	// Subtract the largest legal output before exponentiating so large outputs can't overflow.
+	let max_output = outputs.iter().zip(legal_actions.iter())
+		.filter(|(_, legal)| **legal)
+		.map(|(x, _)| *x)
+		.fold(f32::NEG_INFINITY, f32::max);
+
+	let exponents: Vec<f32> = outputs.iter().zip(legal_actions.iter())
+		.map(|(x, legal)| if *legal { ((*x - max_output) / temperature).exp() } else { 0.0 })
+		.collect();
+
+	let total: f32 = exponents.iter().sum();
+
+	exponents.iter().map(|x| x / total).collect()
+}
+
+// Picks an index at random using the given probabilities.
+fn sample_action<R: Rng>(probabilities: &[f32], rng: &mut R) -> usize
+{
+	let mut r: f32 = rng.gen();
+
+	let mut last_possible = 0;
+
+	for (i, x) in probabilities.iter().enumerate()
+	{
+		if *x <= 0.0
+		{
+			continue;
+		}
+
+		last_possible = i;
+		r -= *x;
+
+		if r < 0.0
+		{
+			return i;
+		}
+	}
+
+	// Rounding errors can leave a tiny amount of probability over, give it to the last possible action.
+	last_possible
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	// The largest output belongs to an illegal action, so a policy ignoring the mask would pick it.
+	const OUTPUTS: [f32; 5] = [0.2, 5.0, -1.0, 0.7, 3.0];
+	const LEGAL_ACTIONS: [bool; 5] = [true, false, true, true, false];
+
+	#[test]
+	fn illegal_actions_have_no_probability()
+	{
+		for temperature in [0.1, 1.0, 10.0].iter()
+		{
+			let probabilities = get_action_probabilities(&OUTPUTS, &LEGAL_ACTIONS, *temperature);
+
+			for (i, x) in probabilities.iter().enumerate()
+			{
+				if LEGAL_ACTIONS[i]
+				{
+					assert!(*x > 0.0, "Legal action {} has probability {}", i, x);
+				}
+				else
+				{
+					assert_eq!(*x, 0.0);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn probabilities_sum_to_one()
+	{
+		// Large outputs would overflow without subtracting the largest legal output first.
+		let large_outputs = [1000.0, 2000.0, 1500.0, -3000.0, 1999.0];
+
+		for outputs in [OUTPUTS, large_outputs].iter()
+		{
+			for temperature in [0.01, 1.0, 100.0].iter()
+			{
+				let total: f32 = get_action_probabilities(outputs, &LEGAL_ACTIONS, *temperature).iter().sum();
+
+				assert!((total - 1.0).abs() < 1e-5, "The probabilities sum to {}", total);
+			}
+		}
+	}
+
+	#[test]
+	fn argmax_picks_the_best_legal_action()
+	{
+		assert_eq!(get_best_action(&OUTPUTS, &LEGAL_ACTIONS), 3);
+		assert_eq!(PolicyMode::Argmax.choose_action(&OUTPUTS, &LEGAL_ACTIONS), 3);
+
+		// Only one action is legal, and it has the smallest output.
+		assert_eq!(get_best_action(&OUTPUTS, &[false, false, true, false, false]), 2);
+	}
+
+	// Picks an action with the policy many times, returning how often each action was picked.
+	fn count_picks(policy: PolicyMode, rng: &mut StdRng) -> [usize; 5]
+	{
+		let mut picked = [0; 5];
+
+		for _i in 0..1000
+		{
+			picked[policy.choose_action_with_rng(&OUTPUTS, &LEGAL_ACTIONS, rng)] += 1;
+		}
+
+		picked
+	}
+
+	#[test]
+	fn sampling_policies_only_pick_legal_actions()
+	{
+		let mut rng = StdRng::seed_from_u64(0);
+
+		let policies = [PolicyMode::Temperature(1.0), PolicyMode::Temperature(100.0), PolicyMode::EpsilonGreedy(0.5), PolicyMode::EpsilonGreedy(1.0)];
+
+		for policy in policies.iter()
+		{
+			let picked = count_picks(*policy, &mut rng);
+
+			assert_eq!(picked[1] + picked[4], 0, "{} picked an illegal action", policy);
+
+			// Each of these policies is random enough to pick every legal action at least once.
+			assert!(picked[0] > 0 && picked[2] > 0 && picked[3] > 0, "{} never picked some legal actions: {:?}", policy, picked);
+		}
+
+		// A low temperature gets close to the argmax.
+		let picked = count_picks(PolicyMode::Temperature(0.1), &mut rng);
+
+		assert_eq!(picked[1] + picked[4], 0, "A low temperature picked an illegal action");
+		assert!(picked[3] > 900, "A low temperature picked the best action {} times out of 1000", picked[3]);
+	}
+
+	#[test]
+	fn policies_are_parsed_from_their_names()
+	{
+		assert_eq!(PolicyMode::from_name("argmax"), Some(PolicyMode::Argmax));
+		assert_eq!(PolicyMode::from_name(" temperature=0.5 "), Some(PolicyMode::Temperature(0.5)));
+		assert_eq!(PolicyMode::from_name("epsilon=0"), Some(PolicyMode::EpsilonGreedy(0.0)));
+		assert_eq!(PolicyMode::from_name("epsilon=1"), Some(PolicyMode::EpsilonGreedy(1.0)));
+
+		for policy in [PolicyMode::Argmax, PolicyMode::Temperature(2.5), PolicyMode::EpsilonGreedy(0.1)].iter()
+		{
+			assert_eq!(PolicyMode::from_name(&policy.to_string()), Some(*policy));
+		}
+
+		let rejected = ["", "greedy", "Argmax", "argmax=1", "temperature", "temperature=", "temperature=0", "temperature=-1",
+						"temperature=hot", "epsilon", "epsilon=1.5", "epsilon=-0.1"];
+
+		for name in rejected.iter()
+		{
+			assert_eq!(PolicyMode::from_name(name), None, "'{}' should not parse", name);
+		}
+	}
+}
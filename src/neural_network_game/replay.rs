@@ -0,0 +1,223 @@
+use super::game::*;
+use super::game::player::card::*;
+use super::model::*;
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+// The version of the replay format written by ReplayWriter.
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+// Something that happened during a game, in the order it happened.
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ReplayEvent
+{
+	// Every player was dealt a new hand at the start of a round.
+	Deal
+	{
+		round: u8,
+		hands: Vec<Vec<Card>>,
+	},
+
+	// A player picked a card from their hand. When chopsticks is true this was the extra pick from using chopsticks,
+	// and a card of None means the player chose not to use them.
+	Action
+	{
+		round: u8,
+		player: usize,
+		hand: Vec<Card>,
+		card: Card,
+		chopsticks: bool,
+
+		// What the network thought of each card it could pick, only recorded for network players.
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		weights: Option<Vec<CardWeight>>,
+	},
+
+	// Everyone passed their hand to the next player, these are the hands after passing.
+	PassHands
+	{
+		round: u8,
+		hands: Vec<Vec<Card>>,
+	},
+
+	// A round finished, these are the scores for the round not counting puddings.
+	RoundEnd
+	{
+		round: u8,
+		scores: Vec<i32>,
+	},
+}
+
+// Everything needed to study a game again or play it back step by step.
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct GameReplay
+{
+	pub format_version: u32,
+
+	// The seed the deck was shuffled with, if the game was seeded.
+	pub seed: Option<u64>,
+
+	// The name of the player in each seat.
+	pub players: Vec<String>,
+
+	pub events: Vec<ReplayEvent>,
+
+	// Filled in once the game is over.
+	pub pudding_scores: Vec<i32>,
+	pub final_scores: Vec<i32>,
+	pub winner: Option<usize>,
+}
+
+impl GameReplay
+{
+	// Starts a replay of a game which has been set up but not played yet.
+	pub fn new(game: &SushiGoGame, players: Vec<String>) -> GameReplay
+	{
+		let events = vec![ReplayEvent::Deal{round: game.get_current_round(), hands: game.get_hands()}];
+
+		GameReplay{format_version: REPLAY_FORMAT_VERSION, seed: game.get_seed(), players, events, pudding_scores: Vec::new(), final_scores: Vec::new(), winner: None}
+	}
+
+	// Records a step of the game, given the state of the game before the step and the game after it.
+	pub fn record_step(&mut self, before: &StepSnapshot, card: Card, result: StepResult, game: &SushiGoGame)
+	{
+		self.events.push(ReplayEvent::Action{round: before.round, player: before.player, hand: before.hand.clone(), card, chopsticks: before.chopsticks, weights: before.weights.clone()});
+
+		if result.is_round_over() || result.is_game_over()
+		{
+			self.events.push(ReplayEvent::RoundEnd{round: before.round, scores: game.get_round_scores(before.round)});
+
+			if result.is_game_over()
+			{
+				self.pudding_scores = game.get_pudding_scores();
+				self.final_scores = game.get_final_scores();
+				self.winner = Some(game.get_winner());
+			}
+			else
+			{
+				self.events.push(ReplayEvent::Deal{round: game.get_current_round(), hands: game.get_hands()});
+			}
+		}
+		// Hands are passed once the last player has acted and play goes back to the first.
+		else if game.get_current_player_id() < before.player
+		{
+			self.events.push(ReplayEvent::PassHands{round: before.round, hands: game.get_hands()});
+		}
+	}
+
+	pub fn is_finished(&self) -> bool
+	{
+		self.winner.is_some()
+	}
+}
+
+// The parts of the game state needed to record a step, taken before the step is played.
+pub struct StepSnapshot
+{
+	pub round: u8,
+	pub player: usize,
+	pub hand: Vec<Card>,
+	pub chopsticks: bool,
+	pub weights: Option<Vec<CardWeight>>,
+}
+
+impl StepSnapshot
+{
+	pub fn new(game: &SushiGoGame) -> StepSnapshot
+	{
+		StepSnapshot{round: game.get_current_round(), player: game.get_current_player_id(), hand: game.get_current_player_hand(), chopsticks: game.is_using_chopsticks(), weights: None}
+	}
+}
+
+#[derive(Debug)]
+pub enum ReplayFileError
+{
+	Io(io::Error),
+
+	// The line of the file which couldn't be read, counting from 1, and why.
+	Parse(usize, serde_json::Error),
+
+	UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ReplayFileError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			ReplayFileError::Io(e) => write!(f, "couldn't access the file: {}", e),
+			ReplayFileError::Parse(line, e) => write!(f, "line {} is not a valid replay: {}", line, e),
+			ReplayFileError::UnsupportedVersion(v) => write!(f, "replay format version {} is not supported, the newest supported version is {}", v, REPLAY_FORMAT_VERSION),
+		}
+	}
+}
+
+impl Error for ReplayFileError {}
+
+impl From<io::Error> for ReplayFileError
+{
+	fn from(e: io::Error) -> ReplayFileError
+	{
+		ReplayFileError::Io(e)
+	}
+}
+
+// Appends replays to a JSON lines file, one game per line.
+pub struct ReplayWriter
+{
+	writer: BufWriter<File>,
+}
+
+impl ReplayWriter
+{
+	pub fn new(file_name: &str) -> io::Result<ReplayWriter>
+	{
+		let file = OpenOptions::new().create(true).append(true).open(Path::new(file_name))?;
+
+		Ok(ReplayWriter{writer: BufWriter::new(file)})
+	}
+
+	pub fn write(&mut self, replay: &GameReplay) -> io::Result<()>
+	{
+		writeln!(self.writer, "{}", serde_json::to_string(replay)?)?;
+		self.writer.flush()
+	}
+}
+
+pub fn load_replays_from_file(file_name: &str) -> Result<Vec<GameReplay>, ReplayFileError>
+{
+	let reader = BufReader::new(File::open(Path::new(file_name))?);
+
+	let mut replays = Vec::new();
+
+	for (i, line) in reader.lines().enumerate()
+	{
+		let line = line?;
+
+		if line.trim().is_empty()
+		{
+			continue;
+		}
+
+		let replay: GameReplay = serde_json::from_str(&line).map_err(|e| ReplayFileError::Parse(i + 1, e))?;
+
+		if replay.format_version > REPLAY_FORMAT_VERSION
+		{
+			return Err(ReplayFileError::UnsupportedVersion(replay.format_version));
+		}
+
+		replays.push(replay);
+	}
+
+	Ok(replays)
+}
@@ -0,0 +1,161 @@
+use super::NeuralNetworkGame;
+use super::NeuralNetworkGamePlayerType;
+use super::game::player::card::*;
+use super::heuristic::*;
+use super::model::*;
+
+use std::time::Duration;
+
+// The turn delay can't be changed past these while watching.
+pub const MIN_TURN_DELAY: Duration = Duration::from_millis(50);
+pub const MAX_TURN_DELAY: Duration = Duration::from_secs(10);
+
+// How a game between bots is shown to someone watching it.
+#[derive(Copy,Clone,Debug)]
+pub struct SpectatorSettings
+{
+	// How long each turn is shown before the card is played.
+	pub turn_delay: Duration,
+
+	// Starts paused, so the first turn is only played once the spectator steps or resumes.
+	pub start_paused: bool,
+}
+
+impl Default for SpectatorSettings
+{
+	fn default() -> SpectatorSettings
+	{
+		SpectatorSettings{turn_delay: Duration::from_millis(500), start_paused: false}
+	}
+}
+
+// The pause and speed controls while a game is being watched.
+pub struct SpectatorControls
+{
+	pub turn_delay: Duration,
+	pub paused: bool,
+}
+
+impl SpectatorControls
+{
+	pub fn new(settings: SpectatorSettings) -> SpectatorControls
+	{
+		SpectatorControls{turn_delay: clamp_turn_delay(settings.turn_delay), paused: settings.start_paused}
+	}
+
+	pub fn toggle_pause(&mut self)
+	{
+		self.paused = !self.paused;
+	}
+
+	pub fn speed_up(&mut self)
+	{
+		self.turn_delay = clamp_turn_delay(self.turn_delay / 2);
+	}
+
+	pub fn slow_down(&mut self)
+	{
+		self.turn_delay = clamp_turn_delay(self.turn_delay * 2);
+	}
+
+	// A line saying how fast the game is going, for the bottom of the screen.
+	pub fn get_status(&self) -> String
+	{
+		let state = if self.paused { "Paused" } else { "Playing" };
+
+		format!("{}, {} ms per turn", state, self.turn_delay.as_millis())
+	}
+}
+
+fn clamp_turn_delay(delay: Duration) -> Duration
+{
+	std::cmp::max(MIN_TURN_DELAY, std::cmp::min(MAX_TURN_DELAY, delay))
+}
+
+// Why a bot picked the card it did.
+pub enum DecisionRationale
+{
+	// The network's output for every card it could pick.
+	Network(Vec<CardWeight>),
+
+	// How many points the heuristic thought each card in the hand was worth.
+	Heuristic(Vec<(Card, f32)>),
+
+	// Random players don't have a reason.
+	Random,
+
+	// Bots in other programs don't say why they picked their card, only their name is known.
+	External(String),
+}
+
+impl DecisionRationale
+{
+	// Describes the decision as lines of text, best card first, with the card which was picked marked.
+	pub fn describe(&self, picked: Card) -> Vec<String>
+	{
+		let marker = |card: Card| if card == picked { "*" } else { " " };
+
+		match self
+		{
+			DecisionRationale::Network(weights) =>
+			{
+				let mut weights = weights.clone();
+				weights.sort_by(|a, b| b.probability.partial_cmp(&a.probability).unwrap_or(std::cmp::Ordering::Equal));
+
+				weights.iter().map(|x| format!("{} {:<15} output {:>8.3} {:>6.1}%", marker(x.card), card_name(x.card), x.weight, x.probability * 100.0)).collect()
+			}
+			DecisionRationale::Heuristic(values) =>
+			{
+				let mut values = values.clone();
+				values.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+				values.iter().map(|x| format!("{} {:<15} worth about {:.1} points", marker(x.0), card_name(x.0), x.1)).collect()
+			}
+			DecisionRationale::Random => vec!["Picked at random.".to_string()],
+			DecisionRationale::External(name) => vec![format!("Picked by {}, which doesn't say why.", name)],
+		}
+	}
+}
+
+fn card_name(card: Card) -> String
+{
+	match card
+	{
+		Card::None => "Keep chopsticks".to_string(),
+		card => card.to_string(),
+	}
+}
+
+impl NeuralNetworkGame
+{
+	// Works out why the current player would pick what they do, for showing to spectators. Humans are treated like
+	// random players as there's nothing to explain.
+	pub fn get_decision_rationale(&self) -> DecisionRationale
+	{
+		let player = &self.players[self.game.get_current_player_id()];
+
+		match player.player_type
+		{
+			NeuralNetworkGamePlayerType::NeuralNetwork => DecisionRationale::Network(get_card_weights(&self.game, &player.nn)),
+			NeuralNetworkGamePlayerType::Heuristic =>
+			{
+				let hand = self.game.get_current_player_hand();
+				let chosen = self.game.get_current_player().get_chosen_cards();
+
+				let mut values: Vec<(Card, f32)> = Vec::new();
+
+				for x in hand.iter().filter(|x| **x != Card::None)
+				{
+					if !values.iter().any(|v| v.0 == *x)
+					{
+						values.push((*x, get_card_value(*x, &chosen, hand.len())));
+					}
+				}
+
+				DecisionRationale::Heuristic(values)
+			}
+			NeuralNetworkGamePlayerType::External => DecisionRationale::External(player.name.clone()),
+			_ => DecisionRationale::Random,
+		}
+	}
+}
@@ -0,0 +1,566 @@
+use super::{GameOutcome, NeuralNetworkGame};
+use super::game::*;
+use super::game::player::card::*;
+use super::spectator::*;
+use super::advisor::*;
+
+use crossterm::{cursor, execute, queue, terminal};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+
+use std::io;
+use std::io::{Stdout, Write};
+use std::time::{Duration, Instant};
+
+// Takes over the terminal while it exists and puts it back how it was when dropped, even if the game panics.
+struct TerminalScreen
+{
+	out: Stdout,
+
+	// The row the next line is written on.
+	row: u16,
+}
+
+impl TerminalScreen
+{
+	fn new() -> io::Result<TerminalScreen>
+	{
+		terminal::enable_raw_mode()?;
+
+		let mut out = io::stdout();
+		execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+		Ok(TerminalScreen{out, row: 0})
+	}
+
+	fn clear(&mut self) -> io::Result<()>
+	{
+		self.row = 0;
+		queue!(self.out, terminal::Clear(terminal::ClearType::All))
+	}
+
+	// Writes a line of text below the last one.
+	fn line(&mut self, text: &str) -> io::Result<()>
+	{
+		queue!(self.out, cursor::MoveTo(0, self.row), Print(text))?;
+		self.row += 1;
+
+		Ok(())
+	}
+
+	fn highlighted_line(&mut self, text: &str, color: Color) -> io::Result<()>
+	{
+		queue!(self.out, cursor::MoveTo(0, self.row), SetForegroundColor(color), SetAttribute(Attribute::Bold), Print(text), SetAttribute(Attribute::Reset), ResetColor)?;
+		self.row += 1;
+
+		Ok(())
+	}
+
+	// Shows everything written since the screen was cleared without waiting for a key.
+	fn flush(&mut self) -> io::Result<()>
+	{
+		self.out.flush()
+	}
+
+	// Shows everything written since the screen was cleared and waits for a key to be pressed. Ctrl+C is read as
+	// escape, as raw mode stops it from interrupting the program.
+	fn read_key(&mut self) -> io::Result<KeyCode>
+	{
+		self.out.flush()?;
+
+		loop
+		{
+			if let Some(key) = get_pressed_key(event::read()?)
+			{
+				return Ok(key);
+			}
+		}
+	}
+
+	// Like read_key, but gives up and returns None if no key is pressed before the timeout.
+	fn poll_key(&mut self, timeout: Duration) -> io::Result<Option<KeyCode>>
+	{
+		self.out.flush()?;
+
+		let deadline = Instant::now() + timeout;
+
+		loop
+		{
+			let now = Instant::now();
+
+			if now >= deadline || !event::poll(deadline - now)?
+			{
+				return Ok(None);
+			}
+
+			if let Some(key) = get_pressed_key(event::read()?)
+			{
+				return Ok(Some(key));
+			}
+		}
+	}
+}
+
+fn get_pressed_key(event: Event) -> Option<KeyCode>
+{
+	match event
+	{
+		Event::Key(key) if key.kind == KeyEventKind::Press =>
+		{
+			if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+			{
+				Some(KeyCode::Esc)
+			}
+			else
+			{
+				Some(key.code)
+			}
+		}
+		_ => None,
+	}
+}
+
+impl Drop for TerminalScreen
+{
+	fn drop(&mut self)
+	{
+		let _ = execute!(self.out, cursor::Show, terminal::LeaveAlternateScreen);
+		let _ = terminal::disable_raw_mode();
+	}
+}
+
+fn is_quit_key(key: KeyCode) -> bool
+{
+	key == KeyCode::Esc || key == KeyCode::Char('q')
+}
+
+// What the spectator asked for while waiting for the next turn.
+enum SpectatorAction
+{
+	NextTurn,
+	Quit,
+}
+
+impl NeuralNetworkGame
+{
+	// Plays the game with everything drawn in the terminal, so it works over SSH or without a display. Bots take their
+	// turns straight away and humans pick their cards with the keyboard.
+	pub fn play_in_terminal(&mut self) -> io::Result<GameOutcome>
+	{
+		// The screen is dropped at the end of the match, so the terminal is back to normal before anything else is printed.
+		let played = match TerminalScreen::new()
+		{
+			Ok(mut screen) => self.play_on_screen(&mut screen),
+			Err(e) => Err(e),
+		};
+
+		if !played?
+		{
+			return Ok(GameOutcome::Abandoned);
+		}
+
+		self.game.print_pudding_scores();
+		self.game.print_final_scores();
+
+		Ok(GameOutcome::Finished)
+	}
+
+	// Shows a game between bots in the terminal, pausing on each turn to show what the bot picked and why.
+	pub fn spectate_in_terminal(&mut self, settings: SpectatorSettings)
+	{
+		let watched = match TerminalScreen::new()
+		{
+			Ok(mut screen) => self.spectate_on_screen(&mut screen, settings),
+			Err(e) => Err(e),
+		};
+
+		match watched
+		{
+			Ok(true) =>
+			{
+				self.game.print_pudding_scores();
+				self.game.print_final_scores();
+			}
+			// The game still has to finish so its results can be counted.
+			Ok(false) => self.finish_game(),
+			Err(e) =>
+			{
+				println!("Couldn't draw the game in the terminal: {}", e);
+				self.finish_game();
+			}
+		}
+	}
+
+	// Returns false if the spectator stopped watching before the game was over.
+	fn spectate_on_screen(&mut self, screen: &mut TerminalScreen, settings: SpectatorSettings) -> io::Result<bool>
+	{
+		let mut controls = SpectatorControls::new(settings);
+		let mut result = StepResult::NoResult;
+
+		while !result.is_game_over()
+		{
+			// The decision is made before it is shown, so the spectator sees the card the bot is about to play.
+			let rationale = self.get_decision_rationale();
+			let card = self.take_turn();
+
+			let action = wait_for_spectator(screen, &mut controls, 1, |screen, controls| self.draw_spectator_table(screen, card, &rationale, controls))?;
+
+			if let SpectatorAction::Quit = action
+			{
+				return Ok(false);
+			}
+
+			let round = self.game.get_current_round();
+
+			result = self.play_card(card);
+
+			if result.is_round_over() || result.is_game_over()
+			{
+				// The results stay up for a few turns' worth of time.
+				let action = wait_for_spectator(screen, &mut controls, 5, |screen, controls|
+				{
+					self.draw_round_results(screen, round)?;
+					screen.line(&controls.get_status())
+				})?;
+
+				if let SpectatorAction::Quit = action
+				{
+					return Ok(false);
+				}
+			}
+		}
+
+		Ok(true)
+	}
+
+	// Draws every player's hand and chosen cards, what the current player is about to pick and why.
+	fn draw_spectator_table(&self, screen: &mut TerminalScreen, card: Card, rationale: &DecisionRationale, controls: &SpectatorControls) -> io::Result<()>
+	{
+		screen.clear()?;
+
+		let current = self.game.get_current_player_id();
+
+		screen.highlighted_line(&format!("Sushi Go! Round {} of {}", self.game.get_current_round() + 1, self.game.get_number_of_rounds()), Color::Cyan)?;
+		screen.line("")?;
+		screen.line(&format!("    {:<26}{:>6}{:>6}{:>9}   {}", "Player", "Score", "Maki", "Pudding", "Chosen this round"))?;
+
+		let scores = self.game.get_running_scores();
+		let puddings = self.game.get_pudding_counts();
+
+		for i in 0..self.game.get_num_players()
+		{
+			let chosen = self.game.get_player_chosen(i);
+
+			let maki: usize = chosen.iter().map(|x| get_maki_roll_value(*x)).sum();
+			let pudding = puddings[i] as usize + chosen.iter().filter(|x| **x == Card::Pudding).count();
+
+			let mut name = self.players[i].name.clone();
+			name.truncate(26);
+
+			let text = format!("{} {} {:<26}{:>6}{:>6}{:>9}   {}", if i == current { ">" } else { " " }, i, name, scores[i], maki, pudding, format_cards(&chosen));
+
+			if i == current
+			{
+				screen.highlighted_line(&text, Color::Cyan)?;
+			}
+			else
+			{
+				screen.line(&text)?;
+			}
+
+			screen.line(&format!("      Hand: {}", format_cards(&self.game.get_player_hand(i))))?;
+		}
+
+		screen.line("")?;
+
+		let action = match card
+		{
+			Card::None => "keeps their chopsticks".to_string(),
+			card if self.game.is_using_chopsticks() => format!("uses their chopsticks on {}", card),
+			card => format!("picks {}", card),
+		};
+
+		screen.highlighted_line(&format!("Player {} ({}) {}", current, self.players[current].name, action), Color::Green)?;
+
+		for x in rationale.describe(card)
+		{
+			screen.line(&format!("  {}", x))?;
+		}
+
+		screen.line("")?;
+		screen.line(&controls.get_status())?;
+		screen.line("Space to pause, n to step while paused, + and - to change the speed, q to stop watching.")
+	}
+
+	// Returns false if a human quit before the game was over.
+	fn play_on_screen(&mut self, screen: &mut TerminalScreen) -> io::Result<bool>
+	{
+		let humans = self.players.iter().filter(|x| x.player_type.is_human()).count();
+		let mut last_human = None;
+
+		let mut result = StepResult::NoResult;
+
+		while !result.is_game_over()
+		{
+			let current = self.game.get_current_player_id();
+
+			let card = if self.players[current].player_type.is_human()
+			{
+				// With more than one human sharing the keyboard, hide the table until the next one is ready so they don't see each other's hands.
+				if humans > 1 && last_human != Some(current)
+				{
+					screen.clear()?;
+					screen.highlighted_line(&format!("Pass the keyboard to player {} ({}) and press any key.", current, self.players[current].name), Color::Yellow)?;
+
+					if is_quit_key(screen.read_key()?)
+					{
+						return Ok(false);
+					}
+				}
+
+				last_human = Some(current);
+
+				match self.pick_card_on_screen(screen, current)?
+				{
+					Some(card) => card,
+					None => return Ok(false),
+				}
+			}
+			else
+			{
+				self.take_turn()
+			};
+
+			let round = self.game.get_current_round();
+
+			result = self.play_card(card);
+
+			if result.is_round_over() || result.is_game_over()
+			{
+				self.draw_round_results(screen, round)?;
+				screen.read_key()?;
+			}
+		}
+
+		Ok(true)
+	}
+
+	// Lets the player move through their hand with the keyboard until they pick a card. Returns None if they quit.
+	fn pick_card_on_screen(&self, screen: &mut TerminalScreen, player: usize) -> io::Result<Option<Card>>
+	{
+		let hand = self.game.get_player_hand(player);
+		let mut selected = 0;
+
+		// Only asked for once a turn, rollouts can take a moment.
+		let mut advice: Option<Vec<CardAdvice>> = None;
+
+		loop
+		{
+			self.draw_table(screen, player, &hand, selected, advice.as_deref())?;
+
+			match screen.read_key()?
+			{
+				KeyCode::Char('h') if self.advisor.is_some() && advice.is_none() =>
+				{
+					screen.highlighted_line("Thinking...", Color::Yellow)?;
+					screen.flush()?;
+
+					advice = self.get_advice();
+				}
+				KeyCode::Up | KeyCode::Left => selected = if selected == 0 { hand.len() - 1 } else { selected - 1 },
+				KeyCode::Down | KeyCode::Right | KeyCode::Tab => selected = (selected + 1) % hand.len(),
+				KeyCode::Char(c) if c.is_ascii_digit() =>
+				{
+					let slot = c.to_digit(10).unwrap() as usize;
+
+					if slot >= 1 && slot <= hand.len()
+					{
+						selected = slot - 1;
+					}
+				}
+				KeyCode::Enter | KeyCode::Char(' ') => return Ok(Some(hand[selected])),
+				key if is_quit_key(key) => return Ok(None),
+				_ => {},
+			}
+		}
+	}
+
+	// Draws the table from the point of view of a human player, with the card they have selected highlighted and the
+	// advisor's hint if they asked for one.
+	fn draw_table(&self, screen: &mut TerminalScreen, viewer: usize, hand: &[Card], selected: usize, advice: Option<&[CardAdvice]>) -> io::Result<()>
+	{
+		screen.clear()?;
+
+		screen.highlighted_line(&format!("Sushi Go! Round {} of {}", self.game.get_current_round() + 1, self.game.get_number_of_rounds()), Color::Cyan)?;
+		screen.line("")?;
+		screen.line(&format!("    {:<26}{:>6}{:>6}{:>9}   {}", "Player", "Score", "Maki", "Pudding", "Chosen this round"))?;
+
+		let scores = self.game.get_running_scores();
+		let puddings = self.game.get_pudding_counts();
+
+		// Everyone reveals the card they picked at the same time, so only show as many of the other players' cards as the viewer has picked.
+		let revealed = self.game.get_player_chosen(viewer).len();
+
+		for i in 0..self.game.get_num_players()
+		{
+			let chosen: Vec<Card> = self.game.get_player_chosen(i).into_iter().take(revealed).collect();
+
+			let maki: usize = chosen.iter().map(|x| get_maki_roll_value(*x)).sum();
+			let pudding = puddings[i] as usize + chosen.iter().filter(|x| **x == Card::Pudding).count();
+
+			let mut name = self.players[i].name.clone();
+			name.truncate(26);
+
+			let text = format!("{} {} {:<26}{:>6}{:>6}{:>9}   {}", if i == viewer { ">" } else { " " }, i, name, scores[i], maki, pudding, format_cards(&chosen));
+
+			if i == viewer
+			{
+				screen.highlighted_line(&text, Color::Cyan)?;
+			}
+			else
+			{
+				screen.line(&text)?;
+			}
+		}
+
+		screen.line("")?;
+
+		if self.game.is_using_chopsticks()
+		{
+			screen.highlighted_line("You can use your chopsticks to pick a second card, or keep them for later.", Color::Yellow)?;
+		}
+
+		screen.line(&format!("Your hand, {}:", self.players[viewer].name))?;
+
+		for (i, card) in hand.iter().enumerate()
+		{
+			let card_name = match card
+			{
+				Card::None => "Keep my chopsticks".to_string(),
+				card => card.to_string(),
+			};
+
+			if i == selected
+			{
+				screen.highlighted_line(&format!("> {}. {}", i + 1, card_name), Color::Green)?;
+			}
+			else
+			{
+				screen.line(&format!("  {}. {}", i + 1, card_name))?;
+			}
+		}
+
+		if let Some(advice) = advice
+		{
+			screen.line("")?;
+			screen.highlighted_line("The advisor suggests, best first:", Color::Yellow)?;
+
+			for x in advice
+			{
+				screen.line(&format!("  {}", x.describe()))?;
+			}
+		}
+
+		screen.line("")?;
+
+		if self.advisor.is_some()
+		{
+			screen.line("Arrow keys or 1-9 to choose a card, Enter to pick it, h for a hint, q to quit.")
+		}
+		else
+		{
+			screen.line("Arrow keys or 1-9 to choose a card, Enter to pick it, q to quit.")
+		}
+	}
+
+	// Shows the scores at the end of a round, and the final result if it was the last round.
+	fn draw_round_results(&self, screen: &mut TerminalScreen, round: u8) -> io::Result<()>
+	{
+		screen.clear()?;
+
+		screen.highlighted_line(&format!("Round {} is over", round + 1), Color::Cyan)?;
+		screen.line("")?;
+		screen.line(&format!("    {:<26}{:>7}{:>7}", "Player", "Round", "Total"))?;
+
+		let round_scores = self.game.get_round_scores(round);
+		let scores = self.game.get_running_scores();
+
+		for i in 0..self.game.get_num_players()
+		{
+			let mut name = self.players[i].name.clone();
+			name.truncate(26);
+
+			screen.line(&format!("  {} {:<26}{:>7}{:>7}", i, name, round_scores[i], scores[i]))?;
+		}
+
+		screen.line("")?;
+
+		if self.game.get_current_round() < self.game.get_number_of_rounds()
+		{
+			return screen.line("Press any key to start the next round.");
+		}
+
+		let pudding_scores = self.game.get_pudding_scores();
+		let final_scores = self.game.get_final_scores();
+
+		screen.line(&format!("    {:<26}{:>7}{:>7}", "Player", "Pudding", "Final"))?;
+
+		for i in 0..self.game.get_num_players()
+		{
+			let mut name = self.players[i].name.clone();
+			name.truncate(26);
+
+			screen.line(&format!("  {} {:<26}{:>7}{:>7}", i, name, pudding_scores[i], final_scores[i]))?;
+		}
+
+		let winner = self.game.get_winner();
+
+		screen.line("")?;
+		screen.highlighted_line(&format!("Player {} ({}) wins!", winner, self.players[winner].name), Color::Green)?;
+		screen.line("Press any key to finish.")
+	}
+}
+
+// Waits for as many turn delays as asked for, or for the spectator to step if they've paused, redrawing the screen
+// whenever the controls change.
+fn wait_for_spectator<F>(screen: &mut TerminalScreen, controls: &mut SpectatorControls, turns: u32, draw: F) -> io::Result<SpectatorAction>
+	where F: Fn(&mut TerminalScreen, &SpectatorControls) -> io::Result<()>
+{
+	draw(screen, controls)?;
+
+	let mut deadline = Instant::now() + controls.turn_delay * turns;
+
+	loop
+	{
+		let key = if controls.paused
+		{
+			Some(screen.read_key()?)
+		}
+		else
+		{
+			let now = Instant::now();
+
+			if now >= deadline
+			{
+				return Ok(SpectatorAction::NextTurn);
+			}
+
+			screen.poll_key(deadline - now)?
+		};
+
+		match key
+		{
+			None => return Ok(SpectatorAction::NextTurn),
+			Some(KeyCode::Char(' ')) | Some(KeyCode::Char('p')) => controls.toggle_pause(),
+			Some(KeyCode::Char('n')) | Some(KeyCode::Right) | Some(KeyCode::Enter) if controls.paused => return Ok(SpectatorAction::NextTurn),
+			Some(KeyCode::Char('+')) | Some(KeyCode::Char('=')) | Some(KeyCode::Up) => controls.speed_up(),
+			Some(KeyCode::Char('-')) | Some(KeyCode::Down) => controls.slow_down(),
+			Some(key) if is_quit_key(key) => return Ok(SpectatorAction::Quit),
+			_ => continue,
+		}
+
+		deadline = Instant::now() + controls.turn_delay * turns;
+
+		draw(screen, controls)?;
+	}
+}
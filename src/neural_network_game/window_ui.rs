@@ -0,0 +1,594 @@
+use super::{GameOutcome, NeuralNetworkGame};
+use super::game::*;
+use super::game::player::card::*;
+use super::spectator::*;
+use super::advisor::*;
+
+use radiant_rs::{Display, Renderer, Layer, Sprite, Font, Color, Input, InputId};
+
+use enum_map::EnumMap;
+
+use std::io;
+use std::time::Instant;
+
+//---- Layout, in pixels. The layer is the same size as the window so mouse positions can be used as they are.
+const WINDOW_WIDTH: f32 = 1280.0;
+const WINDOW_HEIGHT: f32 = 720.0;
+
+const CARD_SIZE: f32 = 64.0;
+const CARD_GAP: f32 = 8.0;
+
+const PANEL_TOP: f32 = 50.0;
+const PANEL_HEIGHT: f32 = 115.0;
+const PANEL_TEXT_X: f32 = 20.0;
+const CHOSEN_CARDS_X: f32 = 320.0;
+
+const HAND_TOP: f32 = 560.0;
+const HAND_X: f32 = 20.0;
+
+// Where spectators are shown why a bot picked its card and humans are shown hints, to the right of the chosen cards.
+const RATIONALE_X: f32 = 900.0;
+const LINE_HEIGHT: f32 = 24.0;
+
+// The button humans click to ask the advisor for a hint.
+const HINT_BUTTON_Y: f32 = 680.0;
+const HINT_BUTTON_WIDTH: f32 = 160.0;
+
+// How far the card under the mouse is raised.
+const HOVER_RAISE: f32 = 10.0;
+
+// Fonts are looked up by family, the first one installed is used.
+const FONT_FAMILIES: [&str; 4] = ["Arial", "DejaVu Sans", "Liberation Sans", "Helvetica"];
+
+const DIM: Color = Color(0.45, 0.45, 0.45, 1.0);
+const HIGHLIGHT: Color = Color(1.0, 1.0, 0.55, 1.0);
+const TITLE: Color = Color(0.5, 0.85, 1.0, 1.0);
+
+// Something on the screen which can be clicked.
+#[derive(Copy,Clone,PartialEq)]
+enum Clickable
+{
+	// A card in the hand of the player whose turn it is, by its slot.
+	HandCard(usize),
+
+	// Declines to use chopsticks, which picks the none card.
+	KeepChopsticks,
+
+	// Asks the advisor which card to pick.
+	Hint,
+}
+
+// Everything loaded for drawing.
+struct WindowAssets
+{
+	sprites: EnumMap<Card, Sprite>,
+	font: Font,
+	title_font: Font,
+}
+
+impl NeuralNetworkGame
+{
+	// Plays the game in a window. Humans pick their cards by clicking on them and the scores are shown in the window.
+	pub fn play_with_display(&mut self) -> io::Result<GameOutcome>
+	{
+		let display = Display::builder().dimensions((WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32)).vsync().title("Sushi Go").build().unwrap();
+
+		let renderer = Renderer::new(&display).unwrap();
+
+		let assets = match load_assets(&renderer)
+		{
+			Some(assets) => assets,
+			None => return Err(io::Error::new(io::ErrorKind::NotFound, "couldn't find a font to draw the game with, try playing with --display terminal")),
+		};
+
+		let input = Input::new(&display);
+		let layer = Layer::new((WINDOW_WIDTH, WINDOW_HEIGHT));
+
+		let mut result = StepResult::NoResult;
+
+		// Which human the table was last shown to, so bots' turns don't show their hands.
+		let mut viewer = self.players.iter().position(|x| x.player_type.is_human()).unwrap_or(0);
+
+		// With more than one human sharing the window, the table is hidden until the next one is ready so they don't
+		// see each other's hands.
+		let humans = self.players.iter().filter(|x| x.player_type.is_human()).count();
+		let mut ready_human = None;
+
+		// The round whose results are being shown, play carries on once they are clicked away.
+		let mut summary: Option<u8> = None;
+
+		// The advisor's hint for the current turn, once it has been asked for.
+		let mut advice: Option<Vec<CardAdvice>> = None;
+
+		while !display.poll_events().was_closed()
+		{
+			let (mouse_x, mouse_y) = input.mouse();
+			let mouse = (mouse_x as f32, mouse_y as f32);
+			let clicked = input.pressed(InputId::Mouse1, false);
+
+			layer.clear();
+
+			if let Some(round) = summary
+			{
+				self.draw_window_round_summary(&layer, &assets, round);
+
+				if clicked
+				{
+					if result.is_game_over()
+					{
+						return Ok(GameOutcome::Finished);
+					}
+
+					summary = None;
+				}
+			}
+			else
+			{
+				let current = self.game.get_current_player_id();
+				let round = self.game.get_current_round();
+
+				if self.players[current].player_type.is_human() && humans > 1 && ready_human != Some(current)
+				{
+					self.draw_window_hand_off(&layer, &assets, current);
+
+					if clicked
+					{
+						ready_human = Some(current);
+					}
+				}
+				else if self.players[current].player_type.is_human()
+				{
+					viewer = current;
+
+					let hovered = self.get_clickable_at(mouse);
+
+					self.draw_window_table(&layer, &assets, viewer, hovered, advice.as_deref());
+
+					if clicked
+					{
+						let hand = self.game.get_current_player_hand();
+
+						let card = match hovered
+						{
+							Some(Clickable::HandCard(slot)) => Some(hand[slot]),
+							Some(Clickable::KeepChopsticks) => Some(Card::None),
+							Some(Clickable::Hint) =>
+							{
+								advice = self.get_advice();
+								None
+							}
+							None => None,
+						};
+
+						if let Some(card) = card
+						{
+							result = self.play_card(card);
+							advice = None;
+						}
+					}
+				}
+				else
+				{
+					self.draw_window_table(&layer, &assets, viewer, None, None);
+
+					result = self.step_game();
+				}
+
+				if result.is_round_over() || result.is_game_over()
+				{
+					summary = Some(round);
+				}
+			}
+
+			display.clear_frame(Color::BLACK);
+			renderer.draw_layer(&layer, 0);
+
+			display.swap_frame();
+		}
+
+		//---- Window closed
+		if result.is_game_over()
+		{
+			Ok(GameOutcome::Finished)
+		}
+		else
+		{
+			Ok(GameOutcome::Abandoned)
+		}
+	}
+
+	// Shows a game between bots in a window, pausing on each turn to show what the bot picked and why. Space pauses,
+	// the right arrow steps while paused and the up and down arrows change the speed.
+	pub fn spectate_in_window(&mut self, settings: SpectatorSettings)
+	{
+		let display = Display::builder().dimensions((WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32)).vsync().title("Sushi Go").build().unwrap();
+
+		let renderer = Renderer::new(&display).unwrap();
+
+		let assets = match load_assets(&renderer)
+		{
+			Some(assets) => assets,
+			None =>
+			{
+				println!("Couldn't find a font to draw the game with, try watching with --display terminal.");
+				self.finish_game();
+				return;
+			}
+		};
+
+		let input = Input::new(&display);
+		let layer = Layer::new((WINDOW_WIDTH, WINDOW_HEIGHT));
+
+		let mut controls = SpectatorControls::new(settings);
+		let mut result = StepResult::NoResult;
+
+		// The card the current player is about to play and why, worked out once per turn.
+		let mut decision: Option<(Card, DecisionRationale)> = None;
+
+		// The round whose results are being shown.
+		let mut summary: Option<u8> = None;
+
+		let mut shown_since = Instant::now();
+
+		while !display.poll_events().was_closed()
+		{
+			if input.pressed(InputId::Space, false)
+			{
+				controls.toggle_pause();
+				shown_since = Instant::now();
+			}
+
+			if input.pressed(InputId::Up, false)
+			{
+				controls.speed_up();
+			}
+
+			if input.pressed(InputId::Down, false)
+			{
+				controls.slow_down();
+			}
+
+			let clicked = input.pressed(InputId::Mouse1, false);
+
+			// Stepping moves on straight away, otherwise wait for the delay unless paused.
+			let stepped = controls.paused && (input.pressed(InputId::Right, false) || clicked);
+			let turns_shown = if summary.is_some() { 5 } else { 1 };
+			let next = stepped || (!controls.paused && shown_since.elapsed() >= controls.turn_delay * turns_shown);
+
+			layer.clear();
+
+			if let Some(round) = summary
+			{
+				self.draw_window_round_summary(&layer, &assets, round);
+
+				// The results can be clicked away like when playing.
+				if next || clicked
+				{
+					if result.is_game_over()
+					{
+						return;
+					}
+
+					summary = None;
+					shown_since = Instant::now();
+				}
+			}
+			else
+			{
+				if decision.is_none()
+				{
+					let rationale = self.get_decision_rationale();
+					decision = Some((self.take_turn(), rationale));
+				}
+
+				if let Some((card, rationale)) = &decision
+				{
+					self.draw_window_spectator_table(&layer, &assets, *card, rationale);
+				}
+
+				if next
+				{
+					let round = self.game.get_current_round();
+
+					if let Some((card, _)) = decision.take()
+					{
+						result = self.play_card(card);
+					}
+
+					if result.is_round_over() || result.is_game_over()
+					{
+						summary = Some(round);
+					}
+
+					shown_since = Instant::now();
+				}
+			}
+
+			assets.font.write(&layer, &format!("{}. Space to pause, right arrow to step, up and down to change the speed.", controls.get_status()), (PANEL_TEXT_X, WINDOW_HEIGHT - 30.0), DIM);
+
+			display.clear_frame(Color::BLACK);
+			renderer.draw_layer(&layer, 0);
+
+			display.swap_frame();
+		}
+
+		//---- Window closed, the game still has to finish so its results can be counted.
+		if !result.is_game_over()
+		{
+			self.finish_game();
+		}
+	}
+
+	// Returns what is under the mouse, if anything can be clicked there.
+	fn get_clickable_at(&self, mouse: (f32, f32)) -> Option<Clickable>
+	{
+		let hand = self.game.get_current_player_hand();
+
+		for (slot, card) in hand.iter().enumerate()
+		{
+			let (x, y) = get_hand_position(slot);
+
+			if is_inside(mouse, x, y - HOVER_RAISE, CARD_SIZE, CARD_SIZE + HOVER_RAISE)
+			{
+				return Some(if *card == Card::None { Clickable::KeepChopsticks } else { Clickable::HandCard(slot) });
+			}
+		}
+
+		if self.advisor.is_some() && is_inside(mouse, HAND_X, HINT_BUTTON_Y, HINT_BUTTON_WIDTH, LINE_HEIGHT)
+		{
+			return Some(Clickable::Hint);
+		}
+
+		None
+	}
+
+	// Draws every player's panel and the hand of the viewer. Only the viewer's hand is shown, and the other players'
+	// chosen cards are only shown once the viewer has picked theirs for the turn. The advisor's hint is shown if the
+	// viewer asked for one.
+	fn draw_window_table(&self, layer: &Layer, assets: &WindowAssets, viewer: usize, hovered: Option<Clickable>, advice: Option<&[CardAdvice]>)
+	{
+		let current = self.game.get_current_player_id();
+
+		assets.title_font.write(layer, &format!("Sushi Go!  Round {} of {}", self.game.get_current_round() + 1, self.game.get_number_of_rounds()), (PANEL_TEXT_X, 10.0), TITLE);
+
+		let scores = self.game.get_running_scores();
+		let puddings = self.game.get_pudding_counts();
+		let revealed = self.game.get_player_chosen(viewer).len();
+
+		for i in 0..self.game.get_num_players()
+		{
+			let top = PANEL_TOP + PANEL_HEIGHT * i as f32;
+			let chosen: Vec<Card> = self.game.get_player_chosen(i).into_iter().take(revealed).collect();
+
+			let maki: usize = chosen.iter().map(|x| get_maki_roll_value(*x)).sum();
+			let pudding = puddings[i] as usize + chosen.iter().filter(|x| **x == Card::Pudding).count();
+
+			let name_color = if i == current { HIGHLIGHT } else { Color::WHITE };
+			let you = if i == viewer { " (you)" } else { "" };
+
+			assets.font.write(layer, &format!("Player {}: {}{}", i, self.players[i].name, you), (PANEL_TEXT_X, top), name_color);
+			assets.font.write(layer, &format!("Score {}   Maki {}   Pudding {}", scores[i], maki, pudding), (PANEL_TEXT_X, top + 28.0), Color::WHITE);
+
+			for (j, card) in chosen.iter().enumerate()
+			{
+				assets.sprites[*card].draw(layer, 0, (CHOSEN_CARDS_X + (CARD_SIZE + CARD_GAP) * j as f32, top), Color::WHITE);
+			}
+		}
+
+		let hand = self.game.get_player_hand(viewer);
+		let is_viewers_turn = current == viewer;
+
+		let prompt = if !is_viewers_turn
+		{
+			"Waiting for the other players...".to_string()
+		}
+		else if self.game.is_using_chopsticks()
+		{
+			"Click a card to use your chopsticks on it, or keep them for later.".to_string()
+		}
+		else
+		{
+			"Click a card to pick it.".to_string()
+		};
+
+		assets.font.write(layer, &format!("Your hand, {}. {}", self.players[viewer].name, prompt), (HAND_X, HAND_TOP - 40.0), Color::WHITE);
+
+		for (slot, card) in hand.iter().enumerate()
+		{
+			let (x, y) = get_hand_position(slot);
+
+			if *card == Card::None
+			{
+				// The none card is the option to keep the chopsticks, shown as a button.
+				let color = if hovered == Some(Clickable::KeepChopsticks) { HIGHLIGHT } else { Color::WHITE };
+
+				assets.sprites[Card::Chopsticks].draw(layer, 0, (x, y), color);
+				assets.font.write(layer, "Keep chopsticks", (x, y + CARD_SIZE + 6.0), color);
+
+				continue;
+			}
+
+			// Cards can only be picked on the viewer's turn.
+			if !is_viewers_turn
+			{
+				assets.sprites[*card].draw(layer, 0, (x, y), DIM);
+			}
+			else if hovered == Some(Clickable::HandCard(slot))
+			{
+				assets.sprites[*card].draw(layer, 0, (x, y - HOVER_RAISE), HIGHLIGHT);
+				assets.font.write(layer, &card.to_string(), (x, y + CARD_SIZE + 6.0), HIGHLIGHT);
+			}
+			else
+			{
+				assets.sprites[*card].draw(layer, 0, (x, y), Color::WHITE);
+			}
+		}
+
+		if self.advisor.is_some() && is_viewers_turn
+		{
+			let color = if hovered == Some(Clickable::Hint) { HIGHLIGHT } else { Color::WHITE };
+
+			assets.font.write(layer, "Ask for a hint", (HAND_X, HINT_BUTTON_Y), color);
+		}
+
+		if let Some(advice) = advice
+		{
+			assets.font.write(layer, "The advisor suggests:", (RATIONALE_X, PANEL_TOP), TITLE);
+
+			// Two lines for each card so they fit beside the chosen cards.
+			for (i, x) in advice.iter().enumerate()
+			{
+				let y = PANEL_TOP + LINE_HEIGHT * (i * 2 + 1) as f32;
+
+				assets.font.write(layer, &x.get_card_name(), (RATIONALE_X, y), if i == 0 { HIGHLIGHT } else { Color::WHITE });
+				assets.font.write(layer, &x.get_summary(), (RATIONALE_X + 20.0, y + LINE_HEIGHT), Color::WHITE);
+			}
+		}
+	}
+
+	// Shown instead of the table when the window has to be passed to another human.
+	fn draw_window_hand_off(&self, layer: &Layer, assets: &WindowAssets, player: usize)
+	{
+		assets.title_font.write(layer, &format!("Pass the mouse to player {} ({})", player, self.players[player].name), (PANEL_TEXT_X, PANEL_TOP), TITLE);
+		assets.font.write(layer, "Click when you are ready to see your hand.", (PANEL_TEXT_X, PANEL_TOP + 50.0), Color::WHITE);
+	}
+
+	// Draws every player's panel with all their chosen cards, the current player's hand with the card they are about to
+	// pick highlighted, and why they picked it.
+	fn draw_window_spectator_table(&self, layer: &Layer, assets: &WindowAssets, card: Card, rationale: &DecisionRationale)
+	{
+		let current = self.game.get_current_player_id();
+
+		assets.title_font.write(layer, &format!("Sushi Go!  Round {} of {}", self.game.get_current_round() + 1, self.game.get_number_of_rounds()), (PANEL_TEXT_X, 10.0), TITLE);
+
+		let scores = self.game.get_running_scores();
+		let puddings = self.game.get_pudding_counts();
+
+		for i in 0..self.game.get_num_players()
+		{
+			let top = PANEL_TOP + PANEL_HEIGHT * i as f32;
+			let chosen = self.game.get_player_chosen(i);
+
+			let maki: usize = chosen.iter().map(|x| get_maki_roll_value(*x)).sum();
+			let pudding = puddings[i] as usize + chosen.iter().filter(|x| **x == Card::Pudding).count();
+
+			let name_color = if i == current { HIGHLIGHT } else { Color::WHITE };
+
+			assets.font.write(layer, &format!("Player {}: {}", i, self.players[i].name), (PANEL_TEXT_X, top), name_color);
+			assets.font.write(layer, &format!("Score {}   Maki {}   Pudding {}", scores[i], maki, pudding), (PANEL_TEXT_X, top + 28.0), Color::WHITE);
+
+			for (j, x) in chosen.iter().enumerate()
+			{
+				assets.sprites[*x].draw(layer, 0, (CHOSEN_CARDS_X + (CARD_SIZE + CARD_GAP) * j as f32, top), Color::WHITE);
+			}
+		}
+
+		let action = match card
+		{
+			Card::None => "keeps their chopsticks".to_string(),
+			card if self.game.is_using_chopsticks() => format!("uses their chopsticks on {}", card),
+			card => format!("picks {}", card),
+		};
+
+		assets.font.write(layer, &format!("{} {}", self.players[current].name, action), (HAND_X, HAND_TOP - 40.0), HIGHLIGHT);
+
+		let hand = self.game.get_current_player_hand();
+		let picked_slot = hand.iter().position(|x| *x == card);
+
+		for (slot, x) in hand.iter().enumerate()
+		{
+			let (x_position, y_position) = get_hand_position(slot);
+
+			if Some(slot) == picked_slot
+			{
+				assets.sprites[*x].draw(layer, 0, (x_position, y_position - HOVER_RAISE), HIGHLIGHT);
+			}
+			else
+			{
+				assets.sprites[*x].draw(layer, 0, (x_position, y_position), DIM);
+			}
+		}
+
+		assets.font.write(layer, "Why:", (RATIONALE_X, PANEL_TOP), TITLE);
+
+		for (i, line) in rationale.describe(card).iter().enumerate()
+		{
+			assets.font.write(layer, line, (RATIONALE_X, PANEL_TOP + LINE_HEIGHT * (i + 1) as f32), Color::WHITE);
+		}
+	}
+
+	// Shows the scores at the end of a round, and the final result if it was the last round.
+	fn draw_window_round_summary(&self, layer: &Layer, assets: &WindowAssets, round: u8)
+	{
+		assets.title_font.write(layer, &format!("Round {} is over", round + 1), (PANEL_TEXT_X, 10.0), TITLE);
+
+		let round_scores = self.game.get_round_scores(round);
+		let scores = self.game.get_running_scores();
+		let is_game_over = self.game.get_current_round() >= self.game.get_number_of_rounds();
+
+		for i in 0..self.game.get_num_players()
+		{
+			let top = PANEL_TOP + 40.0 * i as f32;
+
+			let mut line = format!("Player {}: {}    scored {} this round, {} in total", i, self.players[i].name, round_scores[i], scores[i]);
+
+			if is_game_over
+			{
+				line = format!("{}, {} from puddings, {} final", line, self.game.get_pudding_scores()[i], self.game.get_final_scores()[i]);
+			}
+
+			assets.font.write(layer, &line, (PANEL_TEXT_X, top), Color::WHITE);
+		}
+
+		let bottom = PANEL_TOP + 40.0 * self.game.get_num_players() as f32 + 20.0;
+
+		if is_game_over
+		{
+			let winner = self.game.get_winner();
+
+			assets.title_font.write(layer, &format!("Player {} ({}) wins!", winner, self.players[winner].name), (PANEL_TEXT_X, bottom), HIGHLIGHT);
+			assets.font.write(layer, "Click to finish.", (PANEL_TEXT_X, bottom + 50.0), Color::WHITE);
+		}
+		else
+		{
+			assets.font.write(layer, "Click to start the next round.", (PANEL_TEXT_X, bottom), Color::WHITE);
+		}
+	}
+}
+
+fn get_hand_position(slot: usize) -> (f32, f32)
+{
+	(HAND_X + (CARD_SIZE + CARD_GAP * 2.0) * slot as f32, HAND_TOP)
+}
+
+fn is_inside(point: (f32, f32), x: f32, y: f32, width: f32, height: f32) -> bool
+{
+	point.0 >= x && point.0 < x + width && point.1 >= y && point.1 < y + height
+}
+
+fn load_assets(renderer: &Renderer) -> Option<WindowAssets>
+{
+	let load_font = |size: f32| FONT_FAMILIES.iter().filter_map(|x| Font::builder(&renderer.context()).family(x).size(size).build().ok()).next();
+
+	Some(WindowAssets{sprites: load_card_sprites(renderer), font: load_font(18.0)?, title_font: load_font(28.0)?})
+}
+
+fn load_card_sprites(renderer: &Renderer) -> EnumMap<Card, Sprite>
+{
+	enum_map!
+	{
+			Card::Pudding => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Pudding)).unwrap(),
+			Card::Dumpling => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Dumpling)).unwrap(),
+			Card::Sashimi => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Sashimi)).unwrap(),
+			Card::Tempura => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Tempura)).unwrap(),
+			Card::MakiRoll1 => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::MakiRoll1)).unwrap(),
+			Card::MakiRoll2 => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::MakiRoll2)).unwrap(),
+			Card::MakiRoll3 => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::MakiRoll3)).unwrap(),
+			Card::Chopsticks => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Chopsticks)).unwrap(),
+			Card::SalmonNigiri => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::SalmonNigiri)).unwrap(),
+			Card::EggNigiri => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::EggNigiri)).unwrap(),
+			Card::SquidNigiri => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::SquidNigiri)).unwrap(),
+			Card::Wasabi => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Wasabi)).unwrap(),
+			_ => Sprite::from_file(&renderer.context(), get_sprite_filename(&Card::Chopsticks)).unwrap(),
+    	}
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// The file ratings are kept in between runs.
+pub const RATINGS_FILE_NAME: &str = "ratings.json";
+
+// The rating given to an agent the first time it is seen.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+// How far a single game can move a rating.
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct AgentRating
+{
+	pub rating: f64,
+	pub games: usize,
+}
+
+// A snapshot of an agent's rating, used to track how a training run improves over the generations.
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct RatingHistoryEntry
+{
+	// The training run the agent came from.
+	pub run: String,
+	pub generation: usize,
+	pub agent: String,
+	pub rating: f64,
+}
+
+// Multiplayer Elo ratings. Each game is split into a match between every pair of players, so finishing
+// above a strong player is worth more than finishing above a weak one.
+#[derive(Default)]
+#[derive(Serialize, Deserialize)]
+pub struct RatingsTable
+{
+	ratings: BTreeMap<String, AgentRating>,
+
+	#[serde(default)]
+	history: Vec<RatingHistoryEntry>,
+}
+
+impl RatingsTable
+{
+	pub fn new() -> RatingsTable
+	{
+		RatingsTable::default()
+	}
+
+	pub fn get_rating(&self, agent: &str) -> f64
+	{
+		match self.ratings.get(agent)
+		{
+			Some(x) => x.rating,
+			None => INITIAL_RATING,
+		}
+	}
+
+	pub fn get_games(&self, agent: &str) -> usize
+	{
+		match self.ratings.get(agent)
+		{
+			Some(x) => x.games,
+			None => 0,
+		}
+	}
+
+	// Gives an agent a rating before it has played, e.g. to carry a rating over to a network's offspring.
+	pub fn set_rating(&mut self, agent: &str, rating: f64)
+	{
+		self.ratings.entry(agent.to_string()).or_insert(AgentRating{rating: INITIAL_RATING, games: 0}).rating = rating;
+	}
+
+	// Updates the ratings of the agents who played a game from their final scores. An agent may appear more than once if
+	// it filled several seats. Its seats don't rate against each other, their changes are added together so the total of
+	// everyone's ratings stays the same, and it is counted as playing one game.
+	pub fn update_from_game(&mut self, agents: &[String], scores: &[i32])
+	{
+		assert!(agents.len() == scores.len(), "Every agent needs a score!");
+
+		let number_of_players = agents.len();
+
+		if number_of_players < 2
+		{
+			return;
+		}
+
+		// All the changes are worked out from the ratings before the game.
+		let before: Vec<f64> = agents.iter().map(|x| self.get_rating(x)).collect();
+
+		let mut changes = vec![0.0; number_of_players];
+
+		for i in 0..number_of_players
+		{
+			for j in 0..number_of_players
+			{
+				// Seats filled by the same agent don't rate against each other.
+				if i == j || agents[i] == agents[j]
+				{
+					continue;
+				}
+
+				let expected = 1.0 / (1.0 + 10f64.powf((before[j] - before[i]) / 400.0));
+
+				let actual = if scores[i] > scores[j] { 1.0 } else if scores[i] == scores[j] { 0.5 } else { 0.0 };
+
+				changes[i] += (K_FACTOR / (number_of_players - 1) as f64) * (actual - expected);
+			}
+		}
+
+		for (i, x) in agents.iter().enumerate()
+		{
+			let entry = self.ratings.entry(x.clone()).or_insert(AgentRating{rating: INITIAL_RATING, games: 0});
+			entry.rating += changes[i];
+
+			if !agents[..i].contains(x)
+			{
+				entry.games += 1;
+			}
+		}
+	}
+
+	// Records the current rating of the agent picked from a generation of a training run, so the
+	// progression of the run can be looked at later.
+	pub fn record_history(&mut self, run: &str, generation: usize, agent: &str)
+	{
+		let rating = self.get_rating(agent);
+		self.history.push(RatingHistoryEntry{run: run.to_string(), generation, agent: agent.to_string(), rating});
+	}
+
+	pub fn print_history(&self, run: &str)
+	{
+		println!("\nRating progression for {}:", run);
+
+		for x in self.history.iter().filter(|x| x.run == run)
+		{
+			println!("Generation {:>6}: {:>8.1}", x.generation, x.rating);
+		}
+	}
+
+	pub fn get_history(&self) -> &Vec<RatingHistoryEntry>
+	{
+		&self.history
+	}
+
+	pub fn print_ratings(&self, agents: &[String])
+	{
+		println!("\n{:<40} {:>8} {:>7}", "Agent", "Rating", "Games");
+
+		let mut sorted: Vec<&String> = agents.iter().collect();
+		sorted.sort();
+		sorted.dedup();
+		sorted.sort_by(|a, b| self.get_rating(b).partial_cmp(&self.get_rating(a)).unwrap());
+
+		for x in sorted
+		{
+			println!("{:<40} {:>8.1} {:>7}", x, self.get_rating(x), self.get_games(x));
+		}
+	}
+
+	pub fn print_all_ratings(&self)
+	{
+		let agents: Vec<String> = self.ratings.keys().cloned().collect();
+		self.print_ratings(&agents);
+	}
+
+	pub fn save_to_file(&self, file_name: &str) -> io::Result<()>
+	{
+		let mut file = File::create(Path::new(file_name))?;
+		file.write_all(serde_json::to_string_pretty(self)?.as_bytes())
+	}
+}
+
+// Loads the ratings table, starting a new one if there isn't a file yet.
+pub fn load_ratings_from_file(file_name: &str) -> io::Result<RatingsTable>
+{
+	let path = Path::new(file_name);
+
+	if !path.exists()
+	{
+		return Ok(RatingsTable::new());
+	}
+
+	let mut data = String::new();
+	File::open(path)?.read_to_string(&mut data)?;
+
+	Ok(serde_json::from_str(&data)?)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn names(agents: &[&str]) -> Vec<String>
+	{
+		agents.iter().map(|x| x.to_string()).collect()
+	}
+
+	fn get_total(ratings: &RatingsTable, agents: &[String]) -> f64
+	{
+		let mut unique = agents.to_vec();
+		unique.sort();
+		unique.dedup();
+
+		unique.iter().map(|x| ratings.get_rating(x)).sum()
+	}
+
+	#[test]
+	fn four_player_game_rates_by_placement()
+	{
+		let mut ratings = RatingsTable::new();
+		let agents = names(&["a", "b", "c", "d"]);
+
+		ratings.update_from_game(&agents, &[40, 30, 20, 10]);
+
+		// Between equal ratings every pair is expected to draw, so each seat moves K / 3 for every player it beat or lost to.
+		let step = K_FACTOR / 3.0 * 0.5;
+		let expected = [INITIAL_RATING + 3.0 * step, INITIAL_RATING + step, INITIAL_RATING - step, INITIAL_RATING - 3.0 * step];
+
+		for (x, rating) in agents.iter().zip(expected.iter())
+		{
+			assert!((ratings.get_rating(x) - rating).abs() < 1e-9, "{} has {} rather than {}", x, ratings.get_rating(x), rating);
+			assert_eq!(ratings.get_games(x), 1);
+		}
+
+		// A draw is a loss of rating for the stronger players and a gain for the weaker ones.
+		let before: Vec<f64> = agents.iter().map(|x| ratings.get_rating(x)).collect();
+		ratings.update_from_game(&names(&["a", "c", "e", "f"]), &[25, 25, 25, 25]);
+
+		assert!(ratings.get_rating("a") < before[0], "Drawing against weaker players loses rating");
+		assert!(ratings.get_rating("c") > before[2], "Drawing against stronger players gains rating");
+	}
+
+	#[test]
+	fn total_rating_is_conserved()
+	{
+		let mut ratings = RatingsTable::new();
+
+		let games = [(["a", "b", "c", "d"], [31, 45, 12, 45]), (["d", "e", "a", "b"], [20, 18, 60, 7]), (["a", "a", "a", "e"], [10, 50, 30, 40]),
+					 (["c", "c", "b", "b"], [5, 5, 9, 1])];
+
+		let everyone = names(&["a", "b", "c", "d", "e"]);
+
+		for (agents, scores) in games.iter()
+		{
+			let before = get_total(&ratings, &everyone);
+
+			ratings.update_from_game(&names(agents), scores);
+
+			assert!((get_total(&ratings, &everyone) - before).abs() < 1e-9, "The total rating changed after {:?}", agents);
+		}
+	}
+
+	#[test]
+	fn an_agent_in_several_seats_plays_one_game()
+	{
+		let mut ratings = RatingsTable::new();
+
+		ratings.update_from_game(&names(&["a", "a", "a", "b"]), &[10, 20, 30, 40]);
+
+		assert_eq!(ratings.get_games("a"), 1);
+		assert_eq!(ratings.get_games("b"), 1);
+
+		// b beat every one of a's seats, each worth K / 3, so b gains what a losing a two player game would cost.
+		assert!((ratings.get_rating("b") - (INITIAL_RATING + K_FACTOR / 2.0)).abs() < 1e-9);
+		assert!((ratings.get_rating("a") - (INITIAL_RATING - K_FACTOR / 2.0)).abs() < 1e-9);
+
+		// A game between seats all filled by the same agent changes nothing but its game count.
+		ratings.update_from_game(&names(&["a", "a", "a", "a"]), &[10, 20, 30, 40]);
+
+		assert_eq!(ratings.get_games("a"), 2);
+		assert!((ratings.get_rating("a") - (INITIAL_RATING - K_FACTOR / 2.0)).abs() < 1e-9);
+	}
+}
@@ -0,0 +1,485 @@
+use super::neural_network_game::game::*;
+use super::neural_network_game::game::player::card::*;
+use super::neural_network_game::replay::*;
+use super::replay_viewer::*;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::path::Path;
+
+// Everything is drawn in software so images can be made on servers and in CI, where there is no GPU or display.
+pub const IMAGE_WIDTH: u32 = 1280;
+pub const IMAGE_HEIGHT: u32 = 720;
+
+const CARD_SIZE: i32 = 64;
+const CARD_GAP: i32 = 8;
+const PANEL_TOP: i32 = 50;
+const PANEL_HEIGHT: i32 = 160;
+const CHOSEN_CARDS_X: i32 = 360;
+const TEXT_SCALE: i32 = 2;
+
+type Rgba = [u8; 4];
+
+const BACKGROUND: Rgba = [24, 24, 32, 255];
+const CURRENT_PANEL: Rgba = [44, 52, 72, 255];
+const TEXT: Rgba = [230, 230, 230, 255];
+const TITLE: Rgba = [120, 210, 230, 255];
+const HIGHLIGHT: Rgba = [250, 210, 60, 255];
+const WINNER: Rgba = [120, 220, 120, 255];
+
+// Each glyph is 5 pixels wide and 7 tall, with the leftmost pixel of a row in the highest bit.
+const GLYPH_WIDTH: i32 = 5;
+const GLYPH_HEIGHT: i32 = 7;
+
+const FONT: &[(char, [u8; GLYPH_HEIGHT as usize])] = &[
+	('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+	('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+	('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+	('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+	('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+	('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+	('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+	('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+	('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+	('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+	('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+	('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+	('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+	('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+	('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+	('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+	('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+	('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+	('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+	('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+	('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+	('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+	('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+	('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+	('Y', [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100]),
+	('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+	('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+	('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+	('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+	('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+	('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+	('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+	('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+	('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+	('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+	('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+	(' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+	('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+	(',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+	(':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+	('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+	('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+	('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+	('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+	(')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+	('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+	('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+	('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+	('\'', [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+	('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+	('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+	('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+	('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+	('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+	('*', [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000]),
+];
+
+#[derive(Debug)]
+pub enum RenderError
+{
+	Io(io::Error),
+	Decode(png::DecodingError),
+	Encode(png::EncodingError),
+	Replay(String),
+}
+
+impl fmt::Display for RenderError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			RenderError::Io(e) => write!(f, "{}", e),
+			RenderError::Decode(e) => write!(f, "couldn't read the image, {}", e),
+			RenderError::Encode(e) => write!(f, "couldn't write the image, {}", e),
+			RenderError::Replay(e) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl Error for RenderError {}
+
+impl From<io::Error> for RenderError
+{
+	fn from(error: io::Error) -> Self
+	{
+		RenderError::Io(error)
+	}
+}
+
+impl From<png::DecodingError> for RenderError
+{
+	fn from(error: png::DecodingError) -> Self
+	{
+		RenderError::Decode(error)
+	}
+}
+
+impl From<png::EncodingError> for RenderError
+{
+	fn from(error: png::EncodingError) -> Self
+	{
+		RenderError::Encode(error)
+	}
+}
+
+// An RGBA image held in memory, with 4 bytes per pixel row by row.
+pub struct Canvas
+{
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>,
+}
+
+impl Canvas
+{
+	pub fn new(width: u32, height: u32, color: Rgba) -> Canvas
+	{
+		let pixels = color.iter().cloned().cycle().take((width * height * 4) as usize).collect();
+
+		Canvas{width, height, pixels}
+	}
+
+	pub fn get_width(&self) -> u32
+	{
+		self.width
+	}
+
+	pub fn get_height(&self) -> u32
+	{
+		self.height
+	}
+
+	// Blends a colour over a pixel using its alpha. Pixels off the edge of the canvas are ignored.
+	fn blend_pixel(&mut self, x: i32, y: i32, color: Rgba)
+	{
+		if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32
+		{
+			return;
+		}
+
+		let index = ((y as u32 * self.width + x as u32) * 4) as usize;
+		let alpha = color[3] as u32;
+
+		for (i, x) in color.iter().take(3).enumerate()
+		{
+			let old = self.pixels[index + i] as u32;
+			self.pixels[index + i] = ((*x as u32 * alpha + old * (255 - alpha)) / 255) as u8;
+		}
+
+		self.pixels[index + 3] = 255;
+	}
+
+	pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Rgba)
+	{
+		for py in y..y + height
+		{
+			for px in x..x + width
+			{
+				self.blend_pixel(px, py, color);
+			}
+		}
+	}
+
+	pub fn outline_rect(&mut self, x: i32, y: i32, width: i32, height: i32, thickness: i32, color: Rgba)
+	{
+		self.fill_rect(x, y, width, thickness, color);
+		self.fill_rect(x, y + height - thickness, width, thickness, color);
+		self.fill_rect(x, y, thickness, height, color);
+		self.fill_rect(x + width - thickness, y, thickness, height, color);
+	}
+
+	// Draws another image with its top left corner at x, y. Every pixel is multiplied by the tint, so white leaves the image as it is.
+	pub fn draw_image(&mut self, image: &Canvas, x: i32, y: i32, tint: Rgba)
+	{
+		for iy in 0..image.height
+		{
+			for ix in 0..image.width
+			{
+				let index = ((iy * image.width + ix) * 4) as usize;
+				let mut color = [0; 4];
+
+				for i in 0..4
+				{
+					color[i] = (image.pixels[index + i] as u32 * tint[i] as u32 / 255) as u8;
+				}
+
+				self.blend_pixel(x + ix as i32, y + iy as i32, color);
+			}
+		}
+	}
+
+	// Writes text with the built in font, each font pixel drawn as a square of scale pixels. Lower case letters are
+	// drawn as capitals and anything the font doesn't have as a question mark. Returns how wide the text was.
+	pub fn draw_text(&mut self, text: &str, x: i32, y: i32, scale: i32, color: Rgba) -> i32
+	{
+		let advance = (GLYPH_WIDTH + 1) * scale;
+		let mut cursor = x;
+
+		for c in text.chars()
+		{
+			let rows = get_glyph(c.to_ascii_uppercase()).or_else(|| get_glyph('?')).unwrap();
+
+			for (row, bits) in rows.iter().enumerate()
+			{
+				for column in 0..GLYPH_WIDTH
+				{
+					if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0
+					{
+						self.fill_rect(cursor + column * scale, y + row as i32 * scale, scale, scale, color);
+					}
+				}
+			}
+
+			cursor += advance;
+		}
+
+		cursor - x
+	}
+
+	pub fn save_png(&self, file_name: &Path) -> Result<(), RenderError>
+	{
+		let writer = BufWriter::new(File::create(file_name)?);
+
+		let mut encoder = png::Encoder::new(writer, self.width, self.height);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+		encoder.write_header()?.write_image_data(&self.pixels)?;
+
+		Ok(())
+	}
+}
+
+fn get_glyph(c: char) -> Option<&'static [u8; GLYPH_HEIGHT as usize]>
+{
+	FONT.iter().find(|x| x.0 == c).map(|x| &x.1)
+}
+
+// Reads a PNG into a canvas. Only 8 bit RGBA images are supported, which is what the card sprites are.
+pub fn load_png(file_name: &str) -> Result<Canvas, RenderError>
+{
+	let file = File::open(file_name).map_err(|e| io::Error::new(e.kind(), format!("couldn't open {}, {}", file_name, e)))?;
+	let decoder = png::Decoder::new(file);
+	let mut reader = decoder.read_info()?;
+
+	let mut pixels = vec![0; reader.output_buffer_size()];
+	let info = reader.next_frame(&mut pixels)?;
+
+	if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight
+	{
+		return Err(RenderError::Io(io::Error::new(io::ErrorKind::InvalidData, format!("{} isn't an 8 bit RGBA image", file_name))));
+	}
+
+	pixels.truncate(info.buffer_size());
+
+	Ok(Canvas{width: info.width, height: info.height, pixels})
+}
+
+// The sprite for each card, indexed by card. Card::None doesn't have one.
+pub struct CardImages
+{
+	images: Vec<Option<Canvas>>,
+}
+
+impl CardImages
+{
+	pub fn load() -> Result<CardImages, RenderError>
+	{
+		let mut images = Vec::new();
+
+		for i in 0..Card::CardMax as usize
+		{
+			let card = Card::from(i);
+			let file_name = get_sprite_filename(&card);
+
+			images.push(if file_name.is_empty() { None } else { Some(load_png(file_name)?) });
+		}
+
+		Ok(CardImages{images})
+	}
+
+	pub fn get(&self, card: Card) -> Option<&Canvas>
+	{
+		self.images.get(card as usize).and_then(|x| x.as_ref())
+	}
+}
+
+// Draws a card. Card::None is the choice to keep your chopsticks, so it's drawn as faded chopsticks.
+fn draw_card(canvas: &mut Canvas, images: &CardImages, card: Card, x: i32, y: i32)
+{
+	match card
+	{
+		Card::None =>
+		{
+			if let Some(image) = images.get(Card::Chopsticks)
+			{
+				canvas.draw_image(image, x, y, [255, 255, 255, 90]);
+			}
+
+			canvas.draw_text("KEEP", x + 8, y + 25, TEXT_SCALE, TEXT);
+		}
+		card => match images.get(card)
+		{
+			Some(image) => canvas.draw_image(image, x, y, [255, 255, 255, 255]),
+			None => { canvas.draw_text("?", x + 26, y + 25, TEXT_SCALE, TEXT); },
+		},
+	}
+}
+
+fn get_card_x(slot: usize) -> i32
+{
+	CHOSEN_CARDS_X + slot as i32 * (CARD_SIZE + CARD_GAP)
+}
+
+// Draws the whole table: every player's score, chosen cards and hand. If a card is about to be picked it is outlined
+// in the hand of the player picking it.
+pub fn render_game(game: &SushiGoGame, names: &[String], images: &CardImages, picked: Option<(usize, Card)>) -> Canvas
+{
+	let mut canvas = Canvas::new(IMAGE_WIDTH, IMAGE_HEIGHT, BACKGROUND);
+
+	let game_over = game.get_current_round() >= game.get_number_of_rounds();
+
+	let title = if game_over
+	{
+		"Sushi Go! Game over".to_string()
+	}
+	else
+	{
+		format!("Sushi Go! Round {} of {}", game.get_current_round() + 1, game.get_number_of_rounds())
+	};
+
+	canvas.draw_text(&title, 20, 14, 3, TITLE);
+
+	let scores = game.get_running_scores();
+	let puddings = game.get_pudding_counts();
+	let final_scores = game.get_final_scores();
+
+	for i in 0..game.get_num_players()
+	{
+		let top = PANEL_TOP + i as i32 * PANEL_HEIGHT;
+		let current = !game_over && i == game.get_current_player_id();
+
+		if current
+		{
+			canvas.fill_rect(0, top, IMAGE_WIDTH as i32, PANEL_HEIGHT, CURRENT_PANEL);
+		}
+
+		let chosen = if game_over { Vec::new() } else { game.get_player_chosen(i) };
+		let maki: usize = chosen.iter().map(|x| get_maki_roll_value(*x)).sum();
+		let pudding = puddings[i] as usize + chosen.iter().filter(|x| **x == Card::Pudding).count();
+
+		let mut name = names.get(i).cloned().unwrap_or_default();
+		name.truncate(20);
+
+		canvas.draw_text(&format!("{} {}", i, name), 20, top + 8, TEXT_SCALE, if current { HIGHLIGHT } else { TEXT });
+		canvas.draw_text(&format!("Score {}", scores[i]), 20, top + 34, TEXT_SCALE, TEXT);
+
+		if game_over
+		{
+			canvas.draw_text(&format!("Pudding {}", pudding), 20, top + 56, TEXT_SCALE, TEXT);
+
+			let color = if i == game.get_winner() { WINNER } else { TEXT };
+			canvas.draw_text(&format!("Final {}", final_scores[i]), 20, top + 82, TEXT_SCALE, color);
+			continue;
+		}
+
+		canvas.draw_text(&format!("Maki {}  Pudding {}", maki, pudding), 20, top + 56, TEXT_SCALE, TEXT);
+
+		for (slot, card) in chosen.iter().enumerate()
+		{
+			draw_card(&mut canvas, images, *card, get_card_x(slot), top + 8);
+		}
+
+		let hand_top = top + 8 + CARD_SIZE + CARD_GAP;
+		let hand = game.get_player_hand(i);
+
+		for (slot, card) in hand.iter().enumerate()
+		{
+			draw_card(&mut canvas, images, *card, get_card_x(slot), hand_top);
+		}
+
+		if let Some((player, card)) = picked
+		{
+			if player == i
+			{
+				if let Some(slot) = hand.iter().position(|x| *x == card)
+				{
+					canvas.outline_rect(get_card_x(slot) - 3, hand_top - 3, CARD_SIZE + 6, CARD_SIZE + 6, 3, HIGHLIGHT);
+				}
+			}
+		}
+	}
+
+	canvas
+}
+
+// Plays back a replay and writes a picture of the table before every turn to output_dir, named turn_000.png and so
+// on, then one of the final scores named final.png. If a turn is given only that turn is drawn. Returns how many
+// images were written.
+pub fn render_replay(replay: &GameReplay, output_dir: &Path, turn: Option<usize>) -> Result<usize, RenderError>
+{
+	fs::create_dir_all(output_dir)?;
+
+	let images = CardImages::load()?;
+
+	let mut index = 0;
+	let mut written = 0;
+	let mut error = None;
+
+	let played = play_back_replay(replay, |game, x|
+	{
+		if error.is_none() && turn.is_none_or(|t| t == index)
+		{
+			let canvas = render_game(game, &replay.players, &images, Some((x.player, x.card)));
+
+			match canvas.save_png(&output_dir.join(format!("turn_{:03}.png", index)))
+			{
+				Ok(()) => written += 1,
+				Err(e) => error = Some(e),
+			}
+		}
+
+		index += 1;
+	});
+
+	if let Some(e) = error
+	{
+		return Err(e);
+	}
+
+	let game = played.map_err(|e| RenderError::Replay(format!("the replay doesn't reproduce, {}", e)))?;
+
+	if let Some(t) = turn
+	{
+		if t >= index
+		{
+			return Err(RenderError::Replay(format!("there is no turn {}, the game has {} turns", t, index)));
+		}
+	}
+	else
+	{
+		render_game(&game, &replay.players, &images, None).save_png(&output_dir.join("final.png"))?;
+		written += 1;
+	}
+
+	Ok(written)
+}
@@ -0,0 +1,372 @@
+use super::neural_network_game::NUMBER_OF_PLAYERS;
+use super::neural_network_game::game::*;
+use super::neural_network_game::game::player::card::*;
+use super::neural_network_game::model::*;
+use super::neural_network_game::replay::*;
+
+use std::io;
+
+// A turn of a replay along with the state of the table just before the card was picked.
+pub struct ReplayTurn
+{
+	pub round: u8,
+	pub player: usize,
+	pub hand: Vec<Card>,
+	pub card: Card,
+	pub chopsticks: bool,
+	pub weights: Option<Vec<CardWeight>>,
+
+	// The cards every player had chosen so far this round, indexed by player ID.
+	pub chosen: Vec<Vec<Card>>,
+}
+
+// The result of playing a replay back through the game.
+pub struct ReplaySimulation
+{
+	// Every turn played back before the replay finished or stopped matching the game.
+	pub turns: Vec<ReplayTurn>,
+
+	// Why the game and the replay disagree, if they do.
+	pub error: Option<String>,
+
+	pub final_scores: Vec<i32>,
+}
+
+impl ReplaySimulation
+{
+	pub fn is_verified(&self) -> bool
+	{
+		self.error.is_none()
+	}
+}
+
+// Plays the replay back through SushiGoGame::step, checking the game agrees with the replay at every event. Stops at
+// the first event which doesn't match.
+pub fn simulate_replay(replay: &GameReplay) -> ReplaySimulation
+{
+	let mut turns = Vec::new();
+
+	match play_back_replay(replay, |_, turn| turns.push(turn))
+	{
+		Ok(game) => ReplaySimulation{turns, error: None, final_scores: game.get_final_scores()},
+		Err(e) => ReplaySimulation{turns, error: Some(e), final_scores: Vec::new()},
+	}
+}
+
+// Plays the replay back, calling on_turn with the game just before each card is picked. Returns the finished game, or
+// why the game and the replay disagree.
+pub fn play_back_replay<F>(replay: &GameReplay, mut on_turn: F) -> Result<SushiGoGame, String>
+	where F: FnMut(&SushiGoGame, ReplayTurn)
+{
+	if replay.players.is_empty() || replay.players.len() > NUMBER_OF_PLAYERS
+	{
+		return Err(format!("the replay has {} players, games are played with 1 to {}", replay.players.len(), NUMBER_OF_PLAYERS));
+	}
+
+	let mut game = SushiGoGame::new(replay.players.len());
+
+	match replay.seed
+	{
+		Some(seed) => game.setup_with_seed(seed),
+		None => game.setup(),
+	}
+
+	let mut result = StepResult::NoResult;
+
+	for (i, event) in replay.events.iter().enumerate()
+	{
+		let mismatch = |message: String| Err(format!("event {}: {}", i, message));
+
+		match event
+		{
+			ReplayEvent::Deal{round, hands} =>
+			{
+				if *round != game.get_current_round()
+				{
+					return mismatch(format!("dealt round {} but the game is on round {}", round, game.get_current_round()));
+				}
+
+				// A seeded game deals itself, so the recorded deal has to match the seed.
+				if replay.seed.is_some() && game.get_hands() != *hands
+				{
+					return mismatch("the recorded deal doesn't match the deal from the seed".to_string());
+				}
+
+				if hands.len() != game.get_num_players()
+				{
+					return mismatch(format!("dealt {} hands to {} players", hands.len(), game.get_num_players()));
+				}
+
+				game.set_hands(hands.clone());
+			}
+			ReplayEvent::Action{round, player, hand, card, chopsticks, weights} =>
+			{
+				if result.is_game_over()
+				{
+					return mismatch(format!("player {} acted after the game was over", player));
+				}
+
+				if *round != game.get_current_round() || *player != game.get_current_player_id()
+				{
+					return mismatch(format!("player {} acted in round {} but it is player {}'s turn in round {}", player, round, game.get_current_player_id(), game.get_current_round()));
+				}
+
+				if *chopsticks != game.is_using_chopsticks()
+				{
+					return mismatch(format!("player {} {} using chopsticks", player, if *chopsticks { "should be" } else { "shouldn't be" }));
+				}
+
+				if game.get_current_player_hand() != *hand
+				{
+					return mismatch(format!("player {} has a different hand to the one recorded", player));
+				}
+
+				let chosen = (0..game.get_num_players()).map(|x| game.get_player_chosen(x)).collect();
+
+				on_turn(&game, ReplayTurn{round: *round, player: *player, hand: hand.clone(), card: *card, chopsticks: *chopsticks, weights: weights.clone(), chosen});
+
+				result = game.step(*card);
+
+				if result.is_error()
+				{
+					return mismatch(format!("player {} can't pick {}", player, card));
+				}
+			}
+			ReplayEvent::PassHands{round, hands} =>
+			{
+				if *round != game.get_current_round() || game.get_hands() != *hands
+				{
+					return mismatch("the hands after passing don't match the game".to_string());
+				}
+			}
+			ReplayEvent::RoundEnd{round, scores} =>
+			{
+				if *round >= game.get_current_round()
+				{
+					return mismatch(format!("round {} ended before its last card was picked", round));
+				}
+
+				let game_scores = game.get_round_scores(*round);
+
+				if game_scores != *scores
+				{
+					return mismatch(format!("round {} scored {:?} but the replay has {:?}", round, game_scores, scores));
+				}
+			}
+		}
+	}
+
+	if !result.is_game_over()
+	{
+		return Err("the replay ends before the game is over".to_string());
+	}
+
+	let final_scores = game.get_final_scores();
+
+	if final_scores != replay.final_scores
+	{
+		return Err(format!("the game finished with {:?} but the replay has {:?}", final_scores, replay.final_scores));
+	}
+
+	if game.get_pudding_scores() != replay.pudding_scores
+	{
+		return Err(format!("the puddings scored {:?} but the replay has {:?}", game.get_pudding_scores(), replay.pudding_scores));
+	}
+
+	// Ties are broken at random in unseeded games, so only check the recorded winner had the top score.
+	let winner_matches = match (replay.seed, replay.winner)
+	{
+		(Some(_), Some(winner)) => winner == game.get_winner(),
+		(None, Some(winner)) => winner < final_scores.len() && final_scores[winner] == game.get_winning_score(),
+		(_, None) => false,
+	};
+
+	if !winner_matches
+	{
+		return Err(format!("the replay has {:?} as the winner but the game doesn't", replay.winner));
+	}
+
+	Ok(game)
+}
+
+// Plays back every replay and says whether each one reproduces. Returns true if they all did.
+pub fn verify_replays(replays: &[GameReplay]) -> bool
+{
+	let mut verified = 0;
+
+	for (i, x) in replays.iter().enumerate()
+	{
+		let simulation = simulate_replay(x);
+
+		match &simulation.error
+		{
+			None => verified += 1,
+			Some(e) => println!("Game {} doesn't reproduce, {}", i, e),
+		}
+	}
+
+	println!("{} of {} games reproduced their recorded scores", verified, replays.len());
+
+	verified == replays.len()
+}
+
+// Steps through a replay turn by turn, reading commands from the terminal.
+pub fn view_replay(replay: &GameReplay)
+{
+	let simulation = simulate_replay(replay);
+
+	println!("Players: {}", replay.players.iter().enumerate().map(|(i, x)| format!("{} {}", i, x)).collect::<Vec<String>>().join(", "));
+	println!("Seed: {}", replay.seed.map_or("none".to_string(), |x| x.to_string()));
+
+	match &simulation.error
+	{
+		None => println!("The replay reproduces the recorded scores {:?}", replay.final_scores),
+		Some(e) => println!("The replay doesn't reproduce, {}. Only the turns before that can be viewed.", e),
+	}
+
+	if simulation.turns.is_empty()
+	{
+		return;
+	}
+
+	let mut index = 0;
+
+	loop
+	{
+		print_turn(replay, &simulation, index);
+
+		println!("\n[Enter] next turn, 'b' previous turn, 'g <turn>' go to a turn, 'q' quit");
+
+		let mut command = String::new();
+
+		match io::stdin().read_line(&mut command)
+		{
+			Ok(0) | Err(_) => return,
+			Ok(_) => {},
+		}
+
+		let command: Vec<&str> = command.split_whitespace().collect();
+
+		match command.as_slice()
+		{
+			[] | ["n"] =>
+			{
+				if index + 1 < simulation.turns.len()
+				{
+					index += 1;
+				}
+				else
+				{
+					println!("\nThat was the last turn. Final scores: {:?}", replay.final_scores);
+				}
+			}
+			["b"] => index = index.saturating_sub(1),
+			["g", turn] => match turn.parse::<usize>()
+			{
+				Ok(turn) if turn < simulation.turns.len() => index = turn,
+				_ => println!("\nThere are {} turns, pick one from 0 to {}.", simulation.turns.len(), simulation.turns.len() - 1),
+			},
+			["q"] => return,
+			_ => println!("\nThat isn't a command."),
+		}
+	}
+}
+
+fn print_turn(replay: &GameReplay, simulation: &ReplaySimulation, index: usize)
+{
+	let turn = &simulation.turns[index];
+
+	println!("\nTurn {} of {}, round {}", index, simulation.turns.len() - 1, turn.round + 1);
+
+	for (i, x) in turn.chosen.iter().enumerate()
+	{
+		println!("Player {} ({}) has chosen: {}", i, replay.players[i], format_cards(x));
+	}
+
+	let action = if turn.chopsticks { "uses their chopsticks" } else { "picks" };
+
+	println!("\nPlayer {} ({}) {} from: {}", turn.player, replay.players[turn.player], action, format_cards(&turn.hand));
+
+	match turn.card
+	{
+		Card::None => println!("They keep their chopsticks"),
+		card => println!("They pick {}", card),
+	}
+
+	if let Some(weights) = &turn.weights
+	{
+		println!("\nNetwork outputs:");
+
+		for x in weights
+		{
+			let marker = if x.card == turn.card { "*" } else { " " };
+
+			println!("{} {:<15} weight {:>10.4} probability {:>6.2}%", marker, x.card.to_string(), x.weight, x.probability * 100.0);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use super::super::neural_network_game::*;
+	use super::super::neural_network_game::policy::*;
+	use rand::SeedableRng;
+	use rand::rngs::StdRng;
+	use std::fs;
+
+	// Plays a seeded game between networks, recording it to a replay file, and returns the name of the file.
+	fn record_seeded_game(name: &str) -> String
+	{
+		let mut rng = StdRng::seed_from_u64(3);
+		let properties = NeuralNetworkModel::default().get_node_count();
+		let mut pool = create_neural_networks_with_rng(NUMBER_OF_PLAYERS, &properties, PolicyMode::default(), &mut rng);
+
+		let mut games = Vec::new();
+		create_games(&mut games, 1, &mut pool, Some(11));
+		games[0].enable_replay();
+		games[0].finish_game();
+
+		let file_name = std::env::temp_dir().join(format!("{}_{}.jsonl", name, std::process::id())).to_string_lossy().into_owned();
+
+		let mut writer = ReplayWriter::new(&file_name).unwrap();
+		writer.write(games[0].get_replay().unwrap()).unwrap();
+
+		file_name
+	}
+
+	#[test]
+	fn recorded_game_plays_back()
+	{
+		let file_name = record_seeded_game("replay_round_trip");
+		let replays = load_replays_from_file(&file_name);
+		fs::remove_file(&file_name).unwrap();
+		let replays = replays.unwrap();
+
+		assert_eq!(replays.len(), 1);
+		assert!(simulate_replay(&replays[0]).is_verified());
+		assert!(verify_replays(&replays));
+	}
+
+	#[test]
+	fn tampered_action_is_rejected()
+	{
+		let file_name = record_seeded_game("replay_tampered");
+
+		// Swap the card picked in the first action for a different card from the same hand.
+		let mut replay: serde_json::Value = serde_json::from_str(fs::read_to_string(&file_name).unwrap().trim()).unwrap();
+		let action = replay["events"].as_array_mut().unwrap().iter_mut().find(|x| x["event"] == "action").unwrap();
+		let other = action["hand"].as_array().unwrap().iter().find(|x| **x != action["card"]).unwrap().clone();
+		action["card"] = other;
+
+		fs::write(&file_name, format!("{}\n", replay)).unwrap();
+
+		let replays = load_replays_from_file(&file_name);
+		fs::remove_file(&file_name).unwrap();
+		let replays = replays.unwrap();
+
+		assert!(!simulate_replay(&replays[0]).is_verified());
+		assert!(!verify_replays(&replays));
+	}
+}
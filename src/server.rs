@@ -0,0 +1,568 @@
+// Hosts games over TCP so humans and bots in other processes can play.
+//
+// The protocol is line delimited JSON: every message is one JSON object on its own line, with a "type" field saying
+// which message it is. Cards are named as in the replays, such as "SalmonNigiri", with "None" meaning keep your
+// chopsticks.
+//
+// Client to server:
+//   {"type":"join","name":"alice"}
+//       Must be the first message. The player waits in the lobby until the table is full.
+//   {"type":"action","card":"Tempura"}
+//       Picks a card, only after being sent your_turn. Must be one of its legal_actions.
+//
+// Server to client:
+//   {"type":"joined","players_needed":2}
+//       How many more players the table is waiting for.
+//   {"type":"game_start","seat":1,"players":["alice","bob","random","random"]}
+//       Which seat you are in and who is at each seat.
+//   {"type":"your_turn","observation":{"round":0,"player":1,"hand":[...],"chosen":[[...],...],"scores":[...],
+//       "puddings":[...],"using_chopsticks":false,"legal_actions":[...]}}
+//       Everything you can see of the game. Answer with an action before the turn timeout, which runs from when this
+//       is sent and isn't restarted by rejected actions.
+//   {"type":"action_rejected","message":"..."}
+//       The action couldn't be played. Send another one.
+//   {"type":"round_end","round":0,"scores":[...],"totals":[...]}
+//       What each player scored in the round, and in every round so far, not counting puddings.
+//   {"type":"game_over","pudding_scores":[...],"final_scores":[...],"winner":2}
+//       The connection is closed after this.
+//   {"type":"error","message":"..."}
+//       A line couldn't be understood. It is ignored.
+//
+// A player who disconnects or doesn't answer in time is replaced by a random player for the rest of the game.
+
+use super::neural_network_game::*;
+use super::neural_network_game::game::*;
+use super::neural_network_game::game::player::card::*;
+use super::neural_network_game::neural_network::*;
+use super::neural_network_game::observation::*;
+
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long a new connection has to send its join message.
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How long sending a message can take before the player is treated as gone, so one who stops reading can't hold up
+// their table.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Every message fits in far less than this. A longer line drops the connection, so a player can't run the server out
+// of memory by never ending a line.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage
+{
+	Join{name: String},
+	Action{card: Card},
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage
+{
+	Joined{players_needed: usize},
+	GameStart{seat: usize, players: Vec<String>},
+	YourTurn{observation: Observation},
+	ActionRejected{message: String},
+	RoundEnd{round: u8, scores: Vec<i32>, totals: Vec<i32>},
+	GameOver{pudding_scores: Vec<i32>, final_scores: Vec<i32>, winner: usize},
+	Error{message: String},
+}
+
+pub struct ServerSettings
+{
+	// How many seats at each table are for players who connect.
+	pub remote_seats: usize,
+
+	// Fill the rest of the seats, in order.
+	pub bots: Vec<NeuralNetworkGamePlayer>,
+
+	// How long a player has to pick a legal card on their turn, however many tries it takes them.
+	pub turn_timeout: Duration,
+
+	// Stops after this many games have been played, or carries on forever.
+	pub games: Option<usize>,
+}
+
+// A line delimited JSON connection to a player.
+pub struct Connection
+{
+	name: String,
+	reader: BufReader<TcpStream>,
+	writer: TcpStream,
+}
+
+impl Connection
+{
+	// Waits for the join message from a new connection.
+	fn accept(stream: TcpStream) -> io::Result<Connection>
+	{
+		// Each turn is a short message and a short answer, which shouldn't wait to be batched with anything else.
+		stream.set_nodelay(true)?;
+		stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+		let mut connection = Connection{name: String::new(), reader: BufReader::new(stream.try_clone()?), writer: stream};
+
+		match connection.read_message(Instant::now() + JOIN_TIMEOUT)?
+		{
+			ClientMessage::Join{name} =>
+			{
+				connection.name = if name.trim().is_empty() { "remote".to_string() } else { name.trim().to_string() };
+				Ok(connection)
+			}
+			_ =>
+			{
+				let _ = connection.send(&ServerMessage::Error{message: "The first message has to be join.".to_string()});
+				Err(io::Error::new(io::ErrorKind::InvalidData, "the first message wasn't join"))
+			}
+		}
+	}
+
+	pub fn send(&mut self, message: &ServerMessage) -> io::Result<()>
+	{
+		let line = serde_json::to_string(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+		writeln!(self.writer, "{}", line)?;
+		self.writer.flush()
+	}
+
+	// Reads the next line, failing if the whole line doesn't arrive before the deadline or the connection is closed.
+	fn read_line(&mut self, deadline: Instant) -> io::Result<String>
+	{
+		let mut line = Vec::new();
+
+		loop
+		{
+			let remaining = deadline.saturating_duration_since(Instant::now());
+
+			if remaining == Duration::from_secs(0)
+			{
+				return Err(io::Error::new(io::ErrorKind::TimedOut, "they didn't answer in time"));
+			}
+
+			// Read what has arrived so far rather than waiting for a whole line, so a player can't hold on to their
+			// turn by sending a little at a time.
+			self.writer.set_read_timeout(Some(remaining))?;
+
+			let (found_end, used) = match self.reader.fill_buf()
+			{
+				Ok([]) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed")),
+				Ok(buffer) => match buffer.iter().position(|x| *x == b'\n')
+				{
+					Some(end) =>
+					{
+						line.extend_from_slice(&buffer[..end]);
+						(true, end + 1)
+					}
+					None =>
+					{
+						line.extend_from_slice(buffer);
+						(false, buffer.len())
+					}
+				},
+				Err(e) => match e.kind()
+				{
+					io::ErrorKind::Interrupted => continue,
+					io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => return Err(io::Error::new(io::ErrorKind::TimedOut, "they didn't answer in time")),
+					_ => return Err(e),
+				},
+			};
+
+			self.reader.consume(used);
+
+			if line.len() > MAX_LINE_LENGTH
+			{
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!("they sent a line longer than {} bytes", MAX_LINE_LENGTH)));
+			}
+
+			if found_end
+			{
+				return Ok(String::from_utf8_lossy(&line).into_owned());
+			}
+		}
+	}
+
+	// Reads the next message, skipping lines which can't be understood. Fails if nothing arrives before the deadline
+	// or the connection is closed.
+	fn read_message(&mut self, deadline: Instant) -> io::Result<ClientMessage>
+	{
+		loop
+		{
+			let line = self.read_line(deadline)?;
+
+			if line.trim().is_empty()
+			{
+				continue;
+			}
+
+			match serde_json::from_str(&line)
+			{
+				Ok(message) => return Ok(message),
+				Err(e) => self.send(&ServerMessage::Error{message: format!("Couldn't read the message: {}", e)})?,
+			}
+		}
+	}
+
+	// Asks the player which card to pick, until they pick one they're allowed to. The timeout covers the whole turn,
+	// not each try.
+	fn request_action(&mut self, game: &SushiGoGame, seat: usize, timeout: Duration) -> io::Result<Card>
+	{
+		let observation = Observation::new(game, seat);
+
+		self.send(&ServerMessage::YourTurn{observation: observation.clone()})?;
+
+		let deadline = Instant::now() + timeout;
+
+		loop
+		{
+			let message = match self.read_message(deadline)?
+			{
+				ClientMessage::Action{card} if observation.is_legal(card) => return Ok(card),
+				ClientMessage::Action{card} => format!("You can't pick {}, pick one of: {}", card, format_cards(&observation.legal_actions)),
+				ClientMessage::Join{..} => "You have already joined.".to_string(),
+			};
+
+			self.send(&ServerMessage::ActionRejected{message})?;
+		}
+	}
+}
+
+// Who is playing in a seat.
+enum Seat
+{
+	Remote(Connection),
+	Bot(Box<NeuralNetworkGamePlayer>),
+}
+
+impl Seat
+{
+	fn get_name(&self) -> String
+	{
+		match self
+		{
+			Seat::Remote(connection) => connection.name.clone(),
+			Seat::Bot(player) => player.name.clone(),
+		}
+	}
+}
+
+// Listens for players and starts a game each time there are enough of them. Returns once the number of games in the
+// settings have all finished, or if the address can't be listened on.
+pub fn run_server(address: &str, settings: ServerSettings) -> io::Result<()>
+{
+	run_server_on(TcpListener::bind(address)?, settings)
+}
+
+// The same as run_server, on a listener which is already bound.
+pub fn run_server_on(listener: TcpListener, settings: ServerSettings) -> io::Result<()>
+{
+	assert!(settings.remote_seats > 0 && settings.remote_seats + settings.bots.len() == NUMBER_OF_PLAYERS, "The seats at the table don't add up!");
+
+	info!("Listening on {}, waiting for {} players per game", listener.local_addr()?, settings.remote_seats);
+
+	// Connections are accepted on their own threads so a slow client can't hold up the rest.
+	let (sender, receiver) = mpsc::channel();
+
+	thread::spawn(move ||
+	{
+		for stream in listener.incoming()
+		{
+			let stream = match stream
+			{
+				Ok(stream) => stream,
+				Err(e) =>
+				{
+					warn!("Couldn't accept a connection: {}", e);
+					continue;
+				}
+			};
+
+			let sender = sender.clone();
+
+			thread::spawn(move || match Connection::accept(stream)
+			{
+				Ok(connection) =>
+				{
+					let _ = sender.send(connection);
+				}
+				Err(e) => warn!("A connection didn't join: {}", e),
+			});
+		}
+	});
+
+	let mut lobby: Vec<Connection> = Vec::new();
+	let mut tables = Vec::new();
+
+	while settings.games.is_none_or(|x| tables.len() < x)
+	{
+		let mut connection = match receiver.recv()
+		{
+			Ok(connection) => connection,
+			Err(_) => break,
+		};
+
+		info!("{} joined", connection.name);
+
+		let _ = connection.send(&ServerMessage::Joined{players_needed: settings.remote_seats - lobby.len() - 1});
+
+		lobby.push(connection);
+
+		if lobby.len() == settings.remote_seats
+		{
+			let mut seats: Vec<Seat> = lobby.drain(..).map(Seat::Remote).collect();
+			seats.extend(settings.bots.iter().map(|x| Seat::Bot(Box::new(x.clone()))));
+
+			let turn_timeout = settings.turn_timeout;
+			let table = tables.len();
+
+			tables.push(thread::spawn(move || play_table(table, seats, turn_timeout)));
+		}
+	}
+
+	for x in tables
+	{
+		let _ = x.join();
+	}
+
+	Ok(())
+}
+
+// Plays one game between the players in the seats, telling the remote players what is happening as it goes.
+fn play_table(table: usize, mut seats: Vec<Seat>, turn_timeout: Duration)
+{
+	let names: Vec<String> = seats.iter().map(|x| x.get_name()).collect();
+
+	info!("Table {} started: {}", table, names.join(", "));
+
+	let mut game = SushiGoGame::new(seats.len());
+	game.setup();
+
+	for (i, x) in seats.iter_mut().enumerate()
+	{
+		if let Seat::Remote(connection) = x
+		{
+			let _ = connection.send(&ServerMessage::GameStart{seat: i, players: names.clone()});
+		}
+	}
+
+	let mut result = StepResult::NoResult;
+
+	while !result.is_game_over()
+	{
+		let current = game.get_current_player_id();
+
+		let card = match &mut seats[current]
+		{
+			Seat::Remote(connection) => match connection.request_action(&game, current, turn_timeout)
+			{
+				Ok(card) => card,
+				Err(e) =>
+				{
+					info!("Table {}: {} left ({}), a random player takes their seat", table, names[current], e);
+
+					seats[current] = Seat::Bot(Box::new(NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_neural_network())));
+					continue;
+				}
+			},
+			Seat::Bot(player) => pick_bot_card(&game, player),
+		};
+
+		let round = game.get_current_round();
+
+		result = game.step(card);
+
+		if result.is_round_over() || result.is_game_over()
+		{
+			broadcast(&mut seats, &ServerMessage::RoundEnd{round, scores: game.get_round_scores(round), totals: game.get_running_scores()});
+		}
+	}
+
+	broadcast(&mut seats, &ServerMessage::GameOver{pudding_scores: game.get_pudding_scores(), final_scores: game.get_final_scores(), winner: game.get_winner()});
+
+	info!("Table {} finished, {} won with scores {:?}", table, names[game.get_winner()], game.get_final_scores());
+}
+
+// Sends a message to every remote player. Players who can't be reached are noticed on their next turn.
+fn broadcast(seats: &mut [Seat], message: &ServerMessage)
+{
+	for x in seats.iter_mut()
+	{
+		if let Seat::Remote(connection) = x
+		{
+			let _ = connection.send(message);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	// Speaks the line protocol from the other end of the connection.
+	struct TestClient
+	{
+		reader: BufReader<TcpStream>,
+		writer: TcpStream,
+	}
+
+	impl TestClient
+	{
+		fn join(address: &str, name: &str) -> TestClient
+		{
+			let stream = TcpStream::connect(address).unwrap();
+			stream.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+			stream.set_nodelay(true).unwrap();
+
+			let mut client = TestClient{reader: BufReader::new(stream.try_clone().unwrap()), writer: stream};
+			client.send(&ClientMessage::Join{name: name.to_string()}).unwrap();
+
+			client
+		}
+
+		fn send(&mut self, message: &ClientMessage) -> io::Result<()>
+		{
+			writeln!(self.writer, "{}", serde_json::to_string(message).unwrap())
+		}
+
+		// Returns None once the server has closed the connection.
+		fn read(&mut self) -> Option<ServerMessage>
+		{
+			let mut line = String::new();
+
+			match self.reader.read_line(&mut line).unwrap()
+			{
+				0 => None,
+				_ => Some(serde_json::from_str(&line).unwrap()),
+			}
+		}
+	}
+
+	// Starts a server for one game with two connected players and two random players, returning its address.
+	fn start_server(turn_timeout: Duration) -> (String, thread::JoinHandle<io::Result<()>>)
+	{
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap().to_string();
+
+		let bots = (0..NUMBER_OF_PLAYERS - 2).map(|_| NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_neural_network())).collect();
+		let settings = ServerSettings{remote_seats: 2, bots, turn_timeout, games: Some(1)};
+
+		(address, thread::spawn(move || run_server_on(listener, settings)))
+	}
+
+	// Picks the first legal card every turn, returning the final scores and how many turns were played.
+	fn play_legal_cards(mut client: TestClient) -> (Vec<i32>, usize)
+	{
+		let mut turns = 0;
+
+		loop
+		{
+			match client.read().expect("the connection was closed before the game ended")
+			{
+				ServerMessage::YourTurn{observation} =>
+				{
+					turns += 1;
+					client.send(&ClientMessage::Action{card: observation.legal_actions[0]}).unwrap();
+				}
+				ServerMessage::ActionRejected{message} => panic!("a legal card was rejected: {}", message),
+				ServerMessage::GameOver{final_scores, ..} => return (final_scores, turns),
+				_ => (),
+			}
+		}
+	}
+
+	#[test]
+	fn plays_a_full_game_over_loopback()
+	{
+		let (address, server) = start_server(Duration::from_secs(10));
+
+		let clients: Vec<thread::JoinHandle<(Vec<i32>, usize)>> = ["alice", "bob"].iter().map(|name|
+		{
+			let client = TestClient::join(&address, name);
+			thread::spawn(move || play_legal_cards(client))
+		}).collect();
+
+		let results: Vec<(Vec<i32>, usize)> = clients.into_iter().map(|x| x.join().unwrap()).collect();
+
+		server.join().unwrap().unwrap();
+
+		assert_eq!(results[0].0.len(), NUMBER_OF_PLAYERS);
+		assert_eq!(results[0].0, results[1].0);
+
+		// Every card dealt is picked, with an extra turn each time chopsticks are used.
+		assert!(results.iter().all(|x| x.1 >= NUMBER_OF_ROUNDS as usize * MAX_HAND_SIZE));
+	}
+
+	#[test]
+	fn overlong_lines_drop_the_connection()
+	{
+		let (address, _server) = start_server(Duration::from_secs(10));
+
+		let mut stream = TcpStream::connect(&address).unwrap();
+		stream.set_read_timeout(Some(JOIN_TIMEOUT * 2)).unwrap();
+
+		let started = Instant::now();
+
+		// The server can drop the connection before it has all been sent, so the write can fail.
+		let _ = stream.write_all(&vec![b'a'; MAX_LINE_LENGTH + 1]);
+
+		match stream.read(&mut [0; 16])
+		{
+			Ok(read) => assert_eq!(read, 0, "the server answered an overlong line"),
+			Err(e) => assert!(e.kind() != io::ErrorKind::WouldBlock && e.kind() != io::ErrorKind::TimedOut, "the connection was kept open"),
+		}
+
+		// Without the cap the connection would only be dropped once the join timeout ran out.
+		assert!(started.elapsed() < JOIN_TIMEOUT);
+	}
+
+	#[test]
+	fn rejected_actions_dont_extend_the_turn()
+	{
+		let turn_timeout = Duration::from_millis(500);
+		let (address, server) = start_server(turn_timeout);
+
+		let player = TestClient::join(&address, "player");
+		let player = thread::spawn(move || play_legal_cards(player));
+
+		// Keeps sending a card that is never legal, which the old per-line timeout let go on forever.
+		let mut stalling = TestClient::join(&address, "stalling");
+		let started = Instant::now();
+		let mut turns = 0;
+
+		loop
+		{
+			assert!(started.elapsed() < turn_timeout * 20, "the stalling player kept their turn");
+
+			// A replaced player's connection is closed, so sending can fail once the turn is over.
+			match stalling.read()
+			{
+				Some(ServerMessage::YourTurn{..}) =>
+				{
+					turns += 1;
+					let _ = stalling.send(&ClientMessage::Action{card: Card::CardMax});
+				}
+				Some(ServerMessage::ActionRejected{..}) =>
+				{
+					thread::sleep(Duration::from_millis(50));
+					let _ = stalling.send(&ClientMessage::Action{card: Card::CardMax});
+				}
+				Some(ServerMessage::GameOver{..}) | None => break,
+				Some(_) => (),
+			}
+		}
+
+		player.join().unwrap();
+		server.join().unwrap().unwrap();
+
+		// The stalling player was replaced after their first turn.
+		assert_eq!(turns, 1);
+	}
+}
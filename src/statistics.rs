@@ -0,0 +1,208 @@
+// Helpers for summarising the results of many games.
+
+// The z score used for 95% confidence intervals.
+pub const Z_95: f64 = 1.959964;
+
+pub fn mean(values: &[f64]) -> f64
+{
+	if values.is_empty()
+	{
+		return 0.0;
+	}
+
+	values.iter().sum::<f64>() / values.len() as f64
+}
+
+// The sample standard deviation of the values.
+pub fn standard_deviation(values: &[f64]) -> f64
+{
+	if values.len() < 2
+	{
+		return 0.0;
+	}
+
+	let average = mean(values);
+	let sum_of_squares: f64 = values.iter().map(|x| (x - average) * (x - average)).sum();
+
+	(sum_of_squares / (values.len() - 1) as f64).sqrt()
+}
+
+// Returns the lower and upper bound of the 95% confidence interval of the mean, using the normal approximation.
+pub fn mean_confidence_interval(values: &[f64]) -> (f64, f64)
+{
+	let average = mean(values);
+
+	if values.len() < 2
+	{
+		return (average, average);
+	}
+
+	let half_width = Z_95 * standard_deviation(values) / (values.len() as f64).sqrt();
+
+	(average - half_width, average + half_width)
+}
+
+// Returns the lower and upper bound of the 95% Wilson score interval for a proportion. Unlike the normal
+// approximation this behaves sensibly for win rates close to 0 or 1 and for small numbers of games.
+pub fn wilson_interval(successes: f64, trials: f64) -> (f64, f64)
+{
+	if trials <= 0.0
+	{
+		return (0.0, 1.0);
+	}
+
+	let p = successes / trials;
+	let z2 = Z_95 * Z_95;
+
+	let denominator = 1.0 + z2 / trials;
+	let centre = (p + z2 / (2.0 * trials)) / denominator;
+	let half_width = (Z_95 / denominator) * ((p * (1.0 - p) / trials) + (z2 / (4.0 * trials * trials))).sqrt();
+
+	((centre - half_width).max(0.0), (centre + half_width).min(1.0))
+}
+
+// Converts an Elo difference into the expected score of the stronger side.
+pub fn elo_to_score(elo: f64) -> f64
+{
+	1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+// Converts an expected score into an Elo difference. Scores of 0 or 1 would be infinite, so they are clamped.
+pub fn score_to_elo(score: f64) -> f64
+{
+	let score = score.clamp(0.001, 0.999);
+
+	-400.0 * (1.0 / score - 1.0).log10()
+}
+
+// The outcome of a sequential probability ratio test.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum SprtResult
+{
+	// Not enough evidence yet either way.
+	Continue,
+
+	// The true Elo difference is elo0 or less.
+	AcceptH0,
+
+	// The true Elo difference is elo1 or more.
+	AcceptH1,
+}
+
+// A sequential probability ratio test between two Elo differences. This lets a match stop as soon as there is
+// enough evidence, rather than playing a fixed number of games.
+#[derive(Copy,Clone,Debug)]
+pub struct SprtParameters
+{
+	pub elo0: f64,
+	pub elo1: f64,
+
+	// The chance of accepting H1 when H0 is true.
+	pub alpha: f64,
+
+	// The chance of accepting H0 when H1 is true.
+	pub beta: f64,
+}
+
+impl Default for SprtParameters
+{
+	fn default() -> SprtParameters
+	{
+		SprtParameters{elo0: -20.0, elo1: 20.0, alpha: 0.05, beta: 0.05}
+	}
+}
+
+impl SprtParameters
+{
+	// Parses the hypotheses written as '<elo0>,<elo1>', using the default error rates.
+	pub fn from_bounds(bounds: &str) -> Option<SprtParameters>
+	{
+		let values: Vec<Result<f64, _>> = bounds.trim().split(',').map(|x| x.trim().parse::<f64>()).collect();
+
+		match values.as_slice()
+		{
+			[Ok(elo0), Ok(elo1)] if elo0 < elo1 => Some(SprtParameters{elo0: *elo0, elo1: *elo1, ..SprtParameters::default()}),
+			_ => None,
+		}
+	}
+
+	// Returns the log likelihood ratio below which H0 is accepted and above which H1 is accepted.
+	pub fn get_bounds(&self) -> (f64, f64)
+	{
+		((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+	}
+
+	// Returns the log likelihood ratio of H1 against H0. Only decisive results count, draws carry no information
+	// about which of the two hypotheses is true.
+	pub fn get_llr(&self, wins: f64, losses: f64) -> f64
+	{
+		let p0 = elo_to_score(self.elo0);
+		let p1 = elo_to_score(self.elo1);
+
+		wins * (p1 / p0).ln() + losses * ((1.0 - p1) / (1.0 - p0)).ln()
+	}
+
+	pub fn test(&self, wins: f64, losses: f64) -> SprtResult
+	{
+		let (lower, upper) = self.get_bounds();
+		let llr = self.get_llr(wins, losses);
+
+		if llr >= upper
+		{
+			SprtResult::AcceptH1
+		}
+		else if llr <= lower
+		{
+			SprtResult::AcceptH0
+		}
+		else
+		{
+			SprtResult::Continue
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn assert_close(actual: (f64, f64), expected: (f64, f64))
+	{
+		assert!((actual.0 - expected.0).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+		assert!((actual.1 - expected.1).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+	}
+
+	#[test]
+	fn wilson_interval_matches_known_values()
+	{
+		assert_close(wilson_interval(0.0, 10.0), (0.0, 0.27753));
+		assert_close(wilson_interval(10.0, 10.0), (0.72247, 1.0));
+		assert_close(wilson_interval(50.0, 100.0), (0.40383, 0.59617));
+	}
+
+	#[test]
+	fn wilson_interval_without_trials_is_uninformative()
+	{
+		assert_eq!(wilson_interval(0.0, 0.0), (0.0, 1.0));
+	}
+
+	#[test]
+	fn mean_confidence_interval_matches_known_values()
+	{
+		assert_close(mean_confidence_interval(&[1.0, 2.0, 3.0, 4.0, 5.0]), (1.61410, 4.38590));
+
+		// A single value says nothing about the spread.
+		assert_eq!(mean_confidence_interval(&[2.5]), (2.5, 2.5));
+	}
+
+	#[test]
+	fn sprt_decides_on_clearly_separated_win_streams()
+	{
+		let sprt = SprtParameters::default();
+
+		assert_eq!(sprt.test(100.0, 0.0), SprtResult::AcceptH1);
+		assert_eq!(sprt.test(0.0, 100.0), SprtResult::AcceptH0);
+		assert_eq!(sprt.test(5.0, 5.0), SprtResult::Continue);
+	}
+}
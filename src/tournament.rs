@@ -0,0 +1,399 @@
+use super::neural_network_game::*;
+use super::neural_network_game::game::*;
+use super::neural_network_game::neural_network::*;
+use super::neural_network_game::policy::*;
+use super::neural_network_game::external_bot::*;
+use super::statistics::*;
+use super::rating::*;
+
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+// How tables of players are drawn up for a tournament.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum TournamentFormat
+{
+	// Every combination of entrants plays at a table together.
+	RoundRobin,
+
+	// Entrants are grouped with those on similar points for the given number of rounds.
+	Swiss(usize),
+}
+
+impl fmt::Display for TournamentFormat
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match *self
+		{
+			TournamentFormat::RoundRobin => write!(f, "round_robin"),
+			TournamentFormat::Swiss(rounds) => write!(f, "swiss={}", rounds),
+		}
+	}
+}
+
+impl TournamentFormat
+{
+	// Parses a format written as 'round_robin' or 'swiss=<rounds>'.
+	pub fn from_name(name: &str) -> Option<TournamentFormat>
+	{
+		let mut parts = name.trim().splitn(2, '=');
+
+		match (parts.next(), parts.next().map(|x| x.trim().parse::<usize>()))
+		{
+			(Some("round_robin"), None) => Some(TournamentFormat::RoundRobin),
+			(Some("swiss"), Some(Ok(rounds))) if rounds > 0 => Some(TournamentFormat::Swiss(rounds)),
+			_ => None,
+		}
+	}
+}
+
+pub struct TournamentEntrant
+{
+	// The name shown in the standings, entrants who share an agent are numbered to tell them apart.
+	pub name: String,
+
+	// The player's own name is used for ratings.
+	pub player: NeuralNetworkGamePlayer,
+}
+
+// Entrants which start with this are bots in other programs, the rest of the name is the command which runs them.
+pub const EXTERNAL_BOT_PREFIX: &str = "bot:";
+
+#[derive(Debug)]
+pub enum EntrantError
+{
+	Network(NeuralNetworkFileError),
+	Bot(ExternalBotError),
+}
+
+impl fmt::Display for EntrantError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			EntrantError::Network(e) => write!(f, "{}", e),
+			EntrantError::Bot(e) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl Error for EntrantError {}
+
+impl From<NeuralNetworkFileError> for EntrantError
+{
+	fn from(e: NeuralNetworkFileError) -> EntrantError
+	{
+		EntrantError::Network(e)
+	}
+}
+
+impl From<ExternalBotError> for EntrantError
+{
+	fn from(e: ExternalBotError) -> EntrantError
+	{
+		EntrantError::Bot(e)
+	}
+}
+
+// Creates an entrant from the name of a built in bot, the command for a bot in another program after "bot:", or the
+// file name of a saved network.
+pub fn load_entrant(name: &str, policy: PolicyMode) -> Result<TournamentEntrant, EntrantError>
+{
+	let name = name.trim();
+
+	match name
+	{
+		"random" => Ok(TournamentEntrant{name: name.to_string(), player: NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Random, empty_neural_network())}),
+		"heuristic" => Ok(TournamentEntrant{name: name.to_string(), player: NeuralNetworkGamePlayer::new(NeuralNetworkGamePlayerType::Heuristic, empty_neural_network())}),
+		_ if name.starts_with(EXTERNAL_BOT_PREFIX) =>
+		{
+			let bot = ExternalBot::spawn(&name[EXTERNAL_BOT_PREFIX.len()..], DEFAULT_MOVE_TIMEOUT)?;
+
+			// Bots are rated by their command like networks are by their file name, as any bot can call itself anything.
+			let mut player = NeuralNetworkGamePlayer::external(bot);
+			player.name = name.to_string();
+
+			Ok(TournamentEntrant{name: name.to_string(), player})
+		}
+		_ =>
+		{
+			let nn = load_nn_from_file(name)?;
+
+			let mut player = NeuralNetworkGamePlayer::with_policy(NeuralNetworkGamePlayerType::NeuralNetwork, nn, policy);
+			player.name = name.to_string();
+
+			Ok(TournamentEntrant{name: name.to_string(), player})
+		}
+	}
+}
+
+// The results an entrant has collected over the tournament.
+pub struct EntrantResult
+{
+	pub name: String,
+	pub games: usize,
+
+	// Wins are shared between players who tie for first.
+	pub wins: f64,
+
+	// Placement points, 3 for first down to 0 for last, shared between tied players.
+	pub points: f64,
+
+	pub scores: Vec<f64>,
+
+	// How many rounds of a swiss tournament the entrant sat out.
+	pub byes: usize,
+}
+
+impl EntrantResult
+{
+	fn new(name: &str) -> EntrantResult
+	{
+		EntrantResult{name: name.to_string(), games: 0, wins: 0.0, points: 0.0, scores: Vec::new(), byes: 0}
+	}
+
+	fn add_game(&mut self, score: i32, win: f64, points: f64)
+	{
+		self.games += 1;
+		self.wins += win;
+		self.points += points;
+		self.scores.push(score as f64);
+	}
+
+	pub fn get_win_rate(&self) -> f64
+	{
+		if self.games == 0 { 0.0 } else { self.wins / self.games as f64 }
+	}
+
+	pub fn get_win_rate_interval(&self) -> (f64, f64)
+	{
+		wilson_interval(self.wins, self.games as f64)
+	}
+
+	pub fn get_mean_score(&self) -> f64
+	{
+		mean(&self.scores)
+	}
+
+	pub fn get_mean_score_interval(&self) -> (f64, f64)
+	{
+		mean_confidence_interval(&self.scores)
+	}
+}
+
+// Works out each seat's share of the win and its placement points from the final scores.
+pub fn get_placements(scores: &[i32]) -> (Vec<f64>, Vec<f64>)
+{
+	let mut wins = Vec::new();
+	let mut points = Vec::new();
+
+	for x in scores
+	{
+		let better = scores.iter().filter(|y| *y > x).count();
+		let tied = scores.iter().filter(|y| *y == x).count();
+
+		wins.push(if better == 0 { 1.0 / tied as f64 } else { 0.0 });
+
+		// Tied players share the points for the places they cover.
+		let place_points: usize = (better..better + tied).map(|place| scores.len() - 1 - place).sum();
+		points.push(place_points as f64 / tied as f64);
+	}
+
+	(wins, points)
+}
+
+// Plays a number of seeded games at a table, rotating which seat each entrant sits in every game.
+fn play_table(entrants: &[TournamentEntrant], table: &[usize], number_of_games: usize, first_seed: u64, results: &mut [EntrantResult], ratings: &mut RatingsTable)
+{
+	let mut games = Vec::new();
+
+	for i in 0..number_of_games
+	{
+		let mut game = SushiGoGame::new(NUMBER_OF_PLAYERS);
+		game.setup_with_seed(first_seed.wrapping_add(i as u64));
+
+		let players = (0..NUMBER_OF_PLAYERS).map(|seat| entrants[table[(seat + i) % table.len()]].player.clone()).collect();
+
+		games.push(NeuralNetworkGame::new(game, players));
+	}
+
+	play_games_parallel(&mut games);
+
+	for (i, game) in games.iter().enumerate()
+	{
+		let scores = game.get_game().get_final_scores();
+		let (wins, points) = get_placements(&scores);
+
+		ratings.update_from_game(&game.get_player_names(), &scores);
+
+		for seat in 0..NUMBER_OF_PLAYERS
+		{
+			results[table[(seat + i) % table.len()]].add_game(scores[seat], wins[seat], points[seat]);
+		}
+	}
+}
+
+// Returns every way of picking a table of players from the entrants. If there are fewer entrants than seats
+// they all sit at one table and take turns filling the spare seats.
+fn get_round_robin_tables(number_of_entrants: usize) -> Vec<Vec<usize>>
+{
+	if number_of_entrants <= NUMBER_OF_PLAYERS
+	{
+		return vec![(0..NUMBER_OF_PLAYERS).map(|seat| seat % number_of_entrants).collect()];
+	}
+
+	let mut tables = Vec::new();
+	let mut table: Vec<usize> = (0..NUMBER_OF_PLAYERS).collect();
+
+	// Step through the combinations in lexicographic order.
+	loop
+	{
+		tables.push(table.clone());
+
+		let mut i = NUMBER_OF_PLAYERS;
+
+		while i > 0 && table[i - 1] == number_of_entrants - NUMBER_OF_PLAYERS + i - 1
+		{
+			i -= 1;
+		}
+
+		if i == 0
+		{
+			return tables;
+		}
+
+		table[i - 1] += 1;
+
+		for j in i..NUMBER_OF_PLAYERS
+		{
+			table[j] = table[j - 1] + 1;
+		}
+	}
+}
+
+// Groups entrants with similar points together. Entrants who don't fill a table sit the round out, choosing
+// those who have had the fewest byes and then the fewest points.
+fn get_swiss_tables(results: &[EntrantResult]) -> Vec<Vec<usize>>
+{
+	let mut standings: Vec<usize> = (0..results.len()).collect();
+	standings.sort_by(|a, b| results[*b].points.partial_cmp(&results[*a].points).unwrap());
+
+	if results.len() <= NUMBER_OF_PLAYERS
+	{
+		return get_round_robin_tables(results.len());
+	}
+
+	let number_of_byes = results.len() % NUMBER_OF_PLAYERS;
+
+	let mut bye_order = standings.clone();
+	bye_order.sort_by(|a, b| results[*a].byes.cmp(&results[*b].byes).then(results[*a].points.partial_cmp(&results[*b].points).unwrap()));
+
+	let byes: Vec<usize> = bye_order.into_iter().take(number_of_byes).collect();
+
+	let seated: Vec<usize> = standings.into_iter().filter(|x| !byes.contains(x)).collect();
+
+	seated.chunks(NUMBER_OF_PLAYERS).map(|x| x.to_vec()).collect()
+}
+
+// Plays out a tournament between the entrants and returns their results in the same order as the entrants.
+pub fn run_tournament(entrants: &[TournamentEntrant], format: TournamentFormat, games_per_table: usize, seed: u64, ratings: &mut RatingsTable) -> Vec<EntrantResult>
+{
+	assert!(!entrants.is_empty(), "A tournament needs at least one entrant!");
+
+	let started = Instant::now();
+
+	let mut results: Vec<EntrantResult> = entrants.iter().map(|x| EntrantResult::new(&x.name)).collect();
+
+	// Every game in the tournament gets its own seed so the whole tournament can be repeated.
+	let mut next_seed = seed;
+
+	let rounds = match format
+	{
+		TournamentFormat::RoundRobin => 1,
+		TournamentFormat::Swiss(rounds) => rounds,
+	};
+
+	for round in 0..rounds
+	{
+		let tables = match format
+		{
+			TournamentFormat::RoundRobin => get_round_robin_tables(entrants.len()),
+			TournamentFormat::Swiss(_) => get_swiss_tables(&results),
+		};
+
+		// Keep track of who sat out this round.
+		for (i, x) in results.iter_mut().enumerate()
+		{
+			if !tables.iter().any(|table| table.contains(&i))
+			{
+				x.byes += 1;
+			}
+		}
+
+		for table in tables.iter()
+		{
+			debug!("Round {}: playing {} games at a table of {:?}", round + 1, games_per_table, table.iter().map(|x| &entrants[*x].name).collect::<Vec<&String>>());
+
+			play_table(entrants, table, games_per_table, next_seed, &mut results, ratings);
+			next_seed = next_seed.wrapping_add(games_per_table as u64);
+		}
+
+		info!("Finished round {} of {} with {} tables in {}", round + 1, rounds, tables.len(), sec_from_time(started));
+	}
+
+	results
+}
+
+pub fn print_standings(results: &[EntrantResult])
+{
+	let mut standings: Vec<&EntrantResult> = results.iter().collect();
+	standings.sort_by(|a, b| b.get_win_rate().partial_cmp(&a.get_win_rate()).unwrap().then(b.points.partial_cmp(&a.points).unwrap()));
+
+	println!("\n{:<4} {:<40} {:>6} {:>8} {:>15} {:>7} {:>15}", "Rank", "Entrant", "Games", "Win %", "Win % 95% CI", "Score", "Score 95% CI");
+
+	for (i, x) in standings.iter().enumerate()
+	{
+		let (win_low, win_high) = x.get_win_rate_interval();
+		let (score_low, score_high) = x.get_mean_score_interval();
+
+		let win_interval = format!("{:.2}-{:.2}", win_low * 100.0, win_high * 100.0);
+		let score_interval = format!("{:.2}-{:.2}", score_low, score_high);
+
+		println!("{:<4} {:<40} {:>6} {:>8.2} {:>15} {:>7.2} {:>15}", i + 1, x.name, x.games, x.get_win_rate() * 100.0, win_interval, x.get_mean_score(), score_interval);
+	}
+}
+
+// Loads every entrant by name, numbering entrants who share a name so they can be told apart in the standings.
+// Returns None if any of them couldn't be loaded.
+pub fn load_entrants(names: &[&str], policy: PolicyMode) -> Option<Vec<TournamentEntrant>>
+{
+	let mut entrants: Vec<TournamentEntrant> = Vec::new();
+
+	for name in names.iter().filter(|x| !x.trim().is_empty())
+	{
+		match load_entrant(name, policy)
+		{
+			Ok(mut entrant) =>
+			{
+				let copies = entrants.iter().filter(|x| x.name.split(" #").next() == Some(entrant.name.as_str())).count();
+
+				if copies > 0
+				{
+					entrant.name = format!("{} #{}", entrant.name, copies + 1);
+				}
+
+				entrants.push(entrant);
+			}
+			Err(e) =>
+			{
+				println!("Couldn't load {}: {}", name.trim(), e);
+				return None;
+			}
+		}
+	}
+
+	Some(entrants)
+}
@@ -0,0 +1,399 @@
+use super::neural_network_game::model::*;
+use super::neural_network_game::neural_network::*;
+use super::neural_network_game::policy::*;
+use super::metrics::*;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// The name the config is saved under in the output directory of a run.
+pub const TRAINING_CONFIG_FILE_NAME: &str = "training_config.toml";
+
+// TOML integers are signed 64 bit, so larger seeds would be saved in a config which can't be loaded again.
+pub const MAX_SEED: u64 = i64::MAX as u64;
+
+// Everything needed to reproduce a training run. Any value left out of a config file takes its default.
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrainingConfig
+{
+	// The name of the run, used for the output directory and in the ratings table. Generated from the time if empty.
+	pub name: String,
+
+	// The directory each run gets its own folder in.
+	pub output_dir: String,
+
+	// Seeds the networks, the deals, the breeding and the policies so a run can be repeated.
+	pub seed: Option<u64>,
+
+	// Which format the champion is saved in, 'json' or 'binary'.
+	pub save_format: String,
+
+	// How many generations between progress updates. Defaults to every 1% of the run.
+	pub progress_interval: Option<usize>,
+
+	pub model: ModelConfig,
+	pub genetic: GeneticConfig,
+	pub rating: RatingConfig,
+	pub metrics: MetricsConfig,
+	pub benchmark: BenchmarkConfig,
+}
+
+#[derive(Clone, Debug, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelConfig
+{
+	// Picks how the game is encoded for the network and how its outputs are decoded.
+	pub name: NeuralNetworkModel,
+
+	// Overrides the number of hidden nodes the model normally uses.
+	pub hidden_nodes: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneticConfig
+{
+	// The population is this many games worth of players.
+	pub games_per_generation: usize,
+
+	pub generations: usize,
+
+	// How far each weight and bias can move when a network is mutated.
+	pub mutate_amount: f32,
+
+	// How the networks pick their cards while training, 'argmax', 'temperature=<t>' or 'epsilon=<e>'.
+	pub policy: String,
+}
+
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RatingConfig
+{
+	// How many times over the run the champion's rating is measured.
+	pub count: usize,
+
+	// How many games the champion plays each time it is rated.
+	pub games: usize,
+
+	// Who the champion plays against, either 'random', 'heuristic' or the file name of a saved network. They take turns filling the
+	// other seats.
+	pub opponents: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig
+{
+	// The format the metrics of each generation are written in, 'csv' or 'jsonl'.
+	pub format: String,
+
+	// Prints the metrics in the terminal every progress interval rather than just how far through the run it is.
+	pub live_summary: bool,
+}
+
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchmarkConfig
+{
+	// How many generations between benchmarking the champion, 0 turns the benchmark off.
+	pub interval: usize,
+
+	// How many games the champion plays against each member of the panel every time it is benchmarked.
+	pub games: usize,
+
+	// The seed of the first benchmark deal. The same deals are used every time so benchmarks can be compared.
+	pub seed: u64,
+
+	// The fixed members of the panel, either 'random', 'heuristic' or the file name of a saved network.
+	pub opponents: Vec<String>,
+
+	// How many of the most recent benchmarked champions join the panel, so each champion is compared to its predecessors.
+	pub checkpoints: usize,
+
+	// Also saves the champion which did best against the fixed opponents, as the last one isn't always the best.
+	pub keep_best: bool,
+}
+
+impl Default for TrainingConfig
+{
+	fn default() -> TrainingConfig
+	{
+		TrainingConfig
+		{
+			name: String::new(),
+			output_dir: "runs".to_string(),
+			seed: None,
+			save_format: SaveFormat::default().to_string(),
+			progress_interval: None,
+			model: ModelConfig::default(),
+			genetic: GeneticConfig::default(),
+			rating: RatingConfig::default(),
+			metrics: MetricsConfig::default(),
+			benchmark: BenchmarkConfig::default(),
+		}
+	}
+}
+
+impl Default for GeneticConfig
+{
+	fn default() -> GeneticConfig
+	{
+		GeneticConfig{games_per_generation: 64, generations: 1000, mutate_amount: MUTATE_AMOUNT, policy: PolicyMode::default().to_string()}
+	}
+}
+
+impl Default for RatingConfig
+{
+	fn default() -> RatingConfig
+	{
+		RatingConfig{count: 10, games: 100, opponents: vec!["random".to_string()]}
+	}
+}
+
+impl Default for MetricsConfig
+{
+	fn default() -> MetricsConfig
+	{
+		MetricsConfig{format: MetricsFormat::default().to_string(), live_summary: false}
+	}
+}
+
+impl Default for BenchmarkConfig
+{
+	fn default() -> BenchmarkConfig
+	{
+		BenchmarkConfig{interval: 10, games: 100, seed: 0, opponents: vec!["random".to_string(), "heuristic".to_string()], checkpoints: 2, keep_best: false}
+	}
+}
+
+#[derive(Debug)]
+pub enum TrainingConfigError
+{
+	Io(io::Error),
+	Parse(toml::de::Error),
+	Serialize(toml::ser::Error),
+
+	// The config parsed but one of its values can't be used.
+	Invalid(String),
+}
+
+impl fmt::Display for TrainingConfigError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self
+		{
+			TrainingConfigError::Io(e) => write!(f, "couldn't access the config: {}", e),
+			TrainingConfigError::Parse(e) => write!(f, "the config is not valid TOML: {}", e),
+			TrainingConfigError::Serialize(e) => write!(f, "couldn't write the config: {}", e),
+			TrainingConfigError::Invalid(e) => write!(f, "invalid config: {}", e),
+		}
+	}
+}
+
+impl Error for TrainingConfigError {}
+
+impl From<io::Error> for TrainingConfigError
+{
+	fn from(e: io::Error) -> TrainingConfigError
+	{
+		TrainingConfigError::Io(e)
+	}
+}
+
+impl From<toml::de::Error> for TrainingConfigError
+{
+	fn from(e: toml::de::Error) -> TrainingConfigError
+	{
+		TrainingConfigError::Parse(e)
+	}
+}
+
+impl From<toml::ser::Error> for TrainingConfigError
+{
+	fn from(e: toml::ser::Error) -> TrainingConfigError
+	{
+		TrainingConfigError::Serialize(e)
+	}
+}
+
+impl TrainingConfig
+{
+	// Checks every value can be used, so a run doesn't fail part way through.
+	pub fn validate(&self) -> Result<(), TrainingConfigError>
+	{
+		let invalid = |message: String| Err(TrainingConfigError::Invalid(message));
+
+		if self.genetic.games_per_generation == 0
+		{
+			return invalid("genetic.games_per_generation must be at least 1".to_string());
+		}
+
+		if self.genetic.generations == 0
+		{
+			return invalid("genetic.generations must be at least 1".to_string());
+		}
+
+		if self.genetic.mutate_amount.is_nan() || self.genetic.mutate_amount <= 0.0
+		{
+			return invalid(format!("genetic.mutate_amount must be above 0, not {}", self.genetic.mutate_amount));
+		}
+
+		if PolicyMode::from_name(&self.genetic.policy).is_none()
+		{
+			return invalid(format!("genetic.policy '{}' isn't a policy, use argmax, temperature=<t> or epsilon=<e>", self.genetic.policy));
+		}
+
+		if SaveFormat::from_name(&self.save_format).is_none()
+		{
+			return invalid(format!("save_format '{}' isn't a save format, use json or binary", self.save_format));
+		}
+
+		if self.model.hidden_nodes == Some(0)
+		{
+			return invalid("model.hidden_nodes must be at least 1".to_string());
+		}
+
+		if self.progress_interval == Some(0)
+		{
+			return invalid("progress_interval must be at least 1".to_string());
+		}
+
+		if self.rating.count > 0 && (self.rating.games == 0 || self.rating.opponents.is_empty())
+		{
+			return invalid("rating needs at least one game and one opponent, or a count of 0 to turn rating off".to_string());
+		}
+
+		if MetricsFormat::from_name(&self.metrics.format).is_none()
+		{
+			return invalid(format!("metrics.format '{}' isn't a metrics format, use csv or jsonl", self.metrics.format));
+		}
+
+		if self.benchmark.interval > 0 && (self.benchmark.games == 0 || (self.benchmark.opponents.is_empty() && self.benchmark.checkpoints == 0))
+		{
+			return invalid("benchmark needs at least one game and one opponent or checkpoint, or an interval of 0 to turn benchmarking off".to_string());
+		}
+
+		if self.seed.unwrap_or(0) > MAX_SEED || self.benchmark.seed > MAX_SEED
+		{
+			return invalid(format!("seeds can't be larger than {}", MAX_SEED));
+		}
+
+		if self.benchmark.keep_best && (self.benchmark.interval == 0 || self.benchmark.opponents.is_empty())
+		{
+			return invalid("benchmark.keep_best needs the benchmark to be turned on with at least one opponent".to_string());
+		}
+
+		Ok(())
+	}
+
+	pub fn get_metrics_format(&self) -> MetricsFormat
+	{
+		MetricsFormat::from_name(&self.metrics.format).unwrap_or_default()
+	}
+
+	pub fn get_policy(&self) -> PolicyMode
+	{
+		PolicyMode::from_name(&self.genetic.policy).unwrap_or_default()
+	}
+
+	pub fn get_save_format(&self) -> SaveFormat
+	{
+		SaveFormat::from_name(&self.save_format).unwrap_or_default()
+	}
+
+	// Returns the node counts of the networks being trained.
+	pub fn get_node_count(&self) -> NeuralNetworkProperties
+	{
+		let mut properties = self.model.name.get_node_count();
+
+		if let Some(hidden_nodes) = self.model.hidden_nodes
+		{
+			properties.hidden_node_count = hidden_nodes;
+		}
+
+		properties
+	}
+
+	pub fn get_progress_interval(&self) -> usize
+	{
+		self.progress_interval.unwrap_or(std::cmp::max(1, self.genetic.generations / 100))
+	}
+
+	pub fn get_run_directory(&self) -> PathBuf
+	{
+		Path::new(&self.output_dir).join(&self.name)
+	}
+
+	// Creates the directory for the run and saves the config in it, so the run can be traced back to how it was made.
+	pub fn create_run_directory(&self) -> Result<PathBuf, TrainingConfigError>
+	{
+		let directory = self.get_run_directory();
+
+		fs::create_dir_all(&directory)?;
+
+		let mut file = File::create(directory.join(TRAINING_CONFIG_FILE_NAME))?;
+		file.write_all(toml::to_string_pretty(self)?.as_bytes())?;
+
+		Ok(directory)
+	}
+}
+
+pub fn load_training_config(file_name: &str) -> Result<TrainingConfig, TrainingConfigError>
+{
+	let mut data = String::new();
+	File::open(Path::new(file_name))?.read_to_string(&mut data)?;
+
+	let config: TrainingConfig = toml::from_str(&data)?;
+	config.validate()?;
+
+	Ok(config)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn saved_config_loads_again()
+	{
+		let config = TrainingConfig
+		{
+			name: format!("config_round_trip_{}", std::process::id()),
+			output_dir: std::env::temp_dir().to_string_lossy().into_owned(),
+			seed: Some(MAX_SEED),
+			benchmark: BenchmarkConfig{seed: MAX_SEED, ..Default::default()},
+			..Default::default()
+		};
+
+		let directory = config.create_run_directory().unwrap();
+		let loaded = load_training_config(&directory.join(TRAINING_CONFIG_FILE_NAME).to_string_lossy());
+		fs::remove_dir_all(&directory).unwrap();
+		let loaded = loaded.unwrap();
+
+		assert_eq!(loaded.seed, Some(MAX_SEED));
+		assert_eq!(loaded.benchmark.seed, MAX_SEED);
+		assert_eq!(toml::to_string(&loaded).unwrap(), toml::to_string(&config).unwrap());
+	}
+
+	#[test]
+	fn seeds_toml_cannot_hold_are_rejected()
+	{
+		let config = TrainingConfig{seed: Some(MAX_SEED + 1), ..Default::default()};
+
+		assert!(config.validate().is_err());
+	}
+}